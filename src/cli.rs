@@ -0,0 +1,711 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, StartupItem};
+use crate::operations::{
+    apply_edit, create_item, delay_item, delete_profile, enforce_policy, get_backup_dir,
+    list_backups, list_delayed, list_profiles, load_profile, prune_backups, restore_backup,
+    save_profile, switch_profile, undelay_item, BackupSettings, ChangeOutcome, Compression,
+    DelayLink, Drift, ItemEdit, NewItem, NewItemKind, Policy, RetentionPolicy, SnapshotDiff,
+    TransactionMode, DEFAULT_DELAY_SECS,
+};
+use crate::sources::scan_all_sources;
+
+/// Snapshot name for the current state of the system
+pub const LIVE: &str = "live";
+
+/// Snapshot name for the newest backup
+pub const LATEST: &str = "latest";
+
+/// Exit code of `check` and `enforce` when items differ from the policy
+pub const DRIFT_EXIT_CODE: i32 = 2;
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Inspect and manage programs that run at startup")]
+pub struct Cli {
+    /// Without a command the interactive interface starts
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Show what changed between two backups, or a backup and the live system
+    Diff(DiffArgs),
+
+    /// Delete backups the retention policy in the backup settings no longer keeps
+    Prune,
+
+    /// Show or change how backups are compressed and pruned
+    BackupSettings(BackupSettingsArgs),
+
+    /// Register a program to run at logon
+    Add(AddArgs),
+
+    /// Rename an item or change its command line
+    Edit(EditArgs),
+
+    /// Start an item some time after logon instead of right away
+    Delay(DelayArgs),
+
+    /// Start a delayed item at logon again
+    Undelay(UndelayArgs),
+
+    /// Save or switch between named sets of enabled and disabled items
+    #[command(subcommand)]
+    Profile(ProfileCommand),
+
+    /// Report where startup items differ from a policy file
+    Check(CheckArgs),
+
+    /// Bring startup items in line with a policy file
+    Enforce(EnforceArgs),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ProfileCommand {
+    /// List saved profiles
+    List,
+
+    /// Save the current status of every item as a profile
+    Save {
+        /// Name of the profile, replaced if it exists
+        name: String,
+    },
+
+    /// Enable and disable items to match a profile
+    Switch(SwitchArgs),
+
+    /// Delete a saved profile
+    Delete {
+        /// Name of the profile
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// Older snapshot: a backup file, "latest" or "live"
+    #[arg(default_value = LATEST, conflicts_with = "since")]
+    pub from: String,
+
+    /// Newer snapshot: a backup file, "latest" or "live"
+    #[arg(default_value = LIVE)]
+    pub to: String,
+
+    /// Compare from the newest backup at least this many days old
+    #[arg(long, value_name = "DAYS")]
+    pub since: Option<u32>,
+
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+/// How new backups are written
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CompressionKind {
+    /// Plain JSON
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl From<CompressionKind> for Compression {
+    fn from(kind: CompressionKind) -> Self {
+        match kind {
+            CompressionKind::None => Compression::None,
+            CompressionKind::Gzip => Compression::Gzip,
+            CompressionKind::Zstd => Compression::Zstd,
+        }
+    }
+}
+
+/// Without options the current settings are printed
+#[derive(Debug, Args)]
+pub struct BackupSettingsArgs {
+    #[arg(long, value_enum)]
+    pub compression: Option<CompressionKind>,
+
+    /// Keep this many of the newest backups; turns pruning on
+    #[arg(long, value_name = "COUNT")]
+    pub keep_last: Option<usize>,
+
+    /// Keep the newest backup of this many days; turns pruning on
+    #[arg(long, value_name = "COUNT")]
+    pub keep_daily: Option<usize>,
+
+    /// Keep the newest backup of this many weeks; turns pruning on
+    #[arg(long, value_name = "COUNT")]
+    pub keep_weekly: Option<usize>,
+
+    /// Limit for all backups together, 0 for none; turns pruning on
+    #[arg(long, value_name = "BYTES")]
+    pub max_total_size: Option<u64>,
+
+    /// Never delete backups
+    #[arg(
+        long,
+        conflicts_with_all = ["keep_last", "keep_daily", "keep_weekly", "max_total_size"]
+    )]
+    pub no_retention: bool,
+}
+
+/// Where `add` registers the program
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum AddKind {
+    /// A value in the Run key
+    #[default]
+    Run,
+    /// A value in the RunOnce key, removed after it runs once
+    RunOnce,
+    /// A shortcut in the Startup folder
+    StartupFolder,
+    /// A scheduled task with a logon trigger
+    LogonTask,
+}
+
+impl From<AddKind> for NewItemKind {
+    fn from(kind: AddKind) -> Self {
+        match kind {
+            AddKind::Run => NewItemKind::Run,
+            AddKind::RunOnce => NewItemKind::RunOnce,
+            AddKind::StartupFolder => NewItemKind::StartupFolder,
+            AddKind::LogonTask => NewItemKind::LogonTask,
+        }
+    }
+}
+
+#[derive(Debug, Args)]
+pub struct AddArgs {
+    /// Name of the registry value, shortcut or task; the program's file name by default
+    #[arg(long)]
+    pub name: Option<String>,
+
+    #[arg(long, value_enum, default_value_t)]
+    pub kind: AddKind,
+
+    /// Register for every user instead of the current one; needs administrator rights
+    #[arg(long)]
+    pub all_users: bool,
+
+    /// Absolute path of the program
+    pub target: PathBuf,
+
+    /// Arguments passed to the program, quoted as needed
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct EditArgs {
+    /// Id or name of the item
+    pub item: String,
+
+    /// New name
+    #[arg(long)]
+    pub name: Option<String>,
+
+    /// New command line, with the program quoted if its path has spaces
+    #[arg(long)]
+    pub command: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct DelayArgs {
+    /// Id or name of the item
+    pub item: String,
+
+    /// Seconds to wait after logon; not used for services
+    #[arg(long, default_value_t = DEFAULT_DELAY_SECS)]
+    pub seconds: u32,
+}
+
+#[derive(Debug, Args)]
+pub struct UndelayArgs {
+    /// Id or name of the delayed item or of its task
+    pub item: String,
+}
+
+#[derive(Debug, Args)]
+pub struct SwitchArgs {
+    /// Name of the profile
+    pub name: String,
+
+    /// Keep the changes that worked when one fails, instead of undoing them all
+    #[arg(long)]
+    pub best_effort: bool,
+
+    /// Only print what would change
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    /// Policy file, TOML or YAML
+    pub policy: PathBuf,
+
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct EnforceArgs {
+    /// Policy file, TOML or YAML
+    pub policy: PathBuf,
+
+    /// Keep the changes that worked when one fails, instead of undoing them all
+    #[arg(long)]
+    pub best_effort: bool,
+
+    /// Only print what would change
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Items of a snapshot with a label for reports
+fn load_snapshot(spec: &str) -> Result<(String, Vec<StartupItem>)> {
+    if spec.eq_ignore_ascii_case(LIVE) {
+        let mut items = scan_all_sources();
+        for item in &mut items {
+            item.stat_file();
+        }
+        return Ok((LIVE.to_string(), items));
+    }
+
+    if spec.eq_ignore_ascii_case(LATEST) {
+        let entry = list_backups()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::BackupNotFound("no backups yet".to_string()))?;
+        return Ok((entry.file_name.clone(), entry.load()?.items));
+    }
+
+    // A path, or the name of a file in the backup directory
+    let mut path = PathBuf::from(spec);
+    if !path.exists() {
+        path = get_backup_dir()?.join(spec);
+    }
+    if !path.is_file() {
+        return Err(Error::BackupNotFound(spec.to_string()));
+    }
+
+    let backup = restore_backup(&path)?;
+    Ok((backup_label(&path), backup.items))
+}
+
+/// The newest backup taken at least `days` ago
+fn backup_since(days: u32) -> Result<(String, Vec<StartupItem>)> {
+    let cutoff = Utc::now() - Duration::days(days as i64);
+    let entry = list_backups()?
+        .into_iter()
+        .find(|entry| entry.timestamp <= cutoff)
+        .ok_or_else(|| Error::BackupNotFound(format!("no backup older than {days} days")))?;
+    Ok((entry.file_name.clone(), entry.load()?.items))
+}
+
+fn backup_label(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Run `diff` and print the report
+pub fn diff(args: &DiffArgs) -> Result<()> {
+    let (from, old) = match args.since {
+        Some(days) => backup_since(days)?,
+        None => load_snapshot(&args.from)?,
+    };
+    let (to, new) = load_snapshot(&args.to)?;
+
+    let diff = SnapshotDiff::between(&from, &old, &to, &new);
+    match args.format {
+        OutputFormat::Text => print!("{}", diff.to_text()),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&diff).map_err(Error::Serialization)?
+        ),
+    }
+
+    Ok(())
+}
+
+/// Run `prune` and print the deleted files
+pub fn prune() -> Result<()> {
+    let settings = BackupSettings::load()?;
+    let Some(policy) = settings.retention else {
+        println!("No retention policy in the backup settings, nothing deleted");
+        return Ok(());
+    };
+    let removed = prune_backups(&policy)?;
+    for path in &removed {
+        println!("Deleted {}", backup_label(path));
+    }
+    println!("{} backups deleted", removed.len());
+    Ok(())
+}
+
+/// Run `backup-settings`, saving any changes, and print the settings
+pub fn backup_settings(args: &BackupSettingsArgs) -> Result<()> {
+    let mut settings = BackupSettings::load()?;
+    let before = settings.clone();
+
+    if let Some(compression) = args.compression {
+        settings.compression = compression.into();
+    }
+    if args.no_retention {
+        settings.retention = None;
+    } else if args.keep_last.is_some()
+        || args.keep_daily.is_some()
+        || args.keep_weekly.is_some()
+        || args.max_total_size.is_some()
+    {
+        let retention = settings
+            .retention
+            .get_or_insert_with(RetentionPolicy::default);
+        retention.keep_last = args.keep_last.unwrap_or(retention.keep_last);
+        retention.keep_daily = args.keep_daily.unwrap_or(retention.keep_daily);
+        retention.keep_weekly = args.keep_weekly.unwrap_or(retention.keep_weekly);
+        retention.max_total_size = args.max_total_size.unwrap_or(retention.max_total_size);
+    }
+
+    if settings != before {
+        settings.save()?;
+        println!("Saved {}", BackupSettings::path()?.display());
+    }
+
+    println!("Compression: {}", settings.compression.display());
+    match &settings.retention {
+        Some(retention) => println!("Retention: {}", retention.summary()),
+        None => println!("Retention: off, backups are never deleted"),
+    }
+    Ok(())
+}
+
+/// Run `add` and print the created item
+pub fn add(args: &AddArgs) -> Result<()> {
+    let name = match &args.name {
+        Some(name) => name.clone(),
+        None => args
+            .target
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+
+    let new = NewItem::new(name, args.kind.into(), &args.target)
+        .with_arguments(args.args.clone())
+        .with_all_users(args.all_users);
+    let item = create_item(&new)?;
+
+    println!("Added {} to {}", item.name, item.source.display_name());
+    println!("Command: {}", new.command_line());
+    Ok(())
+}
+
+/// The live item with this id, or the only one with this name
+fn find_item(spec: &str) -> Result<StartupItem> {
+    let items = scan_all_sources();
+    if let Some(item) = items.iter().find(|item| item.matches_id(spec)) {
+        return Ok(item.clone());
+    }
+
+    let mut named: Vec<StartupItem> = items
+        .into_iter()
+        .filter(|item| item.name.eq_ignore_ascii_case(spec))
+        .collect();
+    match named.len() {
+        0 => Err(Error::ItemNotFound {
+            id: spec.to_string(),
+        }),
+        1 => Ok(named.remove(0)),
+        _ => {
+            let ids: Vec<String> = named
+                .iter()
+                .map(|item| format!("{} ({})", item.id, item.source.short_name()))
+                .collect();
+            Err(Error::InvalidName(format!(
+                "{spec} names several items, use one of the ids: {}",
+                ids.join(", ")
+            )))
+        }
+    }
+}
+
+/// Run `edit` and print what changed
+pub fn edit(args: &EditArgs) -> Result<()> {
+    let item = find_item(&args.item)?;
+
+    let mut edit = ItemEdit::for_item(&item);
+    if let Some(name) = &args.name {
+        edit = edit.with_name(name);
+    }
+    if let Some(command) = &args.command {
+        edit = edit.with_command(command);
+    }
+
+    if !edit.changes(&item) {
+        println!("Nothing to change for {}", item.name);
+        return Ok(());
+    }
+
+    apply_edit(&item, &edit)?;
+    println!("Edited {} in {}", item.name, item.source.display_name());
+    if edit.name != item.name {
+        println!("Name: {}", edit.name);
+    }
+    println!("Command: {}", edit.command);
+    Ok(())
+}
+
+/// Run `delay` and print what now starts the item
+pub fn delay(args: &DelayArgs) -> Result<()> {
+    let item = find_item(&args.item)?;
+    let link = delay_item(&item, args.seconds)?;
+
+    match &link.task {
+        Some(task) => println!(
+            "Disabled {} in {} and added the task {}, starting {}s after logon",
+            item.name,
+            item.source.display_name(),
+            task.source_location,
+            link.delay_secs
+        ),
+        None => println!("{} now uses delayed automatic start", item.name),
+    }
+    Ok(())
+}
+
+/// The link an item spec names: the id or name of the original item, or
+/// the id of its task
+fn find_link(spec: &str) -> Result<DelayLink> {
+    let links = list_delayed()?;
+    let by_id = links.iter().find(|link| {
+        link.item.matches_id(spec) || link.task.as_ref().is_some_and(|task| task.matches_id(spec))
+    });
+    if let Some(link) = by_id {
+        return Ok(link.clone());
+    }
+
+    let mut named: Vec<DelayLink> = links
+        .into_iter()
+        .filter(|link| link.item.name.eq_ignore_ascii_case(spec))
+        .collect();
+    match named.len() {
+        0 => Err(Error::ItemNotFound {
+            id: spec.to_string(),
+        }),
+        1 => Ok(named.remove(0)),
+        _ => Err(Error::InvalidName(format!(
+            "{spec} names several delayed items, use the id of one"
+        ))),
+    }
+}
+
+/// Run `undelay` and print what was reverted
+pub fn undelay(args: &UndelayArgs) -> Result<()> {
+    let link = find_link(&args.item)?;
+    undelay_item(&link)?;
+
+    match &link.task {
+        Some(task) => println!(
+            "Enabled {} in {} and removed the task {}",
+            link.item.name,
+            link.item.source.display_name(),
+            task.source_location
+        ),
+        None => println!("{} now uses automatic start", link.item.name),
+    }
+    Ok(())
+}
+
+/// Run a `profile` command
+pub fn profile(command: &ProfileCommand) -> Result<()> {
+    match command {
+        ProfileCommand::List => {
+            let profiles = list_profiles()?;
+            for profile in &profiles {
+                println!(
+                    "{}  {} items, saved {}",
+                    profile.name,
+                    profile.items.len(),
+                    profile.saved_at.format("%Y-%m-%d %H:%M:%S")
+                );
+            }
+            if profiles.is_empty() {
+                println!("No profiles saved yet");
+            }
+        }
+        ProfileCommand::Save { name } => {
+            let profile = save_profile(name, &scan_all_sources())?;
+            println!("Saved {} with {} items", profile.name, profile.items.len());
+        }
+        ProfileCommand::Switch(args) => switch(args)?,
+        ProfileCommand::Delete { name } => {
+            delete_profile(name)?;
+            println!("Deleted {name}");
+        }
+    }
+    Ok(())
+}
+
+/// Run `profile switch` and print each change
+fn switch(args: &SwitchArgs) -> Result<()> {
+    let profile = load_profile(&args.name)?;
+    let plan = profile.plan(&scan_all_sources());
+
+    for entry in &plan.missing {
+        println!(
+            "No longer exists: {} ({})",
+            entry.name,
+            entry.source.display_name()
+        );
+    }
+
+    if plan.is_empty() {
+        println!("Everything already matches {}", profile.name);
+        return Ok(());
+    }
+
+    if args.dry_run {
+        for (item, status) in &plan.changes {
+            let action = if *status == ItemStatus::Enabled {
+                "Enable"
+            } else {
+                "Disable"
+            };
+            println!("{action} {} ({})", item.name, item.source.display_name());
+        }
+        return Ok(());
+    }
+
+    let mode = if args.best_effort {
+        TransactionMode::BestEffort
+    } else {
+        TransactionMode::AllOrNothing
+    };
+    let report = switch_profile(&profile, &plan, mode)?;
+    for result in &report.results {
+        match &result.error {
+            Some(error) => println!("{}: {} ({error})", result.outcome.display(), result.name),
+            None => println!(
+                "{}: {} {}",
+                result.outcome.display(),
+                result.name,
+                result.new_status.display().to_lowercase()
+            ),
+        }
+    }
+
+    let report = report.into_result()?;
+    println!(
+        "Switched to {}: {} of {} changes applied",
+        profile.name,
+        report.count(ChangeOutcome::Applied),
+        report.results.len()
+    );
+    Ok(())
+}
+
+fn print_drift(drift: &[Drift]) {
+    for entry in drift {
+        println!("{}: {}", entry.kind.display(), entry.label());
+    }
+}
+
+/// Run `check` and print the drift. Returns whether the items are in line
+/// with the policy.
+pub fn check(args: &CheckArgs) -> Result<bool> {
+    let policy = Policy::load(&args.policy)?;
+    let label = backup_label(&args.policy);
+    let drift = policy.check(&scan_all_sources());
+
+    match args.format {
+        OutputFormat::Text => {
+            print_drift(&drift);
+            if drift.is_empty() {
+                println!("Everything matches {label}");
+            } else {
+                println!("{} items differ from {label}", drift.len());
+            }
+        }
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&drift).map_err(Error::Serialization)?
+        ),
+    }
+
+    Ok(drift.is_empty())
+}
+
+/// Run `enforce` and print what changed. Returns whether the items are in
+/// line with the policy afterwards.
+pub fn enforce(args: &EnforceArgs) -> Result<bool> {
+    let policy = Policy::load(&args.policy)?;
+    let label = backup_label(&args.policy);
+    let drift = policy.check(&scan_all_sources());
+
+    if drift.is_empty() {
+        println!("Everything matches {label}");
+        return Ok(true);
+    }
+
+    if args.dry_run {
+        for entry in &drift {
+            let note = if entry.is_fixable() {
+                ""
+            } else {
+                " (cannot be fixed)"
+            };
+            println!("{}: {}{note}", entry.kind.display(), entry.label());
+        }
+        return Ok(false);
+    }
+
+    let mode = if args.best_effort {
+        TransactionMode::BestEffort
+    } else {
+        TransactionMode::AllOrNothing
+    };
+    let report = enforce_policy(&label, &drift, mode)?;
+
+    for result in &report.changes.results {
+        match &result.error {
+            Some(error) => println!("{}: {} ({error})", result.outcome.display(), result.name),
+            None => println!(
+                "{}: {} {}",
+                result.outcome.display(),
+                result.name,
+                result.new_status.display().to_lowercase()
+            ),
+        }
+    }
+    for name in &report.deleted {
+        println!("Deleted: {name}");
+    }
+    for (name, error) in &report.delete_failures {
+        println!("Failed: {name} ({error})");
+    }
+    if let Some(e) = report.changes.error() {
+        return Err(e);
+    }
+
+    let remaining = policy.check(&scan_all_sources());
+    if remaining.is_empty() {
+        println!("Everything matches {label}");
+    } else {
+        println!("Still differing from {label}:");
+        print_drift(&remaining);
+    }
+    Ok(remaining.is_empty())
+}
@@ -5,6 +5,7 @@ pub enum Error {
     #[error("Registry access error: {0}")]
     Registry(String),
 
+    #[cfg(windows)]
     #[error("Windows API error: {0}")]
     WindowsApi(#[from] windows::core::Error),
 
@@ -17,14 +18,32 @@ pub enum Error {
     #[error("Item not found: {id}")]
     ItemNotFound { id: String },
 
+    #[error("Item already exists: {0}")]
+    ItemExists(String),
+
+    #[error("Invalid name: {0}")]
+    InvalidName(String),
+
+    #[error("{0} is already delayed")]
+    AlreadyDelayed(String),
+
     #[error("Backup failed: {reason}")]
     BackupFailed { reason: String },
 
+    #[error("Backup not found: {0}")]
+    BackupNotFound(String),
+
+    #[error("Policy error: {0}")]
+    Policy(String),
+
+    #[error("Profile not found: {0}")]
+    ProfileNotFound(String),
+
     #[error("Restore failed: {reason}")]
     RestoreFailed { reason: String },
 
-    #[error("Change failed and was rolled back")]
-    ChangeFailed,
+    #[error("Changing {name} failed and was rolled back: {reason}")]
+    ChangeFailed { name: String, reason: String },
 
     #[error("COM initialization failed: {0}")]
     ComInitFailed(String),
@@ -35,11 +54,17 @@ pub enum Error {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("Property list error: {0}")]
+    Plist(#[from] plist::Error),
+
     #[error("Task scheduler error: {0}")]
     TaskScheduler(String),
 
     #[error("Invalid path: {0}")]
     InvalidPath(String),
+
+    #[error("Not supported on this platform: {0}")]
+    Unsupported(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,3 +1,4 @@
+pub mod cli;
 pub mod error;
 pub mod models;
 pub mod operations;
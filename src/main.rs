@@ -1,8 +1,12 @@
+use clap::Parser;
+use startup_checker::cli::{self, Cli, Command};
 use startup_checker::permissions::{admin_warning, is_elevated};
 use startup_checker::sources::scan_all_sources;
 use startup_checker::ui::run_app;
 
 fn main() {
+    let cli = Cli::parse();
+
     // Check for admin privileges
     if !is_elevated() {
         if let Some(warning) = admin_warning() {
@@ -12,6 +16,42 @@ fn main() {
         }
     }
 
+    // Resolve bare program names through App Paths like the shell does, and
+    // variables set in the registry after this process started
+    #[cfg(windows)]
+    {
+        use startup_checker::models::{LayeredEnvironment, LiveEnvironment, PathResolver};
+        use startup_checker::sources::RegistryScanner;
+
+        let registry = RegistryScanner::new();
+        PathResolver::new(LayeredEnvironment::new(
+            LiveEnvironment,
+            registry.environment(),
+        ))
+        .with_app_paths(registry.app_paths())
+        .install();
+    }
+
+    if let Some(command) = &cli.command {
+        let result = match command {
+            Command::Diff(args) => cli::diff(args),
+            Command::Prune => cli::prune(),
+            Command::BackupSettings(args) => cli::backup_settings(args),
+            Command::Add(args) => cli::add(args),
+            Command::Edit(args) => cli::edit(args),
+            Command::Delay(args) => cli::delay(args),
+            Command::Undelay(args) => cli::undelay(args),
+            Command::Profile(command) => cli::profile(command),
+            Command::Check(args) => cli::check(args).map(exit_on_drift),
+            Command::Enforce(args) => cli::enforce(args).map(exit_on_drift),
+        };
+        if let Err(e) = result {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Scan all startup sources
     eprintln!("Scanning startup items...");
     let items = scan_all_sources();
@@ -23,3 +63,10 @@ fn main() {
         std::process::exit(1);
     }
 }
+
+/// `check` and `enforce` report drift through the exit code
+fn exit_on_drift(in_line: bool) {
+    if !in_line {
+        std::process::exit(cli::DRIFT_EXIT_CODE);
+    }
+}
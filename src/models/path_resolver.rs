@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+use super::command_line::CommandLine;
+use super::environment::{expand_env_vars, Environment, LiveEnvironment};
+
+/// Extensions tried for bare program names when `PATHEXT` is not set
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// Turns the program of a command line into a file path: expands `%VAR%`,
+/// normalizes NT path forms and looks up bare names in `App Paths` and `PATH`
+pub struct PathResolver {
+    env: Arc<dyn Environment>,
+    /// Lower-case executable name to path, from the `App Paths` registry keys
+    app_paths: HashMap<String, PathBuf>,
+}
+
+fn current_resolver() -> &'static RwLock<Arc<PathResolver>> {
+    static CURRENT: OnceLock<RwLock<Arc<PathResolver>>> = OnceLock::new();
+    CURRENT.get_or_init(|| RwLock::new(Arc::new(PathResolver::live())))
+}
+
+impl PathResolver {
+    pub fn new(env: impl Environment + 'static) -> Self {
+        Self {
+            env: Arc::new(env),
+            app_paths: HashMap::new(),
+        }
+    }
+
+    /// Resolve against the environment of the running process
+    pub fn live() -> Self {
+        Self::new(LiveEnvironment)
+    }
+
+    pub fn with_app_paths(mut self, app_paths: HashMap<String, PathBuf>) -> Self {
+        self.app_paths = app_paths
+            .into_iter()
+            .map(|(name, path)| (name.to_lowercase(), path))
+            .collect();
+        self
+    }
+
+    /// The resolver used by [`StartupItem::new`](super::StartupItem::new)
+    pub fn current() -> Arc<PathResolver> {
+        current_resolver()
+            .read()
+            .map(|r| Arc::clone(&r))
+            .unwrap_or_else(|e| Arc::clone(&e.into_inner()))
+    }
+
+    /// Make this the resolver used for newly scanned items
+    pub fn install(self) {
+        let mut current = current_resolver()
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        *current = Arc::new(self);
+    }
+
+    pub fn expand(&self, text: &str) -> String {
+        expand_env_vars(text, self.env.as_ref())
+    }
+
+    fn system_root(&self) -> String {
+        self.env
+            .var("SystemRoot")
+            .unwrap_or_else(|| r"C:\Windows".to_string())
+    }
+
+    /// Rewrite the NT path forms used by services and drivers as Win32 paths:
+    /// `\??\C:\x`, `\\?\C:\x`, `\SystemRoot\x` and `system32\drivers\x.sys`
+    pub fn normalize(&self, path: &str) -> String {
+        if let Some(rest) = path
+            .strip_prefix(r"\\?\UNC\")
+            .or_else(|| path.strip_prefix(r"\??\UNC\"))
+        {
+            return format!(r"\\{rest}");
+        }
+        if let Some(rest) = path
+            .strip_prefix(r"\??\")
+            .or_else(|| path.strip_prefix(r"\\?\"))
+        {
+            return rest.to_string();
+        }
+
+        let lower = path.to_lowercase();
+        if lower.starts_with(r"\systemroot\") {
+            return format!(r"{}\{}", self.system_root(), &path[r"\SystemRoot\".len()..]);
+        }
+        if lower.starts_with(r"system32\") || lower.starts_with(r"syswow64\") {
+            return format!(r"{}\{path}", self.system_root());
+        }
+
+        path.to_string()
+    }
+
+    /// Expand, normalize and, for bare names, search `App Paths`, the system
+    /// directories and `PATH`. Names that cannot be found are returned as they are.
+    pub fn resolve_program(&self, program: &str) -> PathBuf {
+        let program = self.normalize(&self.expand(program));
+        if program.contains(['\\', '/', ':']) {
+            return PathBuf::from(program);
+        }
+
+        let lower = program.to_lowercase();
+        let app_path = self
+            .app_paths
+            .get(&lower)
+            .or_else(|| self.app_paths.get(&format!("{lower}.exe")));
+        if let Some(path) = app_path {
+            return PathBuf::from(self.normalize(&self.expand(&path.to_string_lossy())));
+        }
+
+        self.search_path(&program)
+            .unwrap_or_else(|| PathBuf::from(program))
+    }
+
+    /// The directories `CreateProcess` searches for a bare name
+    fn search_dirs(&self) -> Vec<PathBuf> {
+        let system_root = self.system_root();
+        let mut dirs = vec![
+            PathBuf::from(format!(r"{system_root}\System32")),
+            PathBuf::from(&system_root),
+        ];
+
+        if let Some(path) = self.env.var("PATH") {
+            // Offline Windows environments use ';' even when read on another OS
+            let separator = if cfg!(windows) || path.contains(';') {
+                ';'
+            } else {
+                ':'
+            };
+            dirs.extend(
+                path.split(separator)
+                    .map(str::trim)
+                    .filter(|d| !d.is_empty())
+                    .map(|d| PathBuf::from(self.expand(d.trim_matches('"')))),
+            );
+        }
+
+        dirs
+    }
+
+    fn search_path(&self, name: &str) -> Option<PathBuf> {
+        let pathext = self
+            .env
+            .var("PATHEXT")
+            .unwrap_or_else(|| DEFAULT_PATHEXT.to_string());
+
+        let mut candidates = vec![name.to_string()];
+        if Path::new(name).extension().is_none() {
+            candidates.extend(
+                pathext
+                    .split(';')
+                    .filter(|e| !e.is_empty())
+                    .map(|ext| format!("{name}{}", ext.to_lowercase())),
+            );
+        }
+
+        self.search_dirs()
+            .into_iter()
+            .find_map(|dir| candidates.iter().map(|c| dir.join(c)).find(|p| p.is_file()))
+    }
+
+    /// Split a command line where [`resolve_command`](Self::resolve_command)
+    /// would, but keep the program and arguments as written, e.g. to store
+    /// them somewhere that expands variables itself
+    pub fn split_command(&self, command: &str) -> CommandLine {
+        CommandLine::resolve(command.trim(), |p| {
+            let candidate = self.normalize(&self.expand(&p.to_string_lossy()));
+            Path::new(&candidate).is_file()
+        })
+    }
+
+    /// Split a command line into program and arguments. The program path
+    /// is resolved with [`resolve_program`](Self::resolve_program).
+    pub fn resolve_command(&self, command: &str) -> CommandLine {
+        let expanded = self.expand(command.trim());
+        let mut command_line = CommandLine::resolve(&expanded, |p| {
+            Path::new(&self.normalize(&p.to_string_lossy())).is_file()
+        });
+        if !command_line.program.is_empty() {
+            command_line.program = self
+                .resolve_program(&command_line.program)
+                .to_string_lossy()
+                .to_string();
+        }
+        command_line
+    }
+}
+
+impl Default for PathResolver {
+    fn default() -> Self {
+        Self::live()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::models::{LayeredEnvironment, MapEnvironment};
+
+    #[test]
+    fn split_keeps_variables_and_matches_resolve() {
+        let root =
+            std::env::temp_dir().join(format!("startup-checker-split-{}", std::process::id()));
+        let program = root.join("Program Files").join("App").join("app.exe");
+        fs::create_dir_all(program.parent().unwrap()).unwrap();
+        fs::write(&program, b"").unwrap();
+
+        let resolver =
+            PathResolver::new(MapEnvironment::new().with_var("ROOT", &root.to_string_lossy()));
+        let command = "%ROOT%/Program Files/App/app.exe -x \"a b\"";
+
+        let split = resolver.split_command(command);
+        assert_eq!(split.program, "%ROOT%/Program Files/App/app.exe");
+        assert_eq!(split.raw_args, "-x \"a b\"");
+
+        let resolved = resolver.resolve_command(command);
+        assert_eq!(resolved.program, program.to_string_lossy());
+        assert_eq!(resolved.args, split.args);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn normalizes_nt_path_forms() {
+        let resolver = PathResolver::new(MapEnvironment::new().with_var("SystemRoot", r"D:\Win"));
+
+        assert_eq!(
+            resolver.normalize(r"\??\C:\Drivers\a.sys"),
+            r"C:\Drivers\a.sys"
+        );
+        assert_eq!(resolver.normalize(r"\\?\C:\App\app.exe"), r"C:\App\app.exe");
+        assert_eq!(
+            resolver.normalize(r"\??\UNC\server\share\app.exe"),
+            r"\\server\share\app.exe"
+        );
+        assert_eq!(
+            resolver.normalize(r"\SystemRoot\System32\drivers\a.sys"),
+            r"D:\Win\System32\drivers\a.sys"
+        );
+        assert_eq!(resolver.normalize(r"\systemroot\a.exe"), r"D:\Win\a.exe");
+        assert_eq!(
+            resolver.normalize(r"System32\drivers\b.sys"),
+            r"D:\Win\System32\drivers\b.sys"
+        );
+        assert_eq!(resolver.normalize(r"C:\App\app.exe"), r"C:\App\app.exe");
+    }
+
+    #[test]
+    fn searches_path_with_pathext() {
+        let dir =
+            std::env::temp_dir().join(format!("startup-checker-search-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("tool.cmd"), b"").unwrap();
+
+        let resolver = PathResolver::new(
+            MapEnvironment::new()
+                .with_var("PATH", &format!(r#"C:\missing;"{}""#, dir.display()))
+                .with_var("PATHEXT", ".EXE;.CMD"),
+        );
+
+        assert_eq!(resolver.search_path("tool"), Some(dir.join("tool.cmd")));
+        assert_eq!(resolver.search_path("tool.cmd"), Some(dir.join("tool.cmd")));
+        assert_eq!(resolver.search_path("other"), None);
+        assert_eq!(resolver.resolve_program("tool"), dir.join("tool.cmd"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn resolves_programs_through_variables_and_app_paths() {
+        let resolver = PathResolver::new(
+            MapEnvironment::new()
+                .with_var("SystemRoot", r"D:\Win")
+                .with_var("APPS", r"E:\Apps"),
+        )
+        .with_app_paths(HashMap::from([(
+            "Editor.exe".to_string(),
+            PathBuf::from(r"%APPS%\Editor\editor.exe"),
+        )]));
+
+        assert_eq!(
+            resolver.resolve_program(r"%SystemRoot%\System32\app.exe"),
+            PathBuf::from(r"D:\Win\System32\app.exe")
+        );
+        assert_eq!(
+            resolver.resolve_program("editor"),
+            PathBuf::from(r"E:\Apps\Editor\editor.exe")
+        );
+        assert_eq!(
+            resolver.resolve_program("EDITOR.EXE"),
+            PathBuf::from(r"E:\Apps\Editor\editor.exe")
+        );
+        assert_eq!(
+            resolver.resolve_program("missing"),
+            PathBuf::from("missing")
+        );
+    }
+
+    #[test]
+    fn layered_environments_fill_in_missing_variables() {
+        let process = MapEnvironment::new().with_var("TEMP", r"C:\Temp");
+        let registry = MapEnvironment::new()
+            .with_var("TEMP", r"D:\Temp")
+            .with_var("TOOLS", r"D:\Tools");
+        let resolver = PathResolver::new(LayeredEnvironment::new(process, registry));
+
+        assert_eq!(
+            resolver.expand(r"%TOOLS%\bin;%TEMP%"),
+            r"D:\Tools\bin;C:\Temp"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// Source-specific data captured in a backup, enough to recreate an item
+/// that was deleted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum RawPayload {
+    /// A registry value exactly as stored
+    RegistryValue {
+        /// `REG_SZ`, `REG_EXPAND_SZ`, ... as the numeric type
+        value_type: u32,
+        #[serde(with = "base64_bytes")]
+        data: Vec<u8>,
+    },
+
+    /// A whole file, e.g. a `.lnk` shortcut in a Startup folder
+    File {
+        file_name: String,
+        #[serde(with = "base64_bytes")]
+        data: Vec<u8>,
+    },
+
+    /// The task definition from `schtasks /query /xml`
+    TaskXml { xml: String },
+
+    /// A service's configuration from `QueryServiceConfigW`
+    Service(ServiceConfig),
+
+    /// One line of a text file such as a crontab or `wsl.conf`, without the
+    /// disabled marker
+    Line { line: String },
+
+    /// One entry of a property list array, e.g. a login item, as XML
+    PlistEntry { xml: String },
+
+    /// A system autostart entry together with the user's override of it,
+    /// which restoring must not lose
+    DesktopEntry {
+        file_name: String,
+        #[serde(with = "base64_bytes")]
+        data: Vec<u8>,
+        #[serde(default, with = "optional_base64_bytes")]
+        user_override: Option<Vec<u8>>,
+    },
+}
+
+/// Everything `CreateServiceW` needs to register a service again
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceConfig {
+    pub display_name: String,
+    pub service_type: u32,
+    pub start_type: u32,
+    #[serde(default)]
+    pub delayed_auto_start: bool,
+    pub error_control: u32,
+    pub image_path: String,
+    pub load_order_group: String,
+    pub dependencies: Vec<String>,
+    /// Account the service runs as, e.g. `LocalSystem`. Passwords cannot
+    /// be read back, so other accounts need one set after recreating.
+    pub account: String,
+}
+
+impl RawPayload {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::RegistryValue { .. } => "registry value",
+            Self::File { .. } => "file",
+            Self::TaskXml { .. } => "task XML",
+            Self::Service(_) => "service configuration",
+            Self::Line { .. } => "line",
+            Self::PlistEntry { .. } => "property list entry",
+            Self::DesktopEntry { .. } => "desktop entry",
+        }
+    }
+}
+
+/// Bytes as a base64 string, which keeps backups readable JSON
+mod base64_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        STANDARD.decode(text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Like [`base64_bytes`], for bytes that may be missing
+mod optional_base64_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        bytes
+            .as_ref()
+            .map(|bytes| STANDARD.encode(bytes))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| STANDARD.decode(text).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
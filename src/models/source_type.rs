@@ -11,6 +11,23 @@ pub enum SourceType {
     StartupFolderAllUsers,
     ScheduledTask,
     WindowsService,
+    PowerShellProfileAllUsers,
+    PowerShellProfileCurrentUser,
+    LogonScript,
+    Miscellaneous,
+    Wsl,
+    XdgAutostartUser,
+    XdgAutostartSystem,
+    SystemdSystem,
+    SystemdUser,
+    CronReboot,
+    RcLocal,
+    LaunchAgentsUser,
+    LaunchAgentsSystem,
+    LaunchDaemons,
+    LoginItems,
+    ShellStartupUser,
+    ShellStartupSystem,
 }
 
 impl SourceType {
@@ -25,6 +42,23 @@ impl SourceType {
             Self::StartupFolderAllUsers => "Startup Folder (All Users)",
             Self::ScheduledTask => "Scheduled Tasks",
             Self::WindowsService => "Windows Services",
+            Self::PowerShellProfileAllUsers => "PowerShell Profiles (All Users)",
+            Self::PowerShellProfileCurrentUser => "PowerShell Profiles (User)",
+            Self::LogonScript => "Logon Script (UserInitMprLogonScript)",
+            Self::Miscellaneous => "Miscellaneous",
+            Self::Wsl => "WSL Boot Commands",
+            Self::XdgAutostartUser => "XDG Autostart (User)",
+            Self::XdgAutostartSystem => "XDG Autostart (System)",
+            Self::SystemdSystem => "systemd Units (System)",
+            Self::SystemdUser => "systemd Units (User)",
+            Self::CronReboot => "Cron (@reboot)",
+            Self::RcLocal => "rc.local",
+            Self::LaunchAgentsUser => "Launch Agents (User)",
+            Self::LaunchAgentsSystem => "Launch Agents (System)",
+            Self::LaunchDaemons => "Launch Daemons",
+            Self::LoginItems => "Login Items",
+            Self::ShellStartupUser => "Shell Startup Files (User)",
+            Self::ShellStartupSystem => "Shell Startup Files (System)",
         }
     }
 
@@ -39,6 +73,23 @@ impl SourceType {
             Self::StartupFolderAllUsers => "All Users Startup",
             Self::ScheduledTask => "Tasks",
             Self::WindowsService => "Services",
+            Self::PowerShellProfileAllUsers => "All Users PS Profile",
+            Self::PowerShellProfileCurrentUser => "User PS Profile",
+            Self::LogonScript => "Logon Script",
+            Self::Miscellaneous => "Misc",
+            Self::Wsl => "WSL",
+            Self::XdgAutostartUser => "User Autostart",
+            Self::XdgAutostartSystem => "System Autostart",
+            Self::SystemdSystem => "systemd",
+            Self::SystemdUser => "systemd --user",
+            Self::CronReboot => "Cron",
+            Self::RcLocal => "rc.local",
+            Self::LaunchAgentsUser => "User Agents",
+            Self::LaunchAgentsSystem => "System Agents",
+            Self::LaunchDaemons => "Daemons",
+            Self::LoginItems => "Login Items",
+            Self::ShellStartupUser => "User Shell RC",
+            Self::ShellStartupSystem => "System Shell RC",
         }
     }
 
@@ -50,6 +101,13 @@ impl SourceType {
                 | Self::RegistryLocalMachineWow6432
                 | Self::StartupFolderAllUsers
                 | Self::WindowsService
+                | Self::PowerShellProfileAllUsers
+                | Self::SystemdSystem
+                | Self::CronReboot
+                | Self::RcLocal
+                | Self::LaunchAgentsSystem
+                | Self::LaunchDaemons
+                | Self::ShellStartupSystem
         )
     }
 
@@ -64,6 +122,28 @@ impl SourceType {
         )
     }
 
+    /// Whether the item lives in the registry, a Windows path or another
+    /// case-insensitive Windows namespace
+    pub fn is_windows(&self) -> bool {
+        matches!(
+            self,
+            Self::RegistryCurrentUserRun
+                | Self::RegistryCurrentUserRunOnce
+                | Self::RegistryLocalMachineRun
+                | Self::RegistryLocalMachineRunOnce
+                | Self::RegistryLocalMachineWow6432
+                | Self::StartupFolderUser
+                | Self::StartupFolderAllUsers
+                | Self::ScheduledTask
+                | Self::WindowsService
+                | Self::PowerShellProfileAllUsers
+                | Self::PowerShellProfileCurrentUser
+                | Self::LogonScript
+                | Self::Miscellaneous
+                | Self::Wsl
+        )
+    }
+
     pub fn is_startup_folder(&self) -> bool {
         matches!(self, Self::StartupFolderUser | Self::StartupFolderAllUsers)
     }
@@ -100,6 +180,23 @@ impl SourceType {
             Self::StartupFolderAllUsers,
             Self::ScheduledTask,
             Self::WindowsService,
+            Self::PowerShellProfileAllUsers,
+            Self::PowerShellProfileCurrentUser,
+            Self::LogonScript,
+            Self::Miscellaneous,
+            Self::Wsl,
+            Self::XdgAutostartUser,
+            Self::XdgAutostartSystem,
+            Self::SystemdSystem,
+            Self::SystemdUser,
+            Self::CronReboot,
+            Self::RcLocal,
+            Self::LaunchAgentsUser,
+            Self::LaunchAgentsSystem,
+            Self::LaunchDaemons,
+            Self::LoginItems,
+            Self::ShellStartupUser,
+            Self::ShellStartupSystem,
         ]
     }
 }
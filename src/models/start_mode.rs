@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+
+use super::SourceType;
+
+/// When an item is started. Services use the Service Control Manager start
+/// types; other sources get the mode implied by where they are configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StartMode {
+    /// Driver loaded by the boot loader
+    Boot,
+    /// Driver loaded while the kernel initializes
+    System,
+    /// Started while the system boots
+    Auto,
+    /// Started shortly after the other automatic services
+    DelayedAuto,
+    /// Started only when something asks for it
+    Manual,
+    Disabled,
+    /// Manual, but started by a trigger such as a device arrival or network event
+    TriggerStart,
+    /// Started when a user logs on
+    Logon,
+    /// Started at the next logon only
+    Once,
+}
+
+impl StartMode {
+    /// Map a service's `dwStartType` (`SERVICE_BOOT_START` = 0 through
+    /// `SERVICE_DISABLED` = 4) plus its delayed-auto and trigger settings
+    pub fn from_service(start_type: u32, delayed: bool, triggered: bool) -> Option<Self> {
+        match start_type {
+            0 => Some(Self::Boot),
+            1 => Some(Self::System),
+            2 if delayed => Some(Self::DelayedAuto),
+            2 => Some(Self::Auto),
+            3 if triggered => Some(Self::TriggerStart),
+            3 => Some(Self::Manual),
+            4 => Some(Self::Disabled),
+            _ => None,
+        }
+    }
+
+    /// The mode items from `source` have, if the source implies one
+    pub fn for_source(source: SourceType) -> Option<Self> {
+        match source {
+            SourceType::RegistryCurrentUserRun
+            | SourceType::RegistryLocalMachineRun
+            | SourceType::RegistryLocalMachineWow6432
+            | SourceType::StartupFolderUser
+            | SourceType::StartupFolderAllUsers
+            | SourceType::LogonScript
+            | SourceType::XdgAutostartUser
+            | SourceType::XdgAutostartSystem
+            | SourceType::SystemdUser
+            | SourceType::LaunchAgentsUser
+            | SourceType::LaunchAgentsSystem
+            | SourceType::LoginItems => Some(Self::Logon),
+            SourceType::RegistryCurrentUserRunOnce | SourceType::RegistryLocalMachineRunOnce => {
+                Some(Self::Once)
+            }
+            SourceType::SystemdSystem
+            | SourceType::CronReboot
+            | SourceType::RcLocal
+            | SourceType::LaunchDaemons => Some(Self::Auto),
+            _ => None,
+        }
+    }
+
+    /// Whether the item starts without anyone asking for it
+    pub fn is_automatic(&self) -> bool {
+        !matches!(self, Self::Manual | Self::Disabled)
+    }
+
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::Boot => "Boot",
+            Self::System => "System",
+            Self::Auto => "Automatic",
+            Self::DelayedAuto => "Automatic (Delayed Start)",
+            Self::Manual => "Manual",
+            Self::Disabled => "Disabled",
+            Self::TriggerStart => "Manual (Trigger Start)",
+            Self::Logon => "At logon",
+            Self::Once => "Once at next logon",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_service_start_types() {
+        assert_eq!(
+            StartMode::from_service(0, false, false),
+            Some(StartMode::Boot)
+        );
+        assert_eq!(
+            StartMode::from_service(1, false, false),
+            Some(StartMode::System)
+        );
+        assert_eq!(
+            StartMode::from_service(2, false, false),
+            Some(StartMode::Auto)
+        );
+        assert_eq!(
+            StartMode::from_service(2, true, false),
+            Some(StartMode::DelayedAuto)
+        );
+        assert_eq!(
+            StartMode::from_service(3, false, false),
+            Some(StartMode::Manual)
+        );
+        assert_eq!(
+            StartMode::from_service(3, false, true),
+            Some(StartMode::TriggerStart)
+        );
+        assert_eq!(
+            StartMode::from_service(4, false, true),
+            Some(StartMode::Disabled)
+        );
+        assert_eq!(StartMode::from_service(5, false, false), None);
+    }
+
+    #[test]
+    fn only_manual_and_disabled_wait_to_be_asked() {
+        assert!(StartMode::TriggerStart.is_automatic());
+        assert!(StartMode::DelayedAuto.is_automatic());
+        assert!(!StartMode::Manual.is_automatic());
+        assert!(!StartMode::Disabled.is_automatic());
+    }
+
+    #[test]
+    fn sources_imply_their_start_mode() {
+        assert_eq!(
+            StartMode::for_source(SourceType::RegistryCurrentUserRunOnce),
+            Some(StartMode::Once)
+        );
+        assert_eq!(
+            StartMode::for_source(SourceType::CronReboot),
+            Some(StartMode::Auto)
+        );
+        assert_eq!(
+            StartMode::for_source(SourceType::LoginItems),
+            Some(StartMode::Logon)
+        );
+        assert_eq!(StartMode::for_source(SourceType::WindowsService), None);
+    }
+}
@@ -1,12 +1,17 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use super::{ItemStatus, SourceType};
+use super::command_line::{split_posix, unwrap_launcher, LaunchPayload};
+use super::path_resolver::PathResolver;
+use super::{FileInfo, FileInfoCache, ItemStatus, SourceType, StartMode};
+use crate::sources::DISABLED_SUBKEY;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StartupItem {
-    /// Unique identifier (hash of source + name + command)
+    /// Stable identifier, a hash of source + location + name. It does not
+    /// change when the command is edited or the item is toggled.
     pub id: String,
 
     /// Display name of the startup item
@@ -21,9 +26,18 @@ pub struct StartupItem {
     /// Current status
     pub status: ItemStatus,
 
+    /// When the item starts, e.g. a service's start type
+    #[serde(default)]
+    pub start_mode: Option<StartMode>,
+
     /// Command line or executable path
     pub command: String,
 
+    /// Hash of the command, to notice when an entry is rewritten in place.
+    /// Empty for items saved before ids were location based.
+    #[serde(default)]
+    pub command_fingerprint: String,
+
     /// Publisher/Company name (if available)
     pub publisher: Option<String>,
 
@@ -38,6 +52,24 @@ pub struct StartupItem {
 
     /// Whether the executable file exists
     pub file_exists: bool,
+
+    /// Size and times of the executable, filled in by
+    /// [`stat_file`](Self::stat_file) for backups and diffs
+    #[serde(default)]
+    pub file_info: Option<FileInfo>,
+
+    /// Arguments after the executable, split like `CommandLineToArgvW`
+    #[serde(default)]
+    pub arguments: Vec<String>,
+
+    /// What a launcher such as `rundll32` or `powershell` really runs
+    #[serde(default)]
+    pub payload: Option<LaunchPayload>,
+
+    /// 0-based line of the entry for sources that are lines in a text file,
+    /// so repeated commands can be told apart
+    #[serde(default)]
+    pub line: Option<usize>,
 }
 
 impl StartupItem {
@@ -47,73 +79,173 @@ impl StartupItem {
         source_location: String,
         command: String,
     ) -> Self {
-        let id = Self::generate_id(&source, &name, &command);
-        let executable_path = Self::extract_executable_path(&command);
-        let file_exists = executable_path
-            .as_ref()
-            .is_some_and(|p| p.exists());
+        let id = Self::generate_id(&source, &source_location, &name);
+        let command_fingerprint = Self::fingerprint(&command);
 
-        Self {
+        let mut item = Self {
             id,
             name,
             source,
             source_location,
             status: ItemStatus::Enabled,
+            start_mode: StartMode::for_source(source),
             command,
+            command_fingerprint,
             publisher: None,
             description: None,
             requires_admin: source.requires_admin(),
-            executable_path,
-            file_exists,
+            executable_path: None,
+            file_exists: false,
+            file_info: None,
+            arguments: Vec::new(),
+            payload: None,
+            line: None,
+        };
+        if source.is_windows() {
+            item.resolve_with(&PathResolver::current());
+        } else {
+            item.resolve_posix();
         }
+        item
     }
 
-    fn generate_id(source: &SourceType, name: &str, command: &str) -> String {
+    fn hash(text: &str) -> String {
         let mut hasher = Sha256::new();
-        hasher.update(format!("{:?}:{name}:{command}", source));
+        hasher.update(text);
         let result = hasher.finalize();
         hex::encode(&result[..8]) // Use first 8 bytes for shorter ID
     }
 
-    fn extract_executable_path(command: &str) -> Option<PathBuf> {
-        let command = command.trim();
+    /// Where the item is configured: the key or file without the place
+    /// disabled entries are moved to, case-folded for Windows namespaces
+    fn location_key(source: &SourceType, source_location: &str) -> String {
+        let mut location = source_location.trim_end_matches(['\\', '/']).to_string();
+        let disabled_suffix = format!("\\{}", DISABLED_SUBKEY.to_lowercase());
+        if location.to_lowercase().ends_with(&disabled_suffix) {
+            location.truncate(location.len() - disabled_suffix.len());
+        }
+
+        if source.is_windows() {
+            location.to_lowercase()
+        } else {
+            location
+        }
+    }
+
+    fn generate_id(source: &SourceType, source_location: &str, name: &str) -> String {
+        let location = Self::location_key(source, source_location);
+        let name = if source.is_windows() {
+            name.to_lowercase()
+        } else {
+            name.to_string()
+        };
+        Self::hash(&format!("{source:?}:{location}:{name}"))
+    }
+
+    fn fingerprint(command: &str) -> String {
+        Self::hash(command.trim())
+    }
+
+    /// The id older versions derived from source + name + command
+    pub fn legacy_id(&self) -> String {
+        Self::hash(&format!("{:?}:{}:{}", self.source, self.name, self.command))
+    }
+
+    /// Whether `id` refers to this item under the current or the legacy scheme
+    pub fn matches_id(&self, id: &str) -> bool {
+        self.id == id || self.legacy_id() == id
+    }
+
+    /// Whether the command differs from the one `other` was saved with
+    pub fn command_changed(&self, other: &StartupItem) -> bool {
+        self.command_fingerprint != other.command_fingerprint
+    }
+
+    /// Replace a legacy id with the location based one. Returns whether
+    /// the item needed migrating.
+    pub fn migrate_legacy_id(&mut self) -> bool {
+        if !self.command_fingerprint.is_empty() {
+            return false;
+        }
 
-        // Handle quoted paths
-        if command.starts_with('"') {
-            if let Some(end) = command[1..].find('"') {
-                let path = &command[1..=end];
-                return Some(PathBuf::from(path));
+        self.id = Self::generate_id(&self.source, &self.source_location, &self.name);
+        self.command_fingerprint = Self::fingerprint(&self.command);
+        true
+    }
+
+    /// Give items sharing a location, such as two `@reboot` lines running
+    /// the same program, distinct ids by numbering repeats in scan order
+    pub fn disambiguate_ids(items: &mut [StartupItem]) {
+        let mut seen: HashMap<String, usize> = HashMap::new();
+
+        for item in items.iter_mut() {
+            let count = seen.entry(item.id.clone()).or_insert(0);
+            *count += 1;
+            if *count > 1 {
+                let location = format!("{}#{count}", item.source_location);
+                item.id = Self::generate_id(&item.source, &location, &item.name);
             }
         }
+    }
 
-        // Handle unquoted paths - take until first space or end
-        let path = command.split_whitespace().next()?;
+    /// Fill in the executable and arguments of a Unix item, splitting the
+    /// command like a shell and looking up bare names in `PATH`
+    fn resolve_posix(&mut self) {
+        let mut words = split_posix(&self.command).into_iter();
+        let program = words.next().filter(|program| !program.is_empty());
 
-        // Expand environment variables
-        let expanded = Self::expand_env_vars(path);
-        Some(PathBuf::from(expanded))
+        self.executable_path = program.as_deref().map(|program| {
+            let path = Path::new(program);
+            if program.contains('/') {
+                return path.to_path_buf();
+            }
+            std::env::var_os("PATH")
+                .and_then(|dirs| {
+                    std::env::split_paths(&dirs)
+                        .map(|dir| dir.join(path))
+                        .find(|p| p.is_file())
+                })
+                .unwrap_or_else(|| path.to_path_buf())
+        });
+        self.file_exists = self.executable_path.as_ref().is_some_and(|p| p.is_file());
+        self.payload = None;
+        self.arguments = words.collect();
     }
 
-    fn expand_env_vars(path: &str) -> String {
-        let mut result = path.to_string();
+    /// Fill in the executable, arguments and payload of a Windows item from
+    /// the command, e.g. again with an offline environment
+    pub fn resolve_with(&mut self, resolver: &PathResolver) {
+        let parsed = resolver.resolve_command(&self.command);
 
-        // Common environment variables
-        let env_vars = [
-            ("%SystemRoot%", std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string())),
-            ("%ProgramFiles%", std::env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string())),
-            ("%ProgramFiles(x86)%", std::env::var("ProgramFiles(x86)").unwrap_or_else(|_| "C:\\Program Files (x86)".to_string())),
-            ("%USERPROFILE%", std::env::var("USERPROFILE").unwrap_or_default()),
-            ("%APPDATA%", std::env::var("APPDATA").unwrap_or_default()),
-            ("%LOCALAPPDATA%", std::env::var("LOCALAPPDATA").unwrap_or_default()),
-        ];
+        self.executable_path = (!parsed.program.is_empty()).then(|| PathBuf::from(&parsed.program));
+        self.file_exists = self.executable_path.as_ref().is_some_and(|p| p.exists());
+        self.payload = unwrap_launcher(&parsed).map(|mut payload| {
+            payload.path = payload
+                .path
+                .map(|p| resolver.resolve_program(&p.to_string_lossy()));
+            payload
+        });
+        self.arguments = parsed.args;
+    }
 
-        for (var, value) in env_vars {
-            result = result.replace(var, &value);
-            // Also handle lowercase
-            result = result.replace(&var.to_lowercase(), &value);
+    /// Hashed fingerprint of the executable through the shared cache,
+    /// without storing it on the item
+    pub fn file_info(&self) -> Option<FileInfo> {
+        if let Some(info) = self.file_info.as_ref().filter(|info| info.is_hashed()) {
+            return Some(info.clone());
         }
+        let path = self.executable_path.as_ref().filter(|_| self.file_exists)?;
+        FileInfoCache::global().get(path).ok()
+    }
 
-        result
+    /// Read the size and times of the executable, if it exists, and keep
+    /// them on the item. Hashing is left to [`file_info`](Self::file_info).
+    pub fn stat_file(&mut self) -> Option<&FileInfo> {
+        if self.file_info.is_none() {
+            let path = self.executable_path.as_ref().filter(|_| self.file_exists);
+            self.file_info = path.and_then(|path| FileInfo::stat(path).ok());
+        }
+        self.file_info.as_ref()
     }
 
     pub fn with_status(mut self, status: ItemStatus) -> Self {
@@ -121,6 +253,11 @@ impl StartupItem {
         self
     }
 
+    pub fn with_start_mode(mut self, start_mode: Option<StartMode>) -> Self {
+        self.start_mode = start_mode;
+        self
+    }
+
     pub fn with_publisher(mut self, publisher: Option<String>) -> Self {
         self.publisher = publisher;
         self
@@ -131,6 +268,11 @@ impl StartupItem {
         self
     }
 
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
     pub fn display_command(&self) -> String {
         if self.command.len() > 60 {
             format!("{}...", &self.command[..57])
@@ -160,3 +302,61 @@ impl std::hash::Hash for StartupItem {
         self.id.hash(state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_commands_are_split_like_a_shell() {
+        let item = StartupItem::new(
+            "app".to_string(),
+            SourceType::SystemdUser,
+            "/home/user/.config/systemd/user/app.service".to_string(),
+            r#""/opt/my app/%PATH%" --flag 'two words'"#.to_string(),
+        );
+
+        assert_eq!(
+            item.executable_path,
+            Some(PathBuf::from("/opt/my app/%PATH%"))
+        );
+        assert_eq!(item.arguments, vec!["--flag", "two words"]);
+        assert_eq!(item.payload, None);
+    }
+
+    #[test]
+    fn windows_commands_follow_windows_rules() {
+        let item = StartupItem::new(
+            "App".to_string(),
+            SourceType::RegistryCurrentUserRun,
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run".to_string(),
+            r#""C:\Program Files\App\app.exe" /min C:\dir\"#.to_string(),
+        );
+
+        assert_eq!(
+            item.executable_path,
+            Some(PathBuf::from(r"C:\Program Files\App\app.exe"))
+        );
+        assert_eq!(item.arguments, vec!["/min", r"C:\dir\"]);
+    }
+
+    #[test]
+    fn ids_survive_moving_to_the_disabled_key() {
+        let run = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+        let enabled = StartupItem::new(
+            "App".to_string(),
+            SourceType::RegistryCurrentUserRun,
+            run.to_string(),
+            "app.exe".to_string(),
+        );
+        let disabled = StartupItem::new(
+            "app".to_string(),
+            SourceType::RegistryCurrentUserRun,
+            format!(r"{run}\AutorunsDisabled"),
+            "other.exe".to_string(),
+        );
+
+        assert_eq!(enabled.id, disabled.id);
+        assert!(enabled.command_changed(&disabled));
+    }
+}
@@ -1,11 +1,18 @@
-use std::fs;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
-use crate::models::StartupItem;
+use crate::models::{RawPayload, StartupItem};
+use crate::sources::capture_item;
+
+use super::backup_settings::{BackupSettings, Compression, RetentionPolicy};
+
+const MANIFEST_FILE: &str = "manifest.json";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Backup {
@@ -13,6 +20,10 @@ pub struct Backup {
     pub items: Vec<StartupItem>,
     pub version: String,
     pub description: Option<String>,
+
+    /// Source-specific data to recreate deleted items, keyed by item id
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub payloads: HashMap<String, RawPayload>,
 }
 
 impl Backup {
@@ -22,14 +33,104 @@ impl Backup {
             items,
             version: env!("CARGO_PKG_VERSION").to_string(),
             description,
+            payloads: HashMap::new(),
+        }
+    }
+
+    /// Capture the raw data behind every item that can provide it
+    pub fn capture_payloads(&mut self) {
+        self.payloads = self
+            .items
+            .iter()
+            .filter_map(|item| Some((item.id.clone(), capture_item(item).ok()??)))
+            .collect();
+    }
+
+    /// Give items saved with legacy source + name + command ids their
+    /// location based ids, so they match freshly scanned items
+    pub fn migrate(&mut self) -> bool {
+        let mut migrated = false;
+        for item in &mut self.items {
+            migrated |= item.migrate_legacy_id();
+        }
+        if migrated {
+            StartupItem::disambiguate_ids(&mut self.items);
+        }
+        migrated
+    }
+
+    /// Find the saved copy of a scanned item
+    pub fn find_item(&self, item: &StartupItem) -> Option<&StartupItem> {
+        self.items.iter().find(|saved| saved.id == item.id)
+    }
+
+    /// File name for this backup, e.g. `backup_20240115_143022.json.gz`.
+    /// `attempt` numbers backups taken in the same second.
+    pub fn filename(&self, compression: Compression, attempt: u32) -> String {
+        let stamp = self.timestamp.format("%Y%m%d_%H%M%S");
+        let suffix = if attempt > 1 {
+            format!("_{attempt}")
+        } else {
+            String::new()
+        };
+        format!("backup_{stamp}{suffix}.json{}", compression.extension())
+    }
+}
+
+/// Summary of one backup file. The manifest keeps these so listing
+/// backups does not parse every file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub file_name: String,
+    pub timestamp: DateTime<Utc>,
+    pub version: String,
+    pub description: Option<String>,
+    pub item_count: usize,
+
+    /// Size on disk in bytes
+    pub size: u64,
+
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl BackupEntry {
+    fn new(path: &Path, backup: &Backup, size: u64) -> Self {
+        Self {
+            file_name: file_name(path),
+            timestamp: backup.timestamp,
+            version: backup.version.clone(),
+            description: backup.description.clone(),
+            item_count: backup.items.len(),
+            size,
+            path: path.to_path_buf(),
         }
     }
 
-    pub fn filename(&self) -> String {
-        format!("backup_{}.json", self.timestamp.format("%Y%m%d_%H%M%S"))
+    /// Read the whole backup
+    pub fn load(&self) -> Result<Backup> {
+        restore_backup(&self.path)
     }
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    entries: Vec<BackupEntry>,
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn is_backup_file(name: &str) -> bool {
+    name.starts_with("backup_")
+        && [".json", ".json.gz", ".json.zst"]
+            .iter()
+            .any(|ext| name.ends_with(ext))
+}
+
 /// Get the backup directory path
 pub fn get_backup_dir() -> Result<PathBuf> {
     let backup_dir = dirs::data_local_dir()
@@ -42,58 +143,182 @@ pub fn get_backup_dir() -> Result<PathBuf> {
     Ok(backup_dir)
 }
 
-/// Create a backup of the current startup items
+fn read_manifest(backup_dir: &Path) -> Manifest {
+    fs::read_to_string(backup_dir.join(MANIFEST_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_manifest(backup_dir: &Path, manifest: &Manifest) -> Result<()> {
+    // Write then rename, so a crash never leaves half a manifest
+    let json = serde_json::to_string_pretty(manifest).map_err(Error::Serialization)?;
+    let temp = backup_dir.join(format!("{MANIFEST_FILE}.tmp"));
+    fs::write(&temp, json).map_err(Error::Io)?;
+    fs::rename(&temp, backup_dir.join(MANIFEST_FILE)).map_err(Error::Io)
+}
+
+/// Bring the manifest in line with the files on disk: drop entries for
+/// deleted files and read files it does not know yet
+fn sync_manifest(backup_dir: &Path) -> Result<Vec<BackupEntry>> {
+    let manifest = read_manifest(backup_dir);
+    let mut known: HashMap<String, BackupEntry> = manifest
+        .entries
+        .into_iter()
+        .map(|entry| (entry.file_name.clone(), entry))
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut changed = false;
+
+    for dir_entry in fs::read_dir(backup_dir).map_err(Error::Io)?.flatten() {
+        let path = dir_entry.path();
+        let name = file_name(&path);
+        if !is_backup_file(&name) {
+            continue;
+        }
+        let size = dir_entry.metadata().map(|m| m.len()).unwrap_or_default();
+
+        match known.remove(&name) {
+            Some(mut entry) if entry.size == size => {
+                entry.path = path;
+                entries.push(entry);
+            }
+            _ => {
+                changed = true;
+                if let Ok(backup) = restore_backup(&path) {
+                    entries.push(BackupEntry::new(&path, &backup, size));
+                }
+            }
+        }
+    }
+    changed |= !known.is_empty();
+
+    // Sort by timestamp, newest first
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+
+    if changed {
+        write_manifest(
+            backup_dir,
+            &Manifest {
+                entries: entries.clone(),
+            },
+        )?;
+    }
+
+    Ok(entries)
+}
+
+/// Write `data` under the first free name for `backup`
+fn write_new_file(
+    backup_dir: &Path,
+    backup: &Backup,
+    compression: Compression,
+    data: &[u8],
+) -> Result<PathBuf> {
+    for attempt in 1.. {
+        let path = backup_dir.join(backup.filename(compression, attempt));
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(data).map_err(Error::Io)?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(Error::Io(e)),
+        }
+    }
+    unreachable!("attempts are unbounded")
+}
+
+/// Create a backup of the current startup items, then prune old backups
+/// if a retention policy is saved
 pub fn create_backup(items: &[StartupItem], description: Option<String>) -> Result<PathBuf> {
     let backup_dir = get_backup_dir()?;
+    let settings = BackupSettings::load()?;
 
-    let backup = Backup::new(items.to_vec(), description);
-    let filename = backup.filename();
-    let path = backup_dir.join(&filename);
+    let mut items = items.to_vec();
+    for item in &mut items {
+        item.stat_file();
+    }
+
+    let mut backup = Backup::new(items, description);
+    backup.capture_payloads();
 
     let json = serde_json::to_string_pretty(&backup).map_err(Error::Serialization)?;
-    fs::write(&path, json).map_err(Error::Io)?;
+    let data = settings.compression.compress(json.as_bytes())?;
+    let path = write_new_file(&backup_dir, &backup, settings.compression, &data)?;
+
+    let mut entries = sync_manifest(&backup_dir)?;
+    if !entries.iter().any(|entry| entry.path == path) {
+        entries.insert(0, BackupEntry::new(&path, &backup, data.len() as u64));
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.timestamp));
+    }
+    match &settings.retention {
+        Some(policy) => {
+            prune(&backup_dir, entries, policy)?;
+        }
+        None => write_manifest(&backup_dir, &Manifest { entries })?,
+    }
 
     Ok(path)
 }
 
-/// List all available backups
-pub fn list_backups() -> Result<Vec<(PathBuf, Backup)>> {
+/// Delete the backups the policy does not keep, returning their paths
+pub fn prune_backups(policy: &RetentionPolicy) -> Result<Vec<PathBuf>> {
     let backup_dir = get_backup_dir()?;
-    let mut backups = Vec::new();
-
-    if !backup_dir.exists() {
-        return Ok(backups);
-    }
-
-    let entries = fs::read_dir(&backup_dir).map_err(Error::Io)?;
+    let entries = sync_manifest(&backup_dir)?;
+    prune(&backup_dir, entries, policy)
+}
 
-    for entry in entries.flatten() {
-        let path = entry.path();
+fn prune(
+    backup_dir: &Path,
+    entries: Vec<BackupEntry>,
+    policy: &RetentionPolicy,
+) -> Result<Vec<PathBuf>> {
+    let expired: Vec<PathBuf> = policy
+        .expired(&entries)
+        .into_iter()
+        .map(|entry| entry.path.clone())
+        .collect();
 
-        if path.extension().is_some_and(|e| e == "json") {
-            if let Ok(content) = fs::read_to_string(&path) {
-                if let Ok(backup) = serde_json::from_str::<Backup>(&content) {
-                    backups.push((path, backup));
-                }
-            }
+    let mut removed = Vec::new();
+    for path in expired {
+        match fs::remove_file(&path) {
+            Ok(()) => removed.push(path),
+            Err(e) if e.kind() == ErrorKind::NotFound => removed.push(path),
+            // Keep listing files that could not be deleted
+            Err(_) => {}
         }
     }
 
-    // Sort by timestamp, newest first
-    backups.sort_by(|a, b| b.1.timestamp.cmp(&a.1.timestamp));
+    let entries = entries
+        .into_iter()
+        .filter(|entry| !removed.contains(&entry.path))
+        .collect();
+    write_manifest(backup_dir, &Manifest { entries })?;
+
+    Ok(removed)
+}
 
-    Ok(backups)
+/// List all available backups, newest first
+pub fn list_backups() -> Result<Vec<BackupEntry>> {
+    sync_manifest(&get_backup_dir()?)
 }
 
-/// Restore from a backup file
-pub fn restore_backup(backup_path: &PathBuf) -> Result<Backup> {
-    let content = fs::read_to_string(backup_path).map_err(Error::Io)?;
-    let backup: Backup = serde_json::from_str(&content).map_err(Error::Serialization)?;
+/// Read a backup file, compressed or not
+pub fn restore_backup(backup_path: &Path) -> Result<Backup> {
+    let data = fs::read(backup_path).map_err(Error::Io)?;
+    let json = Compression::decompress(&data)?;
+    let mut backup: Backup = serde_json::from_slice(&json).map_err(Error::Serialization)?;
+    backup.migrate();
     Ok(backup)
 }
 
 /// Get the most recent backup
 pub fn get_latest_backup() -> Result<Option<(PathBuf, Backup)>> {
-    let backups = list_backups()?;
-    Ok(backups.into_iter().next())
+    let Some(entry) = list_backups()?.into_iter().next() else {
+        return Ok(None);
+    };
+    let backup = entry.load()?;
+    Ok(Some((entry.path, backup)))
 }
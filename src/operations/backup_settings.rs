@@ -0,0 +1,338 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use chrono::{Datelike, Local};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use ruzstd::decoding::StreamingDecoder;
+use ruzstd::encoding::{compress_to_vec, CompressionLevel};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+use super::backup::{get_backup_dir, BackupEntry};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// How backup files are written. Reading detects the format from the
+/// contents, so changing this never strands older backups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Plain, readable JSON
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Appended to `.json`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Gzip => ".gz",
+            Self::Zstd => ".zst",
+        }
+    }
+
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(GZIP_MAGIC) {
+            Self::Gzip
+        } else if data.starts_with(ZSTD_MAGIC) {
+            Self::Zstd
+        } else {
+            Self::None
+        }
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(data.to_vec()),
+            Self::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data).map_err(Error::Io)?;
+                encoder.finish().map_err(Error::Io)
+            }
+            Self::Zstd => Ok(compress_to_vec(data, CompressionLevel::Fastest)),
+        }
+    }
+
+    /// Decompress whatever format `data` is in
+    pub fn decompress(data: &[u8]) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        match Self::detect(data) {
+            Self::None => output.extend_from_slice(data),
+            Self::Gzip => {
+                GzDecoder::new(data)
+                    .read_to_end(&mut output)
+                    .map_err(Error::Io)?;
+            }
+            Self::Zstd => {
+                StreamingDecoder::new(data)
+                    .map_err(|e| {
+                        Error::Io(io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+                    })?
+                    .read_to_end(&mut output)
+                    .map_err(Error::Io)?;
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Which backups survive pruning. A backup is kept when any rule keeps it;
+/// the size limit then drops the oldest of those.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    /// The newest backups, whatever their age. The newest one is always kept.
+    pub keep_last: usize,
+
+    /// The newest backup of each of the last days that have backups
+    pub keep_daily: usize,
+
+    /// The newest backup of each of the last weeks that have backups
+    pub keep_weekly: usize,
+
+    /// Limit for all backups together in bytes, 0 for none
+    pub max_total_size: u64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            keep_last: 10,
+            keep_daily: 7,
+            keep_weekly: 4,
+            max_total_size: 100 * 1024 * 1024,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// e.g. "keep last 10, 7 daily, 4 weekly, at most 100.0 MB"
+    pub fn summary(&self) -> String {
+        let size = match self.max_total_size {
+            0 => "no size limit".to_string(),
+            bytes => format!("at most {:.1} MB", bytes as f64 / (1024.0 * 1024.0)),
+        };
+        format!(
+            "keep last {}, {} daily, {} weekly, {size}",
+            self.keep_last, self.keep_daily, self.keep_weekly
+        )
+    }
+
+    /// Entries to delete, given entries sorted newest first
+    pub fn expired<'a>(&self, entries: &'a [BackupEntry]) -> Vec<&'a BackupEntry> {
+        let mut kept = vec![false; entries.len()];
+
+        for keep in kept.iter_mut().take(self.keep_last.max(1)) {
+            *keep = true;
+        }
+
+        let mut days = HashSet::new();
+        let mut weeks = HashSet::new();
+        for (index, entry) in entries.iter().enumerate() {
+            let local = entry.timestamp.with_timezone(&Local);
+            if days.len() < self.keep_daily && days.insert(local.date_naive()) {
+                kept[index] = true;
+            }
+            let week = local.iso_week();
+            if weeks.len() < self.keep_weekly && weeks.insert((week.year(), week.week())) {
+                kept[index] = true;
+            }
+        }
+
+        if self.max_total_size > 0 {
+            let mut total: u64 = entries
+                .iter()
+                .zip(&kept)
+                .filter(|(_, keep)| **keep)
+                .map(|(entry, _)| entry.size)
+                .sum();
+            for index in (1..entries.len()).rev() {
+                if total <= self.max_total_size {
+                    break;
+                }
+                if kept[index] {
+                    kept[index] = false;
+                    total -= entries[index].size;
+                }
+            }
+        }
+
+        entries
+            .iter()
+            .zip(kept)
+            .filter(|(_, keep)| !keep)
+            .map(|(entry, _)| entry)
+            .collect()
+    }
+}
+
+/// Backup options, read from `settings.json` in the backup directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub compression: Compression,
+    /// Backups are only ever pruned once a policy is saved; fields left
+    /// out of it take the [defaults](RetentionPolicy::default)
+    pub retention: Option<RetentionPolicy>,
+}
+
+impl BackupSettings {
+    pub fn path() -> Result<PathBuf> {
+        Ok(get_backup_dir()?.join(SETTINGS_FILE))
+    }
+
+    /// The saved settings, or the defaults when there are none
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path).map_err(Error::Io)?;
+        serde_json::from_str(&content).map_err(Error::Serialization)
+    }
+
+    /// Write the settings; they apply from the next backup or `prune`
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(Error::Serialization)?;
+        fs::write(Self::path()?, json).map_err(Error::Io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+
+    /// One backup a day at noon UTC, newest on a Wednesday so every time
+    /// zone sees the first three in the same week
+    fn daily_entries(count: i64, size: u64) -> Vec<BackupEntry> {
+        let newest = Utc.with_ymd_and_hms(2024, 6, 26, 12, 0, 0).unwrap();
+        (0..count)
+            .map(|day| BackupEntry {
+                file_name: format!("backup_{day}.json"),
+                timestamp: newest - Duration::days(day),
+                version: String::new(),
+                description: None,
+                item_count: 0,
+                size,
+                path: PathBuf::from(format!("backup_{day}.json")),
+            })
+            .collect()
+    }
+
+    fn names(entries: Vec<&BackupEntry>) -> Vec<&str> {
+        entries.iter().map(|e| e.file_name.as_str()).collect()
+    }
+
+    #[test]
+    fn keeps_the_newest_backups() {
+        let policy = RetentionPolicy {
+            keep_last: 2,
+            keep_daily: 0,
+            keep_weekly: 0,
+            max_total_size: 0,
+        };
+        let entries = daily_entries(4, 1);
+
+        assert_eq!(
+            names(policy.expired(&entries)),
+            ["backup_2.json", "backup_3.json"]
+        );
+    }
+
+    #[test]
+    fn keeps_one_backup_per_day_and_week() {
+        let policy = RetentionPolicy {
+            keep_last: 1,
+            keep_daily: 3,
+            keep_weekly: 2,
+            max_total_size: 0,
+        };
+        let entries = daily_entries(21, 1);
+        let expired = policy.expired(&entries);
+
+        // Three days, then the newest backup of the week before
+        let kept: Vec<&str> = entries
+            .iter()
+            .filter(|e| !expired.iter().any(|x| x.path == e.path))
+            .map(|e| e.file_name.as_str())
+            .collect();
+        assert_eq!(kept.len(), 4);
+        assert_eq!(
+            &kept[..3],
+            ["backup_0.json", "backup_1.json", "backup_2.json"]
+        );
+    }
+
+    #[test]
+    fn size_limit_drops_the_oldest_but_never_the_newest() {
+        let policy = RetentionPolicy {
+            keep_last: 5,
+            keep_daily: 0,
+            keep_weekly: 0,
+            max_total_size: 25,
+        };
+        let entries = daily_entries(5, 10);
+
+        assert_eq!(policy.expired(&entries).len(), 3);
+
+        let tiny = RetentionPolicy {
+            max_total_size: 1,
+            ..policy
+        };
+        assert_eq!(tiny.expired(&entries).len(), 4);
+    }
+
+    #[test]
+    fn retention_is_off_until_configured() {
+        let settings: BackupSettings = serde_json::from_str(r#"{"compression":"gzip"}"#).unwrap();
+        assert_eq!(settings.retention, None);
+
+        let settings: BackupSettings =
+            serde_json::from_str(r#"{"retention":{"keep_last":3}}"#).unwrap();
+        let retention = settings.retention.unwrap();
+        assert_eq!(retention.keep_last, 3);
+        assert_eq!(retention.keep_daily, RetentionPolicy::default().keep_daily);
+    }
+
+    #[test]
+    fn saved_settings_read_back() {
+        let settings = BackupSettings {
+            compression: Compression::Zstd,
+            retention: Some(RetentionPolicy {
+                keep_last: 3,
+                ..RetentionPolicy::default()
+            }),
+        };
+        let json = serde_json::to_string_pretty(&settings).unwrap();
+        assert_eq!(
+            serde_json::from_str::<BackupSettings>(&json).unwrap(),
+            settings
+        );
+
+        assert_eq!(
+            settings.retention.unwrap().summary(),
+            "keep last 3, 7 daily, 4 weekly, at most 100.0 MB"
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(windows)]
+use windows::Win32::System::Registry::REG_SZ;
+
+use crate::error::{Error, Result};
+use crate::models::command_line::{join_command_line, quote_argument};
+use crate::models::{RawPayload, SourceType, StartupItem};
+use crate::operations::create_backup;
+use crate::sources::{
+    recreate_item, scan_all_sources, scanner_for, LogonTask, Shortcut, StartupFolderScanner,
+};
+
+/// Characters Windows does not allow in file and task names
+const INVALID_NAME_CHARS: &[char] = &['\\', '/', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Where a new item is registered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NewItemKind {
+    #[default]
+    Run,
+    RunOnce,
+    StartupFolder,
+    LogonTask,
+}
+
+impl NewItemKind {
+    pub fn all() -> &'static [NewItemKind] {
+        &[
+            Self::Run,
+            Self::RunOnce,
+            Self::StartupFolder,
+            Self::LogonTask,
+        ]
+    }
+
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::Run => "Run key",
+            Self::RunOnce => "RunOnce key",
+            Self::StartupFolder => "Startup folder shortcut",
+            Self::LogonTask => "Logon scheduled task",
+        }
+    }
+
+    /// The next kind, wrapping around, for cycling through them
+    pub fn next(&self) -> Self {
+        let all = Self::all();
+        let index = all.iter().position(|kind| kind == self).unwrap_or(0);
+        all[(index + 1) % all.len()]
+    }
+
+    /// The source the item shows up in once created
+    pub fn source(&self, all_users: bool) -> SourceType {
+        match (self, all_users) {
+            (Self::Run, false) => SourceType::RegistryCurrentUserRun,
+            (Self::Run, true) => SourceType::RegistryLocalMachineRun,
+            (Self::RunOnce, false) => SourceType::RegistryCurrentUserRunOnce,
+            (Self::RunOnce, true) => SourceType::RegistryLocalMachineRunOnce,
+            (Self::StartupFolder, false) => SourceType::StartupFolderUser,
+            (Self::StartupFolder, true) => SourceType::StartupFolderAllUsers,
+            (Self::LogonTask, _) => SourceType::ScheduledTask,
+        }
+    }
+}
+
+/// A program to register to run at logon
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewItem {
+    /// Value, shortcut or task name
+    pub name: String,
+    pub kind: NewItemKind,
+    /// For every user rather than the current one; needs administrator rights
+    pub all_users: bool,
+    /// Absolute path of the program
+    pub target: PathBuf,
+    pub arguments: Vec<String>,
+}
+
+impl NewItem {
+    pub fn new(name: impl Into<String>, kind: NewItemKind, target: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            target: target.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_arguments(mut self, arguments: Vec<String>) -> Self {
+        self.arguments = arguments;
+        self
+    }
+
+    pub fn with_all_users(mut self, all_users: bool) -> Self {
+        self.all_users = all_users;
+        self
+    }
+
+    pub fn source(&self) -> SourceType {
+        self.kind.source(self.all_users)
+    }
+
+    /// The full command line, with the program and arguments quoted
+    pub fn command_line(&self) -> String {
+        join_command_line(&self.target.to_string_lossy(), &self.arguments)
+    }
+
+    /// The arguments alone, quoted, as shortcuts and tasks store them
+    fn quoted_arguments(&self) -> String {
+        self.arguments
+            .iter()
+            .map(|arg| quote_argument(arg))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn working_dir(&self) -> String {
+        self.target
+            .parent()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Check the name and that the program exists
+    pub fn validate(&self) -> Result<()> {
+        validate_name(&self.name, self.source())?;
+        validate_target(&self.target)
+    }
+
+    /// The item as the scanner will see it, and what writes it
+    fn build(&self) -> Result<(StartupItem, RawPayload)> {
+        let source = self.source();
+        let target = self.target.to_string_lossy().to_string();
+
+        let built = match self.kind {
+            NewItemKind::Run | NewItemKind::RunOnce => {
+                let key = source.registry_path().unwrap_or_default();
+                let item = StartupItem::new(
+                    self.name.clone(),
+                    source,
+                    key.to_string(),
+                    self.command_line(),
+                );
+                (item, self.registry_value()?)
+            }
+            NewItemKind::StartupFolder => {
+                let folder = StartupFolderScanner::folder_for(source)
+                    .ok_or_else(|| Error::Unsupported(source.display_name().to_string()))?;
+                let file_name = format!("{}.lnk", self.name);
+                let item = StartupItem::new(
+                    self.name.clone(),
+                    source,
+                    folder.display().to_string(),
+                    folder.join(&file_name).display().to_string(),
+                );
+                let data = Shortcut::new(target)
+                    .with_arguments(self.quoted_arguments())
+                    .with_working_dir(self.working_dir())
+                    .to_bytes();
+                (item, RawPayload::File { file_name, data })
+            }
+            NewItemKind::LogonTask => {
+                let task_name = format!("\\{}", self.name);
+                let user = if self.all_users {
+                    None
+                } else {
+                    LogonTask::current_user()
+                };
+                let xml = LogonTask::new(target)
+                    .with_arguments(self.quoted_arguments())
+                    .with_working_directory(self.working_dir())
+                    .for_user(user)
+                    .to_xml();
+                let item = StartupItem::new(
+                    self.name.clone(),
+                    source,
+                    task_name.clone(),
+                    format!("Scheduled Task: {}", task_name),
+                );
+                (item, RawPayload::TaskXml { xml })
+            }
+        };
+
+        Ok(built)
+    }
+
+    /// The command line as a `REG_SZ` value
+    #[cfg(windows)]
+    fn registry_value(&self) -> Result<RawPayload> {
+        Ok(RawPayload::RegistryValue {
+            value_type: REG_SZ.0,
+            data: utf16_nul(&self.command_line()),
+        })
+    }
+
+    /// Run keys exist only on Windows
+    #[cfg(not(windows))]
+    fn registry_value(&self) -> Result<RawPayload> {
+        Err(Error::Unsupported(self.source().display_name().to_string()))
+    }
+}
+
+#[cfg(windows)]
+fn utf16_nul(text: &str) -> Vec<u8> {
+    text.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect()
+}
+
+/// A name must not be empty or padded, and names that become file or task
+/// names must be valid ones
+pub fn validate_name(name: &str, source: SourceType) -> Result<()> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err(Error::InvalidName("the name is empty".to_string()));
+    }
+    if trimmed != name {
+        return Err(Error::InvalidName(format!(
+            "\"{name}\" starts or ends with spaces"
+        )));
+    }
+
+    let is_file_name = source.is_startup_folder() || source == SourceType::ScheduledTask;
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_control() || (is_file_name && INVALID_NAME_CHARS.contains(c)))
+    {
+        return Err(Error::InvalidName(format!(
+            "\"{name}\" contains '{}'",
+            c.escape_default()
+        )));
+    }
+
+    Ok(())
+}
+
+/// A program path must be absolute and point at an existing file
+pub fn validate_target(target: &Path) -> Result<()> {
+    if target.as_os_str().is_empty() {
+        return Err(Error::InvalidPath("no program given".to_string()));
+    }
+    if !target.is_absolute() {
+        return Err(Error::InvalidPath(format!(
+            "{} is not an absolute path",
+            target.display()
+        )));
+    }
+    if !target.is_file() {
+        return Err(Error::InvalidPath(format!(
+            "{} does not exist",
+            target.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Register a new item after taking a backup that records it in the
+/// backup list. Existing items are never overwritten.
+pub fn create_item(new: &NewItem) -> Result<StartupItem> {
+    new.validate()?;
+    let (item, raw) = new.build()?;
+
+    let existing = scanner_for(item.source)?.scan()?;
+    if existing.iter().any(|other| other.id == item.id) {
+        return Err(Error::ItemExists(format!(
+            "{} in {}",
+            item.name,
+            item.source.display_name()
+        )));
+    }
+
+    create_backup(
+        &scan_all_sources(),
+        Some(format!(
+            "Before adding {} to {}",
+            item.name,
+            item.source.short_name()
+        )),
+    )?;
+
+    recreate_item(&item, Some(&raw))?;
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = r"C:\Program Files\App\app.exe";
+
+    fn new_item(kind: NewItemKind) -> NewItem {
+        NewItem::new("App", kind, TARGET)
+            .with_arguments(vec!["--minimized".to_string(), "two words".to_string()])
+    }
+
+    #[test]
+    fn names_must_be_trimmed_and_valid_file_names() {
+        assert!(validate_name("App", SourceType::StartupFolderUser).is_ok());
+        assert!(validate_name("", SourceType::RegistryCurrentUserRun).is_err());
+        assert!(validate_name(" App", SourceType::RegistryCurrentUserRun).is_err());
+        assert!(validate_name("App\n", SourceType::RegistryCurrentUserRun).is_err());
+
+        // Value names may hold characters file and task names cannot
+        assert!(validate_name("App: tray", SourceType::RegistryCurrentUserRun).is_ok());
+        assert!(validate_name("App: tray", SourceType::StartupFolderUser).is_err());
+        assert!(validate_name("App/tray", SourceType::ScheduledTask).is_err());
+    }
+
+    #[test]
+    fn targets_must_be_existing_absolute_files() {
+        assert!(validate_target(Path::new("")).is_err());
+        assert!(validate_target(Path::new("app.exe")).is_err());
+
+        let file = std::env::current_exe().unwrap();
+        assert!(validate_target(&file).is_ok());
+        assert!(validate_target(file.parent().unwrap()).is_err());
+        assert!(validate_target(&file.with_extension("missing")).is_err());
+    }
+
+    #[test]
+    fn quotes_the_program_and_arguments() {
+        assert_eq!(
+            new_item(NewItemKind::Run).command_line(),
+            r#""C:\Program Files\App\app.exe" --minimized "two words""#
+        );
+        assert_eq!(
+            new_item(NewItemKind::Run).quoted_arguments(),
+            r#"--minimized "two words""#
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn builds_registry_values() {
+        let (item, raw) = new_item(NewItemKind::RunOnce)
+            .with_all_users(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(item.source, SourceType::RegistryLocalMachineRunOnce);
+        assert_eq!(item.command, new_item(NewItemKind::RunOnce).command_line());
+        assert_eq!(
+            raw,
+            RawPayload::RegistryValue {
+                value_type: REG_SZ.0,
+                data: utf16_nul(&item.command),
+            }
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn registry_values_need_windows() {
+        assert!(matches!(
+            new_item(NewItemKind::RunOnce).build(),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn builds_startup_folder_shortcuts() {
+        let (item, raw) = new_item(NewItemKind::StartupFolder)
+            .with_all_users(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(item.source, SourceType::StartupFolderAllUsers);
+        let RawPayload::File { file_name, data } = raw else {
+            panic!("expected a shortcut, got {raw:?}");
+        };
+        assert_eq!(file_name, "App.lnk");
+        let shortcut = Shortcut::parse(&data).unwrap();
+        assert_eq!(shortcut.target, TARGET);
+        assert_eq!(shortcut.arguments, r#"--minimized "two words""#);
+    }
+
+    #[test]
+    fn builds_logon_tasks() {
+        let (item, raw) = new_item(NewItemKind::LogonTask)
+            .with_all_users(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(item.source, SourceType::ScheduledTask);
+        assert_eq!(item.source_location, "\\App");
+        let RawPayload::TaskXml { xml } = raw else {
+            panic!("expected a task, got {raw:?}");
+        };
+        assert!(xml.contains(&format!("<Command>{TARGET}</Command>")));
+        assert!(xml.contains("<Arguments>--minimized &quot;two words&quot;</Arguments>"));
+        assert!(!xml.contains("<UserId>"));
+    }
+}
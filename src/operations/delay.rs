@@ -0,0 +1,336 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, PathResolver, RawPayload, SourceType, StartupItem};
+use crate::operations::create_backup;
+use crate::sources::{
+    delete_item, modify_item, recreate_item, scan_all_sources, set_delayed_start, LogonTask,
+    Shortcut,
+};
+
+/// Delay used when none is given
+pub const DEFAULT_DELAY_SECS: u32 = 120;
+
+/// Task Scheduler folder the delayed tasks are created in
+const DELAYED_TASK_FOLDER: &str = "\\StartupChecker Delayed";
+
+const LINKS_FILE: &str = "delayed.json";
+
+/// An item that starts later than logon, and the task that starts it now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DelayLink {
+    pub created_at: DateTime<Utc>,
+    /// The original entry as it was before it was disabled
+    pub item: StartupItem,
+    /// The logon task standing in for it; services delay themselves
+    pub task: Option<StartupItem>,
+    pub delay_secs: u32,
+}
+
+impl DelayLink {
+    /// Whether the link is about this item, the original or its task
+    pub fn involves(&self, item: &StartupItem) -> bool {
+        self.item.id == item.id || self.task.as_ref().is_some_and(|task| task.id == item.id)
+    }
+}
+
+fn links_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("startup-checker");
+
+    fs::create_dir_all(&data_dir).map_err(Error::Io)?;
+
+    Ok(data_dir.join(LINKS_FILE))
+}
+
+/// Items delayed by this tool, oldest first
+pub fn list_delayed() -> Result<Vec<DelayLink>> {
+    let path = links_path()?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).map_err(Error::Serialization),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(Error::Io(e)),
+    }
+}
+
+/// The link an item or its task belongs to
+pub fn find_delayed(item: &StartupItem) -> Result<Option<DelayLink>> {
+    Ok(list_delayed()?.into_iter().find(|link| link.involves(item)))
+}
+
+fn save_links(links: &[DelayLink]) -> Result<()> {
+    // Write then rename, so a crash never loses the links
+    let path = links_path()?;
+    let json = serde_json::to_string_pretty(links).map_err(Error::Serialization)?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, json).map_err(Error::Io)?;
+    fs::rename(&temp, &path).map_err(Error::Io)
+}
+
+/// The program, arguments and working directory an entry starts
+fn launch_of(item: &StartupItem) -> Result<(String, String, String)> {
+    let (program, arguments, working_dir) = match item.source {
+        SourceType::RegistryCurrentUserRun
+        | SourceType::RegistryLocalMachineRun
+        | SourceType::RegistryLocalMachineWow6432 => {
+            let resolver = PathResolver::current();
+            let parsed = resolver.resolve_command(&item.command);
+            let program = resolver.normalize(&parsed.program);
+            (program, parsed.raw_args, String::new())
+        }
+        SourceType::StartupFolderUser | SourceType::StartupFolderAllUsers => {
+            let path = Path::new(&item.command);
+            if !path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"))
+            {
+                return Err(Error::Unsupported(format!(
+                    "delaying {}, which is not a shortcut",
+                    item.name
+                )));
+            }
+            let data = fs::read(path).map_err(Error::Io)?;
+            let shortcut = Shortcut::parse(&data).ok_or_else(|| {
+                Error::Unsupported(format!(
+                    "delaying {}, whose shortcut has no program path",
+                    item.name
+                ))
+            })?;
+            (shortcut.target, shortcut.arguments, shortcut.working_dir)
+        }
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "delaying {} items",
+                item.source.display_name()
+            )))
+        }
+    };
+
+    if program.is_empty() {
+        return Err(Error::InvalidPath(format!("{} has no program", item.name)));
+    }
+
+    let working_dir = if working_dir.is_empty() {
+        Path::new(&program)
+            .parent()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default()
+    } else {
+        working_dir
+    };
+
+    Ok((program, arguments, working_dir))
+}
+
+/// The logon task that starts the item's program after the delay
+fn delayed_task(item: &StartupItem, delay_secs: u32) -> Result<(StartupItem, RawPayload)> {
+    let (program, arguments, working_dir) = launch_of(item)?;
+    let user = if item.source.requires_admin() {
+        None
+    } else {
+        LogonTask::current_user()
+    };
+
+    let task_name = format!("{DELAYED_TASK_FOLDER}\\{}", item.name);
+    let xml = LogonTask::new(program)
+        .with_arguments(arguments)
+        .with_working_directory(working_dir)
+        .for_user(user)
+        .with_delay(delay_secs)
+        .to_xml();
+    let task = StartupItem::new(
+        item.name.clone(),
+        SourceType::ScheduledTask,
+        task_name.clone(),
+        format!("Scheduled Task: {}", task_name),
+    );
+
+    Ok((task, RawPayload::TaskXml { xml }))
+}
+
+/// Start an item later than logon after taking a backup. Run and Startup
+/// folder entries are disabled and replaced by a logon task with a delay;
+/// automatic services are switched to delayed automatic start.
+pub fn delay_item(item: &StartupItem, delay_secs: u32) -> Result<DelayLink> {
+    let mut links = list_delayed()?;
+    if links.iter().any(|link| link.involves(item)) {
+        return Err(Error::AlreadyDelayed(item.name.clone()));
+    }
+    if !item.status.is_enabled() {
+        return Err(Error::Unsupported(format!(
+            "delaying {}, which is disabled",
+            item.name
+        )));
+    }
+
+    let task = match item.source {
+        SourceType::WindowsService => None,
+        _ => Some(delayed_task(item, delay_secs)?),
+    };
+
+    create_backup(
+        &scan_all_sources(),
+        Some(format!("Before delaying {}", item.name)),
+    )?;
+
+    let task = match task {
+        None => {
+            set_delayed_start(item, true)?;
+            None
+        }
+        Some((task, raw)) => {
+            recreate_item(&task, Some(&raw))?;
+            if let Err(e) = modify_item(item, ItemStatus::Disabled) {
+                let _ = delete_item(&task);
+                return Err(e);
+            }
+            Some(task)
+        }
+    };
+
+    let link = DelayLink {
+        created_at: Utc::now(),
+        item: item.clone(),
+        task,
+        delay_secs,
+    };
+    links.push(link.clone());
+    if let Err(e) = save_links(&links) {
+        roll_back(item, link.task.as_ref());
+        return Err(e);
+    }
+
+    Ok(link)
+}
+
+/// Undo a delay whose link could not be saved, so no task is left behind
+/// that `undelay` would not know about
+fn roll_back(item: &StartupItem, task: Option<&StartupItem>) {
+    match task {
+        None => {
+            let _ = set_delayed_start(item, false);
+        }
+        Some(task) => {
+            let _ = delete_item(task);
+            let mut disabled = item.clone();
+            disabled.status = ItemStatus::Disabled;
+            let _ = modify_item(&disabled, ItemStatus::Enabled);
+        }
+    }
+}
+
+/// Start a delayed item at logon again: re-enable the original entry and
+/// remove its task, or turn delayed automatic start off for a service
+pub fn undelay_item(link: &DelayLink) -> Result<()> {
+    match &link.task {
+        None => set_delayed_start(&link.item, false)?,
+        Some(task) => {
+            modify_item(&link.item, ItemStatus::Enabled)?;
+            delete_item(task)?;
+        }
+    }
+
+    let mut links = list_delayed()?;
+    links.retain(|other| other.item.id != link.item.id);
+    save_links(&links)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::fake_root;
+
+    fn item(source: SourceType, location: &str, command: &str) -> StartupItem {
+        StartupItem::new(
+            "App".to_string(),
+            source,
+            location.to_string(),
+            command.to_string(),
+        )
+    }
+
+    #[test]
+    fn links_involve_the_item_and_its_task() {
+        let original = item(SourceType::RegistryCurrentUserRun, "Run", "app.exe");
+        let task = item(
+            SourceType::ScheduledTask,
+            DELAYED_TASK_FOLDER,
+            "Scheduled Task",
+        );
+        let link = DelayLink {
+            created_at: Utc::now(),
+            item: original.clone(),
+            task: Some(task.clone()),
+            delay_secs: DEFAULT_DELAY_SECS,
+        };
+
+        assert!(link.involves(&original));
+        assert!(link.involves(&task));
+        assert!(!link.involves(&item(SourceType::RegistryLocalMachineRun, "Run", "app.exe")));
+
+        let service = DelayLink { task: None, ..link };
+        assert!(service.involves(&original));
+        assert!(!service.involves(&task));
+    }
+
+    #[test]
+    fn run_entries_launch_their_command() {
+        let run = item(
+            SourceType::RegistryCurrentUserRun,
+            "Run",
+            r#""C:\Program Files\App\app.exe" --tray "two words""#,
+        );
+        let (program, arguments, _) = launch_of(&run).unwrap();
+
+        assert!(program.ends_with("app.exe"));
+        assert_eq!(arguments, r#"--tray "two words""#);
+    }
+
+    #[test]
+    fn startup_shortcuts_launch_their_target() {
+        let dir = fake_root("delay-shortcut");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("App.lnk");
+        let shortcut = Shortcut::new(r"C:\Tools\app.exe")
+            .with_arguments("--tray")
+            .with_working_dir(r"C:\Work");
+        fs::write(&path, shortcut.to_bytes()).unwrap();
+
+        let entry = item(
+            SourceType::StartupFolderUser,
+            &dir.display().to_string(),
+            &path.display().to_string(),
+        );
+        assert_eq!(
+            launch_of(&entry).unwrap(),
+            (
+                r"C:\Tools\app.exe".to_string(),
+                "--tray".to_string(),
+                r"C:\Work".to_string()
+            )
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn other_entries_cannot_be_delayed() {
+        let script = item(
+            SourceType::StartupFolderUser,
+            "Startup",
+            r"C:\Startup\run.bat",
+        );
+        assert!(matches!(launch_of(&script), Err(Error::Unsupported(_))));
+
+        let cron = item(SourceType::CronReboot, "/etc/crontab", "/usr/bin/app");
+        assert!(matches!(launch_of(&cron), Err(Error::Unsupported(_))));
+
+        let empty = item(SourceType::RegistryCurrentUserRun, "Run", "");
+        assert!(matches!(launch_of(&empty), Err(Error::InvalidPath(_))));
+    }
+}
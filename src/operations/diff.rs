@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::models::{FileInfo, ItemStatus, SourceType, StartupItem};
+
+/// How an item differs between two snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    StatusChanged,
+    CommandChanged,
+    /// Same command, but the executable's contents changed
+    FileChanged,
+}
+
+impl ChangeKind {
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::Added => "Added",
+            Self::Removed => "Removed",
+            Self::StatusChanged => "Status",
+            Self::CommandChanged => "Command",
+            Self::FileChanged => "File",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Added => "+",
+            Self::Removed => "-",
+            Self::StatusChanged | Self::CommandChanged | Self::FileChanged => "~",
+        }
+    }
+}
+
+/// One difference for one item
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemChange {
+    pub kind: ChangeKind,
+    pub id: String,
+    pub name: String,
+    pub source: SourceType,
+    /// The status, command or file before, depending on the kind
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl ItemChange {
+    fn new(kind: ChangeKind, item: &StartupItem) -> Self {
+        Self {
+            kind,
+            id: item.id.clone(),
+            name: item.name.clone(),
+            source: item.source,
+            before: None,
+            after: None,
+        }
+    }
+
+    fn values(mut self, before: impl Into<String>, after: impl Into<String>) -> Self {
+        self.before = Some(before.into());
+        self.after = Some(after.into());
+        self
+    }
+
+    /// e.g. "Enabled -> Disabled"
+    pub fn summary(&self) -> String {
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => format!("{before} -> {after}"),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Differences between two sets of startup items, matched by id
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    /// Where the older items came from, e.g. a backup file name
+    pub from: String,
+    /// Where the newer items came from, e.g. "live"
+    pub to: String,
+    pub changes: Vec<ItemChange>,
+}
+
+impl SnapshotDiff {
+    pub fn between(from: &str, old: &[StartupItem], to: &str, new: &[StartupItem]) -> Self {
+        let old_by_id: HashMap<&str, &StartupItem> =
+            old.iter().map(|item| (item.id.as_str(), item)).collect();
+        let new_by_id: HashMap<&str, &StartupItem> =
+            new.iter().map(|item| (item.id.as_str(), item)).collect();
+
+        let mut changes = Vec::new();
+
+        for item in new {
+            let Some(previous) = old_by_id.get(item.id.as_str()) else {
+                changes.push(ItemChange::new(ChangeKind::Added, item));
+                continue;
+            };
+            changes.extend(Self::compare(previous, item));
+        }
+
+        changes.extend(
+            old.iter()
+                .filter(|item| !new_by_id.contains_key(item.id.as_str()))
+                .map(|item| ItemChange::new(ChangeKind::Removed, item)),
+        );
+
+        changes.sort_by(|a, b| {
+            (a.kind, a.source, a.name.to_lowercase()).cmp(&(
+                b.kind,
+                b.source,
+                b.name.to_lowercase(),
+            ))
+        });
+
+        Self {
+            from: from.to_string(),
+            to: to.to_string(),
+            changes,
+        }
+    }
+
+    fn compare(old: &StartupItem, new: &StartupItem) -> Vec<ItemChange> {
+        let mut changes = Vec::new();
+
+        if Self::effective(old.status) != Self::effective(new.status) {
+            changes.push(
+                ItemChange::new(ChangeKind::StatusChanged, new)
+                    .values(old.status.display(), new.status.display()),
+            );
+        }
+
+        // Legacy items carry no fingerprint, so compare the commands themselves
+        if old.command.trim() != new.command.trim() {
+            changes.push(
+                ItemChange::new(ChangeKind::CommandChanged, new)
+                    .values(old.command.clone(), new.command.clone()),
+            );
+        } else if let (Some(old_file), Some(new_file)) = (&old.file_info, &new.file_info) {
+            if old_file.content_changed(new_file) {
+                // Older backups carry hashes, newer ones only size and times
+                let describe = |info: &FileInfo| {
+                    if old_file.is_hashed() && new_file.is_hashed() {
+                        info.sha256.clone()
+                    } else {
+                        info.summary()
+                    }
+                };
+                changes.push(
+                    ItemChange::new(ChangeKind::FileChanged, new)
+                        .values(describe(old_file), describe(new_file)),
+                );
+            }
+        }
+
+        changes
+    }
+
+    /// Pending and still-triggered are derived from the surroundings, so
+    /// they compare equal to Enabled and Disabled
+    fn effective(status: ItemStatus) -> ItemStatus {
+        match status {
+            ItemStatus::Pending => ItemStatus::Enabled,
+            ItemStatus::DisabledButTriggered => ItemStatus::Disabled,
+            status => status,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.kind == kind).count()
+    }
+
+    /// e.g. "2 added, 1 removed, 3 status changed, 0 command changed, 0 file changed"
+    pub fn summary(&self) -> String {
+        [
+            (ChangeKind::Added, "added"),
+            (ChangeKind::Removed, "removed"),
+            (ChangeKind::StatusChanged, "status changed"),
+            (ChangeKind::CommandChanged, "command changed"),
+            (ChangeKind::FileChanged, "file changed"),
+        ]
+        .iter()
+        .map(|(kind, label)| format!("{} {label}", self.count(*kind)))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+
+    /// Plain text report, one line per change
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Changes from {} to {}\n", self.from, self.to);
+        if self.is_empty() {
+            text.push_str("No changes\n");
+            return text;
+        }
+
+        for change in &self.changes {
+            let _ = write!(
+                text,
+                "{} {:<8} {} [{}]",
+                change.kind.symbol(),
+                change.kind.display(),
+                change.name,
+                change.source.short_name()
+            );
+            let summary = change.summary();
+            if !summary.is_empty() {
+                let _ = write!(text, ": {summary}");
+            }
+            text.push('\n');
+        }
+
+        let _ = writeln!(text, "\n{}", self.summary());
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, command: &str) -> StartupItem {
+        StartupItem::new(
+            name.to_string(),
+            SourceType::CronReboot,
+            "/etc/crontab".to_string(),
+            command.to_string(),
+        )
+    }
+
+    fn file(size: u64, sha256: &str) -> FileInfo {
+        FileInfo {
+            size,
+            created: None,
+            modified: None,
+            md5: String::new(),
+            sha1: String::new(),
+            sha256: sha256.to_string(),
+            pe_timestamp: None,
+        }
+    }
+
+    fn kinds(diff: &SnapshotDiff) -> Vec<(ChangeKind, &str)> {
+        diff.changes
+            .iter()
+            .map(|c| (c.kind, c.name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_each_kind_of_change() {
+        let mut updated = item("updated", "/usr/bin/updated");
+        updated.file_info = Some(file(10, "aaaa"));
+        let old = vec![
+            item("removed", "/usr/bin/removed"),
+            item("toggled", "/usr/bin/toggled"),
+            item("edited", "/usr/bin/edited"),
+            updated.clone(),
+            item("same", "/usr/bin/same"),
+        ];
+
+        updated.file_info = Some(file(10, "bbbb"));
+        let new = vec![
+            item("added", "/usr/bin/added"),
+            item("toggled", "/usr/bin/toggled").with_status(ItemStatus::Disabled),
+            item("edited", "/usr/bin/edited --verbose"),
+            updated,
+            item("same", "/usr/bin/same"),
+        ];
+
+        let diff = SnapshotDiff::between("backup", &old, "live", &new);
+        assert_eq!(
+            kinds(&diff),
+            [
+                (ChangeKind::Added, "added"),
+                (ChangeKind::Removed, "removed"),
+                (ChangeKind::StatusChanged, "toggled"),
+                (ChangeKind::CommandChanged, "edited"),
+                (ChangeKind::FileChanged, "updated"),
+            ]
+        );
+        assert_eq!(diff.changes[2].summary(), "Enabled -> Disabled");
+        assert_eq!(
+            diff.changes[3].summary(),
+            "/usr/bin/edited -> /usr/bin/edited --verbose"
+        );
+        assert_eq!(diff.changes[4].summary(), "aaaa -> bbbb");
+    }
+
+    #[test]
+    fn file_changes_need_the_same_command_and_both_fingerprints() {
+        let mut old = item("app", "/usr/bin/app");
+        old.file_info = Some(file(10, "aaaa"));
+
+        let unknown = item("app", "/usr/bin/app");
+        assert!(SnapshotDiff::between("a", &[old.clone()], "b", &[unknown]).is_empty());
+
+        let mut moved = item("app", "/opt/app");
+        moved.file_info = Some(file(20, "bbbb"));
+        let diff = SnapshotDiff::between("a", &[old], "b", &[moved]);
+        assert_eq!(kinds(&diff), [(ChangeKind::CommandChanged, "app")]);
+    }
+
+    #[test]
+    fn derived_statuses_compare_as_their_effective_status() {
+        let old = [
+            item("once", "/usr/bin/once"),
+            item("triggered", "/usr/bin/triggered").with_status(ItemStatus::Disabled),
+        ];
+        let new = [
+            item("once", "/usr/bin/once").with_status(ItemStatus::Pending),
+            item("triggered", "/usr/bin/triggered").with_status(ItemStatus::DisabledButTriggered),
+        ];
+        assert!(SnapshotDiff::between("a", &old, "b", &new).is_empty());
+
+        let disabled = [item("once", "/usr/bin/once").with_status(ItemStatus::DisabledByPolicy)];
+        let diff = SnapshotDiff::between("a", &new[..1], "b", &disabled);
+        assert_eq!(kinds(&diff), [(ChangeKind::StatusChanged, "once")]);
+    }
+
+    #[test]
+    fn formats_a_text_report() {
+        let old = [item("edited", "/usr/bin/edited")];
+        let new = [
+            item("edited", "/usr/bin/edited -q"),
+            item("added", "/usr/bin/added"),
+        ];
+
+        assert_eq!(
+            SnapshotDiff::between("backup.json", &old, "live", &new).to_text(),
+            "Changes from backup.json to live\n\
+             + Added    added [Cron]\n\
+             ~ Command  edited [Cron]: /usr/bin/edited -> /usr/bin/edited -q\n\
+             \n\
+             1 added, 0 removed, 0 status changed, 1 command changed, 0 file changed\n"
+        );
+        assert_eq!(
+            SnapshotDiff::between("backup.json", &old, "live", &old).to_text(),
+            "Changes from backup.json to live\nNo changes\n"
+        );
+    }
+}
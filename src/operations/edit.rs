@@ -0,0 +1,125 @@
+use std::path::Path;
+
+use crate::error::{Error, Result};
+use crate::models::{PathResolver, RawPayload, SourceType, StartupItem};
+use crate::operations::{create_backup, validate_name};
+use crate::sources::{capture_item, edit_item, exec_command, scan_all_sources, scanner_for};
+
+/// A new name and command line for an existing item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemEdit {
+    pub name: String,
+    pub command: String,
+}
+
+impl ItemEdit {
+    /// Start from the item as it is
+    pub fn for_item(item: &StartupItem) -> Self {
+        Self {
+            name: item.name.clone(),
+            command: current_command(item),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = command.into();
+        self
+    }
+
+    /// Whether anything differs from the item
+    pub fn changes(&self, item: &StartupItem) -> bool {
+        self.name != item.name || self.command != current_command(item)
+    }
+
+    /// Check the name and, if the command changed, that its program exists
+    pub fn validate(&self, item: &StartupItem) -> Result<()> {
+        validate_name(&self.name, item.source)?;
+
+        if self.command != current_command(item) {
+            validate_program(&self.command)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The command line an edit starts from. Tasks keep theirs in the task
+/// definition rather than in the item.
+pub fn current_command(item: &StartupItem) -> String {
+    if item.source == SourceType::ScheduledTask {
+        if let Ok(Some(RawPayload::TaskXml { xml })) = capture_item(item) {
+            if let Some(command) = exec_command(&xml) {
+                return command;
+            }
+        }
+    }
+    item.command.clone()
+}
+
+/// Where the item lives once renamed: tasks are named by their path, other
+/// items keep their key or folder
+fn location_after_rename(item: &StartupItem, name: &str) -> String {
+    match item.source_location.rsplit_once('\\') {
+        Some((folder, _)) if item.source == SourceType::ScheduledTask => {
+            format!("{folder}\\{name}")
+        }
+        _ => item.source_location.clone(),
+    }
+}
+
+/// The program of a command line must exist, after expanding variables.
+/// Sources split the command with the same
+/// [`split_command`](PathResolver::split_command) when writing it.
+fn validate_program(command: &str) -> Result<()> {
+    let resolver = PathResolver::current();
+    let parsed = resolver.split_command(command);
+    if parsed.program.is_empty() {
+        return Err(Error::InvalidPath("no program given".to_string()));
+    }
+
+    let program = resolver
+        .resolve_program(&parsed.program)
+        .to_string_lossy()
+        .to_string();
+    if !Path::new(&program).is_file() {
+        return Err(Error::InvalidPath(format!("{program} does not exist")));
+    }
+    Ok(())
+}
+
+/// Rename an item or change its command line after taking a backup.
+/// Renaming onto another item of the same source is refused.
+pub fn apply_edit(item: &StartupItem, edit: &ItemEdit) -> Result<()> {
+    if !edit.changes(item) {
+        return Ok(());
+    }
+    edit.validate(item)?;
+
+    if edit.name != item.name {
+        let location = location_after_rename(item, &edit.name);
+        let taken = scanner_for(item.source)?.scan()?.into_iter().any(|other| {
+            other.id != item.id
+                && other.source_location.eq_ignore_ascii_case(&location)
+                && other.name.eq_ignore_ascii_case(&edit.name)
+        });
+        if taken {
+            return Err(Error::ItemExists(format!(
+                "{} in {}",
+                edit.name,
+                item.source.display_name()
+            )));
+        }
+    }
+
+    create_backup(
+        &scan_all_sources(),
+        Some(format!("Before editing {}", item.name)),
+    )?;
+
+    edit_item(item, &edit.name, &edit.command)
+}
@@ -1,3 +1,35 @@
 mod backup;
+mod backup_settings;
+mod create;
+mod delay;
+mod diff;
+mod edit;
+mod policy;
+mod profile;
+mod quarantine;
+mod restore;
+mod transaction;
 
-pub use backup::{create_backup, get_backup_dir, list_backups, restore_backup, Backup};
+pub use backup::{
+    create_backup, get_backup_dir, get_latest_backup, list_backups, prune_backups, restore_backup,
+    Backup, BackupEntry,
+};
+pub use backup_settings::{BackupSettings, Compression, RetentionPolicy};
+pub use create::{create_item, validate_name, validate_target, NewItem, NewItemKind};
+pub use delay::{
+    delay_item, find_delayed, list_delayed, undelay_item, DelayLink, DEFAULT_DELAY_SECS,
+};
+pub use diff::{ChangeKind, ItemChange, SnapshotDiff};
+pub use edit::{apply_edit, current_command, ItemEdit};
+pub use policy::{
+    enforce_policy, Drift, DriftKind, EnforceReport, Policy, PolicyRule, WantedState,
+};
+pub use profile::{
+    delete_profile, get_profiles_dir, list_profiles, load_profile, save_profile, switch_profile,
+    Profile, ProfileEntry, ProfilePlan,
+};
+pub use quarantine::{get_quarantine_dir, list_quarantine, quarantine_item, QuarantineEntry};
+pub use restore::{RestoreAction, RestorePlan, RestoreResult, RestoreStep};
+pub use transaction::{
+    ChangeOutcome, ChangeResult, Transaction, TransactionMode, TransactionReport,
+};
@@ -0,0 +1,482 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, SourceType, StartupItem};
+use crate::operations::{
+    create_backup, quarantine_item, Transaction, TransactionMode, TransactionReport,
+};
+use crate::sources::scan_all_sources;
+
+/// What a policy wants an item to be
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WantedState {
+    Enabled,
+    Disabled,
+    /// Deleted into the quarantine
+    Absent,
+    /// Left as it is
+    #[default]
+    Ignore,
+}
+
+/// One rule of a policy. The name may use `*` and `?` wildcards and is
+/// matched without regard to case.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    /// Source by its full, short or type name, e.g. `HKCU\Run`; any source when missing
+    #[serde(default)]
+    pub source: Option<String>,
+    pub name: String,
+    pub state: WantedState,
+
+    #[serde(skip)]
+    source_type: Option<SourceType>,
+}
+
+impl PolicyRule {
+    fn is_pattern(&self) -> bool {
+        self.name.contains(['*', '?'])
+    }
+
+    fn matches(&self, item: &StartupItem) -> bool {
+        self.source_type.is_none_or(|source| source == item.source)
+            && wildcard_match(&self.name, &item.name)
+    }
+}
+
+/// The wanted state of startup items, read from a TOML or YAML file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Policy {
+    /// What items no rule matches should be
+    #[serde(default)]
+    pub default: WantedState,
+
+    #[serde(default, rename = "item", alias = "items")]
+    pub rules: Vec<PolicyRule>,
+}
+
+/// How an item differs from the policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DriftKind {
+    ShouldBeEnabled,
+    ShouldBeDisabled,
+    ShouldBeAbsent,
+    /// A rule names an item that does not exist
+    Missing,
+}
+
+impl DriftKind {
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::ShouldBeEnabled => "Should be enabled",
+            Self::ShouldBeDisabled => "Should be disabled",
+            Self::ShouldBeAbsent => "Should be absent",
+            Self::Missing => "Missing",
+        }
+    }
+}
+
+/// One difference between the system and the policy
+#[derive(Debug, Clone, Serialize)]
+pub struct Drift {
+    pub kind: DriftKind,
+    pub name: String,
+    pub source: Option<SourceType>,
+
+    /// The live item; `None` for missing items
+    #[serde(skip)]
+    pub item: Option<StartupItem>,
+}
+
+impl Drift {
+    fn of(kind: DriftKind, item: &StartupItem) -> Self {
+        Self {
+            kind,
+            name: item.name.clone(),
+            source: Some(item.source),
+            item: Some(item.clone()),
+        }
+    }
+
+    /// Whether enforcing can fix it. Missing items cannot be created from a
+    /// name, and policy settings win over local ones.
+    pub fn is_fixable(&self) -> bool {
+        match (&self.item, self.kind) {
+            (None, _) | (_, DriftKind::Missing) => false,
+            (Some(_), DriftKind::ShouldBeAbsent) => true,
+            (Some(item), _) => item.status.can_toggle(),
+        }
+    }
+
+    /// The name and source, for reports
+    pub fn label(&self) -> String {
+        match self.source {
+            Some(source) => format!("{} ({})", self.name, source.display_name()),
+            None => self.name.clone(),
+        }
+    }
+}
+
+impl Policy {
+    /// Read a policy, as YAML for `.yaml` and `.yml` files and TOML otherwise
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(Error::Io)?;
+        let is_yaml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+
+        let mut policy: Policy = if is_yaml {
+            serde_yaml::from_str(&content)
+                .map_err(|e| Error::Policy(format!("{}: {e}", path.display())))?
+        } else {
+            toml::from_str(&content)
+                .map_err(|e| Error::Policy(format!("{}: {e}", path.display())))?
+        };
+
+        for rule in &mut policy.rules {
+            if rule.name.trim().is_empty() {
+                return Err(Error::Policy("every item needs a name".to_string()));
+            }
+            if let Some(source) = &rule.source {
+                rule.source_type = Some(parse_source(source).ok_or_else(|| {
+                    Error::Policy(format!("unknown source {source:?} for {}", rule.name))
+                })?);
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// The rule for an item: rules naming it exactly win over patterns,
+    /// then the first rule in the file wins
+    fn rule_for(&self, item: &StartupItem) -> Option<&PolicyRule> {
+        let mut matching = self.rules.iter().filter(|rule| rule.matches(item));
+        let first = matching.next()?;
+        if !first.is_pattern() {
+            return Some(first);
+        }
+        matching.find(|rule| !rule.is_pattern()).or(Some(first))
+    }
+
+    /// Everything about the items that differs from the policy
+    pub fn check(&self, items: &[StartupItem]) -> Vec<Drift> {
+        let mut drift: Vec<Drift> = items
+            .iter()
+            .filter_map(|item| {
+                let wanted = self.rule_for(item).map_or(self.default, |rule| rule.state);
+                let kind = match wanted {
+                    WantedState::Enabled if !item.status.is_enabled() => DriftKind::ShouldBeEnabled,
+                    WantedState::Disabled if item.status.is_enabled() => {
+                        DriftKind::ShouldBeDisabled
+                    }
+                    WantedState::Absent => DriftKind::ShouldBeAbsent,
+                    _ => return None,
+                };
+                Some(Drift::of(kind, item))
+            })
+            .collect();
+
+        // Exact rules for items that should exist
+        for rule in &self.rules {
+            let should_exist = matches!(rule.state, WantedState::Enabled | WantedState::Disabled);
+            if should_exist && !rule.is_pattern() && !items.iter().any(|item| rule.matches(item)) {
+                drift.push(Drift {
+                    kind: DriftKind::Missing,
+                    name: rule.name.clone(),
+                    source: rule.source_type,
+                    item: None,
+                });
+            }
+        }
+
+        drift
+    }
+}
+
+/// A source by its type name (`RegistryCurrentUserRun`), short name
+/// (`HKCU\Run`) or display name, in any case
+fn parse_source(text: &str) -> Option<SourceType> {
+    SourceType::all().iter().copied().find(|source| {
+        format!("{source:?}").eq_ignore_ascii_case(text)
+            || source.short_name().eq_ignore_ascii_case(text)
+            || source.display_name().eq_ignore_ascii_case(text)
+    })
+}
+
+/// Match `*` (any run of characters) and `?` (one character), ignoring case
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was, and the text position it is trying to cover
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the `*` cover one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// What enforcing a policy did
+#[derive(Debug, Clone)]
+pub struct EnforceReport {
+    /// The enabling and disabling, applied together
+    pub changes: TransactionReport,
+    /// Items deleted into the quarantine
+    pub deleted: Vec<String>,
+    /// Items that could not be deleted, and why
+    pub delete_failures: Vec<(String, String)>,
+}
+
+impl EnforceReport {
+    pub fn succeeded(&self) -> bool {
+        self.changes.succeeded() && self.delete_failures.is_empty()
+    }
+}
+
+/// Fix the drift that can be fixed after taking a backup. Status changes
+/// are applied as one transaction; items that should be absent are moved
+/// to the quarantine afterwards, unless the transaction was rolled back.
+pub fn enforce_policy(
+    label: &str,
+    drift: &[Drift],
+    mode: TransactionMode,
+) -> Result<EnforceReport> {
+    let mut transaction = Transaction::new(mode);
+    let mut to_delete = Vec::new();
+    for entry in drift.iter().filter(|entry| entry.is_fixable()) {
+        let Some(item) = &entry.item else {
+            continue;
+        };
+        match entry.kind {
+            DriftKind::ShouldBeEnabled => transaction.push(item, ItemStatus::Enabled),
+            DriftKind::ShouldBeDisabled => transaction.push(item, ItemStatus::Disabled),
+            DriftKind::ShouldBeAbsent => to_delete.push(item),
+            DriftKind::Missing => {}
+        }
+    }
+
+    if !transaction.is_empty() || !to_delete.is_empty() {
+        create_backup(
+            &scan_all_sources(),
+            Some(format!("Before enforcing {label}")),
+        )?;
+    }
+
+    let mut report = EnforceReport {
+        changes: transaction.commit(),
+        deleted: Vec::new(),
+        delete_failures: Vec::new(),
+    };
+    if report.changes.rolled_back() {
+        return Ok(report);
+    }
+
+    for item in to_delete {
+        match quarantine_item(item) {
+            Ok(_) => report.deleted.push(item.name.clone()),
+            Err(e) => report
+                .delete_failures
+                .push((item.name.clone(), e.to_string())),
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, source: SourceType, status: ItemStatus) -> StartupItem {
+        StartupItem::new(
+            name.to_string(),
+            source,
+            "/etc/crontab".to_string(),
+            format!("/usr/bin/{}", name.to_lowercase()),
+        )
+        .with_status(status)
+    }
+
+    fn rule(name: &str, source: Option<SourceType>, state: WantedState) -> PolicyRule {
+        PolicyRule {
+            source: source.map(|s| s.short_name().to_string()),
+            name: name.to_string(),
+            state,
+            source_type: source,
+        }
+    }
+
+    fn load(extension: &str, content: &str) -> Result<Policy> {
+        let path = std::env::temp_dir().join(format!(
+            "startup-checker-policy-{}.{extension}",
+            std::process::id()
+        ));
+        fs::write(&path, content).unwrap();
+        let policy = Policy::load(&path);
+        let _ = fs::remove_file(&path);
+        policy
+    }
+
+    #[test]
+    fn wildcards_match_runs_and_single_characters() {
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("Steam*", "steamwebhelper"));
+        assert!(wildcard_match("*updater*", "Google Updater Task"));
+        assert!(wildcard_match("app?", "App1"));
+        assert!(wildcard_match("a*b*c", "aXbYbZc"));
+        assert!(!wildcard_match("app?", "app"));
+        assert!(!wildcard_match("Steam", "Steam Client"));
+        assert!(!wildcard_match("*.exe", "app.exe.bak"));
+    }
+
+    #[test]
+    fn sources_are_found_by_any_name() {
+        let source = SourceType::RegistryCurrentUserRun;
+        assert_eq!(parse_source("registrycurrentuserrun"), Some(source));
+        assert_eq!(parse_source("hkcu\\run"), Some(source));
+        assert_eq!(parse_source(source.display_name()), Some(source));
+        assert_eq!(parse_source("nowhere"), None);
+    }
+
+    #[test]
+    fn exact_rules_win_over_patterns() {
+        let policy = Policy {
+            default: WantedState::Ignore,
+            rules: vec![
+                rule("App*", None, WantedState::Disabled),
+                rule("App*", None, WantedState::Absent),
+                rule("AppOne", None, WantedState::Enabled),
+            ],
+        };
+
+        let one = item("AppOne", SourceType::CronReboot, ItemStatus::Enabled);
+        let two = item("AppTwo", SourceType::CronReboot, ItemStatus::Enabled);
+        assert_eq!(policy.rule_for(&one).unwrap().state, WantedState::Enabled);
+        assert_eq!(policy.rule_for(&two).unwrap().state, WantedState::Disabled);
+    }
+
+    #[test]
+    fn rules_only_match_their_source() {
+        let policy = Policy {
+            default: WantedState::Ignore,
+            rules: vec![rule(
+                "app",
+                Some(SourceType::RegistryCurrentUserRun),
+                WantedState::Disabled,
+            )],
+        };
+
+        let cron = item("app", SourceType::CronReboot, ItemStatus::Enabled);
+        assert!(policy.rule_for(&cron).is_none());
+    }
+
+    #[test]
+    fn check_reports_each_kind_of_drift() {
+        let policy = Policy {
+            default: WantedState::Disabled,
+            rules: vec![
+                rule("wanted", None, WantedState::Enabled),
+                rule("gone", None, WantedState::Absent),
+                rule("kept", None, WantedState::Ignore),
+                rule("missing", None, WantedState::Enabled),
+                rule("missing*", None, WantedState::Enabled),
+            ],
+        };
+        let items = [
+            item("wanted", SourceType::CronReboot, ItemStatus::Disabled),
+            item("gone", SourceType::CronReboot, ItemStatus::Disabled),
+            item("kept", SourceType::CronReboot, ItemStatus::Enabled),
+            item("other", SourceType::CronReboot, ItemStatus::Enabled),
+            item("quiet", SourceType::CronReboot, ItemStatus::Disabled),
+        ];
+
+        let drift = policy.check(&items);
+        let drift: Vec<(DriftKind, &str)> =
+            drift.iter().map(|d| (d.kind, d.name.as_str())).collect();
+        assert_eq!(
+            drift,
+            [
+                (DriftKind::ShouldBeEnabled, "wanted"),
+                (DriftKind::ShouldBeAbsent, "gone"),
+                (DriftKind::ShouldBeDisabled, "other"),
+                (DriftKind::Missing, "missing"),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_items_cannot_be_fixed() {
+        let policy = Policy {
+            default: WantedState::Ignore,
+            rules: vec![
+                rule("missing", None, WantedState::Enabled),
+                rule("app", None, WantedState::Disabled),
+            ],
+        };
+        let items = [item("app", SourceType::CronReboot, ItemStatus::Enabled)];
+
+        let drift = policy.check(&items);
+        assert_eq!(drift.len(), 2);
+        assert!(drift[0].is_fixable());
+        assert_eq!(drift[1].kind, DriftKind::Missing);
+        assert!(!drift[1].is_fixable());
+    }
+
+    #[test]
+    fn loads_toml_and_yaml() {
+        let toml = load(
+            "toml",
+            r#"default = "disabled"
+
+[[item]]
+source = 'HKCU\Run'
+name = "App*"
+state = "enabled"
+"#,
+        )
+        .unwrap();
+        assert_eq!(toml.default, WantedState::Disabled);
+        assert_eq!(
+            toml.rules[0].source_type,
+            Some(SourceType::RegistryCurrentUserRun)
+        );
+
+        let yaml = load("yml", "items:\n  - name: app\n    state: absent\n").unwrap();
+        assert_eq!(yaml.default, WantedState::Ignore);
+        assert_eq!(yaml.rules[0].state, WantedState::Absent);
+        assert_eq!(yaml.rules[0].source_type, None);
+    }
+
+    #[test]
+    fn rejects_unknown_sources_and_empty_names() {
+        let unknown = load(
+            "toml",
+            "[[item]]\nsource = \"nowhere\"\nname = \"app\"\nstate = \"enabled\"\n",
+        );
+        assert!(matches!(unknown, Err(Error::Policy(_))));
+
+        let unnamed = load("toml", "[[item]]\nname = \" \"\nstate = \"enabled\"\n");
+        assert!(matches!(unnamed, Err(Error::Policy(_))));
+    }
+}
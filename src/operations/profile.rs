@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, SourceType, StartupItem};
+use crate::operations::{create_backup, Transaction, TransactionMode, TransactionReport};
+use crate::sources::scan_all_sources;
+
+/// Characters that cannot be part of a profile's file name
+const INVALID_NAME_CHARS: &[char] = &['\\', '/', ':', '*', '?', '"', '<', '>', '|'];
+
+/// An item's wanted status, with what is needed to name it once it is gone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileEntry {
+    pub name: String,
+    pub source: SourceType,
+    pub enabled: bool,
+}
+
+/// A named set of wanted item statuses, such as "gaming" or "presentation"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub saved_at: DateTime<Utc>,
+
+    /// Wanted status by item id
+    pub items: BTreeMap<String, ProfileEntry>,
+}
+
+impl Profile {
+    /// The current status of every item whose status can be changed
+    pub fn from_items(name: &str, items: &[StartupItem]) -> Self {
+        let items = items
+            .iter()
+            .filter(|item| item.status.can_toggle() && item.status != ItemStatus::Unknown)
+            .map(|item| {
+                let entry = ProfileEntry {
+                    name: item.name.clone(),
+                    source: item.source,
+                    enabled: item.status.is_enabled(),
+                };
+                (item.id.clone(), entry)
+            })
+            .collect();
+
+        Self {
+            name: name.to_string(),
+            saved_at: Utc::now(),
+            items,
+        }
+    }
+
+    fn entry_for(&self, item: &StartupItem) -> Option<&ProfileEntry> {
+        self.items
+            .get(&item.id)
+            .or_else(|| self.items.get(&item.legacy_id()))
+    }
+
+    /// What switching to the profile changes. Items added since the profile
+    /// was saved are left as they are.
+    pub fn plan(&self, items: &[StartupItem]) -> ProfilePlan {
+        let changes = items
+            .iter()
+            .filter(|item| item.status.can_toggle())
+            .filter_map(|item| {
+                let entry = self.entry_for(item)?;
+                (entry.enabled != item.status.is_enabled()).then(|| {
+                    let status = if entry.enabled {
+                        ItemStatus::Enabled
+                    } else {
+                        ItemStatus::Disabled
+                    };
+                    (item.clone(), status)
+                })
+            })
+            .collect();
+
+        let missing = self
+            .items
+            .iter()
+            .filter(|(id, _)| !items.iter().any(|item| item.matches_id(id)))
+            .map(|(_, entry)| entry.clone())
+            .collect();
+
+        ProfilePlan { changes, missing }
+    }
+}
+
+/// The status changes that bring the system in line with a profile
+#[derive(Debug, Clone, Default)]
+pub struct ProfilePlan {
+    pub changes: Vec<(StartupItem, ItemStatus)>,
+
+    /// Items the profile has a status for that no longer exist
+    pub missing: Vec<ProfileEntry>,
+}
+
+impl ProfilePlan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Names of the items the plan enables or disables
+    pub fn names(&self, status: ItemStatus) -> Vec<&str> {
+        self.changes
+            .iter()
+            .filter(|(_, new_status)| *new_status == status)
+            .map(|(item, _)| item.name.as_str())
+            .collect()
+    }
+}
+
+/// Get the profiles directory path
+pub fn get_profiles_dir() -> Result<PathBuf> {
+    let profiles_dir = dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("startup-checker")
+        .join("profiles");
+
+    fs::create_dir_all(&profiles_dir).map_err(Error::Io)?;
+
+    Ok(profiles_dir)
+}
+
+fn profile_path(name: &str) -> Result<PathBuf> {
+    if name.trim().is_empty() {
+        return Err(Error::InvalidName("a profile needs a name".to_string()));
+    }
+    if name.trim() != name || name.contains(INVALID_NAME_CHARS) || name.starts_with('.') {
+        return Err(Error::InvalidName(format!(
+            "{name:?} cannot be used as a profile name"
+        )));
+    }
+
+    Ok(get_profiles_dir()?.join(format!("{name}.json")))
+}
+
+fn read_profile(path: &Path) -> Option<Profile> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// List saved profiles by name
+pub fn list_profiles() -> Result<Vec<Profile>> {
+    let profiles_dir = get_profiles_dir()?;
+
+    let mut profiles: Vec<Profile> = fs::read_dir(&profiles_dir)
+        .map_err(Error::Io)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .filter_map(|path| read_profile(&path))
+        .collect();
+
+    profiles.sort_by_key(|profile| profile.name.to_lowercase());
+
+    Ok(profiles)
+}
+
+pub fn load_profile(name: &str) -> Result<Profile> {
+    let path = profile_path(name)?;
+    if !path.exists() {
+        return Err(Error::ProfileNotFound(name.to_string()));
+    }
+    let content = fs::read_to_string(&path).map_err(Error::Io)?;
+    serde_json::from_str(&content).map_err(Error::Serialization)
+}
+
+/// Save the current status of the items as a profile, replacing any
+/// profile with the same name
+pub fn save_profile(name: &str, items: &[StartupItem]) -> Result<Profile> {
+    let path = profile_path(name)?;
+    let profile = Profile::from_items(name, items);
+
+    let json = serde_json::to_string_pretty(&profile).map_err(Error::Serialization)?;
+    fs::write(&path, json).map_err(Error::Io)?;
+
+    Ok(profile)
+}
+
+pub fn delete_profile(name: &str) -> Result<()> {
+    match fs::remove_file(profile_path(name)?) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(Error::ProfileNotFound(name.to_string()))
+        }
+        result => result.map_err(Error::Io),
+    }
+}
+
+/// Apply a profile's plan as one transaction after taking a backup
+pub fn switch_profile(
+    profile: &Profile,
+    plan: &ProfilePlan,
+    mode: TransactionMode,
+) -> Result<TransactionReport> {
+    let mut transaction = Transaction::new(mode);
+    for (item, status) in &plan.changes {
+        transaction.push(item, *status);
+    }
+    if transaction.is_empty() {
+        return Ok(transaction.commit());
+    }
+
+    create_backup(
+        &scan_all_sources(),
+        Some(format!("Before switching to profile {}", profile.name)),
+    )?;
+
+    Ok(transaction.commit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, status: ItemStatus) -> StartupItem {
+        StartupItem::new(
+            name.to_string(),
+            SourceType::CronReboot,
+            "/etc/crontab".to_string(),
+            format!("/usr/bin/{name}"),
+        )
+        .with_status(status)
+    }
+
+    fn changes(plan: &ProfilePlan) -> Vec<(&str, ItemStatus)> {
+        plan.changes
+            .iter()
+            .map(|(item, status)| (item.name.as_str(), *status))
+            .collect()
+    }
+
+    #[test]
+    fn plans_only_the_differences() {
+        let saved = [
+            item("on", ItemStatus::Enabled),
+            item("off", ItemStatus::Disabled),
+            item("same", ItemStatus::Enabled),
+            item("once", ItemStatus::Pending),
+        ];
+        let profile = Profile::from_items("gaming", &saved);
+
+        let live = [
+            item("on", ItemStatus::Disabled),
+            item("off", ItemStatus::Enabled),
+            item("same", ItemStatus::Enabled),
+            item("once", ItemStatus::Pending),
+            item("new", ItemStatus::Disabled),
+            item("blocked", ItemStatus::DisabledByPolicy),
+        ];
+        let plan = profile.plan(&live);
+
+        assert_eq!(
+            changes(&plan),
+            [("on", ItemStatus::Enabled), ("off", ItemStatus::Disabled)]
+        );
+        assert_eq!(plan.names(ItemStatus::Enabled), ["on"]);
+        assert!(plan.missing.is_empty());
+    }
+
+    #[test]
+    fn policy_blocked_items_are_neither_saved_nor_changed() {
+        let profile = Profile::from_items("work", &[item("blocked", ItemStatus::Enabled)]);
+        assert!(profile
+            .plan(&[item("blocked", ItemStatus::DisabledByPolicy)])
+            .is_empty());
+
+        let blocked = Profile::from_items("work", &[item("blocked", ItemStatus::DisabledByPolicy)]);
+        assert!(blocked.items.is_empty());
+    }
+
+    #[test]
+    fn profiles_saved_with_legacy_ids_still_match() {
+        let live = item("app", ItemStatus::Enabled);
+        let mut profile = Profile::from_items("old", &[]);
+        profile.items.insert(
+            live.legacy_id(),
+            ProfileEntry {
+                name: live.name.clone(),
+                source: live.source,
+                enabled: false,
+            },
+        );
+
+        let plan = profile.plan(&[live]);
+        assert_eq!(changes(&plan), [("app", ItemStatus::Disabled)]);
+        assert!(plan.missing.is_empty());
+    }
+
+    #[test]
+    fn reports_items_that_no_longer_exist() {
+        let profile = Profile::from_items(
+            "gaming",
+            &[
+                item("kept", ItemStatus::Enabled),
+                item("gone", ItemStatus::Disabled),
+            ],
+        );
+        let plan = profile.plan(&[item("kept", ItemStatus::Enabled)]);
+
+        assert!(plan.is_empty());
+        let missing: Vec<&str> = plan
+            .missing
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(missing, ["gone"]);
+    }
+
+    #[test]
+    fn rejects_names_that_are_not_file_names() {
+        for name in [
+            "", "  ", " gaming", "gaming ", "a/b", "a\\b", "what?", ".hidden",
+        ] {
+            assert!(
+                matches!(profile_path(name), Err(Error::InvalidName(_))),
+                "{name:?} was accepted"
+            );
+        }
+    }
+}
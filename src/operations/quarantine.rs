@@ -0,0 +1,208 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::models::{RawPayload, StartupItem};
+use crate::sources::{capture_item, delete_item, recreate_item};
+
+/// A deleted item with everything needed to recreate it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub deleted_at: DateTime<Utc>,
+    pub item: StartupItem,
+    pub raw: RawPayload,
+
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl QuarantineEntry {
+    fn file_name(&self) -> String {
+        format!(
+            "{}_{}.json",
+            self.deleted_at.format("%Y%m%d_%H%M%S"),
+            self.item.id
+        )
+    }
+
+    /// Recreate the item and drop it from the quarantine
+    pub fn restore(&self) -> Result<()> {
+        recreate_item(&self.item, Some(&self.raw))?;
+        self.purge()
+    }
+
+    /// Forget the item for good
+    pub fn purge(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::Io(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Get the quarantine directory path
+pub fn get_quarantine_dir() -> Result<PathBuf> {
+    let quarantine_dir = dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("startup-checker")
+        .join("quarantine");
+
+    fs::create_dir_all(&quarantine_dir).map_err(Error::Io)?;
+
+    Ok(quarantine_dir)
+}
+
+/// Delete an item after saving a copy in the quarantine. Items whose source
+/// cannot capture them are refused rather than lost.
+pub fn quarantine_item(item: &StartupItem) -> Result<QuarantineEntry> {
+    quarantine_with(item, &get_quarantine_dir()?, capture_item, delete_item)
+}
+
+/// [`quarantine_item`] with the source calls passed in, so tests can fake them
+fn quarantine_with(
+    item: &StartupItem,
+    quarantine_dir: &Path,
+    capture: impl FnOnce(&StartupItem) -> Result<Option<RawPayload>>,
+    delete: impl FnOnce(&StartupItem) -> Result<()>,
+) -> Result<QuarantineEntry> {
+    let raw = capture(item)?.ok_or_else(|| Error::BackupFailed {
+        reason: format!(
+            "no copy of {} could be saved, so it was not deleted",
+            item.name
+        ),
+    })?;
+
+    let mut entry = QuarantineEntry {
+        deleted_at: Utc::now(),
+        item: item.clone(),
+        raw,
+        path: PathBuf::new(),
+    };
+    entry.path = quarantine_dir.join(entry.file_name());
+
+    let json = serde_json::to_string_pretty(&entry).map_err(Error::Serialization)?;
+    fs::write(&entry.path, json).map_err(Error::Io)?;
+
+    if let Err(e) = delete(item) {
+        let _ = fs::remove_file(&entry.path);
+        return Err(e);
+    }
+
+    Ok(entry)
+}
+
+fn read_entry(path: &Path) -> Option<QuarantineEntry> {
+    let content = fs::read_to_string(path).ok()?;
+    let mut entry: QuarantineEntry = serde_json::from_str(&content).ok()?;
+    entry.path = path.to_path_buf();
+    Some(entry)
+}
+
+/// List quarantined items, most recently deleted first
+pub fn list_quarantine() -> Result<Vec<QuarantineEntry>> {
+    let quarantine_dir = get_quarantine_dir()?;
+
+    let mut entries: Vec<QuarantineEntry> = fs::read_dir(&quarantine_dir)
+        .map_err(Error::Io)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == "json"))
+        .filter_map(|path| read_entry(&path))
+        .collect();
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.deleted_at));
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SourceType;
+    use crate::sources::fake_root;
+
+    fn item() -> StartupItem {
+        StartupItem::new(
+            "backup".to_string(),
+            SourceType::CronReboot,
+            "/etc/crontab".to_string(),
+            "/usr/bin/backup".to_string(),
+        )
+    }
+
+    fn line() -> Result<Option<RawPayload>> {
+        Ok(Some(RawPayload::Line {
+            line: "@reboot /usr/bin/backup".to_string(),
+        }))
+    }
+
+    fn files_in(dir: &Path) -> usize {
+        fs::read_dir(dir).unwrap().count()
+    }
+
+    #[test]
+    fn entries_read_back_from_their_file() {
+        let dir = fake_root("quarantine-round-trip");
+        fs::create_dir_all(&dir).unwrap();
+
+        let entry = quarantine_with(&item(), &dir, |_| line(), |_| Ok(())).unwrap();
+        assert!(entry.path.starts_with(&dir));
+        assert!(entry
+            .file_name()
+            .ends_with(&format!("_{}.json", entry.item.id)));
+
+        let read = read_entry(&entry.path).unwrap();
+        assert_eq!(read.item.id, entry.item.id);
+        assert_eq!(read.item.command, "/usr/bin/backup");
+        assert_eq!(read.raw, entry.raw);
+        assert_eq!(read.deleted_at, entry.deleted_at);
+        assert_eq!(read.path, entry.path);
+
+        read.purge().unwrap();
+        assert_eq!(files_in(&dir), 0);
+        read.purge().unwrap();
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_deletes_remove_the_saved_copy() {
+        let dir = fake_root("quarantine-rollback");
+        fs::create_dir_all(&dir).unwrap();
+
+        let result = quarantine_with(
+            &item(),
+            &dir,
+            |_| line(),
+            |item| {
+                Err(Error::PermissionDenied {
+                    message: format!("cannot delete {}", item.name),
+                })
+            },
+        );
+        assert!(matches!(result, Err(Error::PermissionDenied { .. })));
+        assert_eq!(files_in(&dir), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn items_without_a_copy_are_not_deleted() {
+        let dir = fake_root("quarantine-no-copy");
+        fs::create_dir_all(&dir).unwrap();
+
+        let result = quarantine_with(
+            &item(),
+            &dir,
+            |_| Ok(None),
+            |_| panic!("deleted an item that was not saved"),
+        );
+        assert!(matches!(result, Err(Error::BackupFailed { .. })));
+        assert_eq!(files_in(&dir), 0);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,335 @@
+use serde::Serialize;
+
+use crate::models::{ItemStatus, RawPayload, StartupItem};
+use crate::sources::recreate_item;
+
+use super::{Backup, ChangeOutcome, ChangeResult, Transaction, TransactionMode};
+
+/// What restoring one item involves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RestoreAction {
+    Enable,
+    Disable,
+    /// The item no longer exists and has to be written again
+    Recreate,
+}
+
+impl RestoreAction {
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::Enable => "Enable",
+            Self::Disable => "Disable",
+            Self::Recreate => "Recreate",
+        }
+    }
+}
+
+/// One change needed to bring an item back to its backed up state
+#[derive(Debug, Clone)]
+pub struct RestoreStep {
+    pub action: RestoreAction,
+
+    /// The item as it was backed up
+    pub item: StartupItem,
+
+    /// The item as it is now, `None` when it was deleted
+    pub current: Option<StartupItem>,
+
+    /// Data the backup captured to recreate the item
+    pub raw: Option<RawPayload>,
+
+    /// Whether the step is applied
+    pub selected: bool,
+}
+
+impl RestoreStep {
+    /// Status before and after, e.g. "Disabled -> Enabled"
+    pub fn summary(&self) -> String {
+        match &self.current {
+            Some(current) => format!(
+                "{} -> {}",
+                current.status.display(),
+                self.item.status.display()
+            ),
+            None => format!("Missing -> {}", self.item.status.display()),
+        }
+    }
+
+    /// The live item and the status to set it to, `None` for recreations
+    fn status_change(&self) -> Option<(&StartupItem, ItemStatus)> {
+        let status = match self.action {
+            RestoreAction::Enable => ItemStatus::Enabled,
+            RestoreAction::Disable => ItemStatus::Disabled,
+            RestoreAction::Recreate => return None,
+        };
+        Some((self.current.as_ref()?, status))
+    }
+
+    fn result(&self, error: Option<String>) -> RestoreResult {
+        RestoreResult {
+            item_id: self.item.id.clone(),
+            name: self.item.name.clone(),
+            action: self.action,
+            error,
+        }
+    }
+}
+
+/// Outcome of one applied step
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub item_id: String,
+    pub name: String,
+    pub action: RestoreAction,
+    /// Why the step failed, `None` on success
+    pub error: Option<String>,
+}
+
+impl RestoreResult {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Why a status change did not stick, e.g. because another one failed
+    fn error_of(result: &ChangeResult) -> Option<String> {
+        match (result.outcome, &result.error) {
+            (ChangeOutcome::Applied, _) => None,
+            (ChangeOutcome::Failed, Some(error)) => Some(error.clone()),
+            (outcome, Some(error)) => Some(format!("{}: {error}", outcome.display())),
+            (outcome, None) => Some(outcome.display().to_string()),
+        }
+    }
+}
+
+/// The changes that would bring live startup items back to a backup
+#[derive(Debug, Clone, Default)]
+pub struct RestorePlan {
+    pub steps: Vec<RestoreStep>,
+}
+
+impl RestorePlan {
+    /// Compare a backup with a live scan. Items added since the backup are
+    /// left alone; restoring never removes anything.
+    pub fn new(backup: &Backup, live: &[StartupItem]) -> Self {
+        let steps = backup
+            .items
+            .iter()
+            .filter_map(|saved| {
+                let current = live.iter().find(|item| item.id == saved.id);
+                let action = Self::action_for(saved, current)?;
+                Some(RestoreStep {
+                    action,
+                    item: saved.clone(),
+                    current: current.cloned(),
+                    raw: backup.payloads.get(&saved.id).cloned(),
+                    selected: true,
+                })
+            })
+            .collect();
+
+        Self { steps }
+    }
+
+    fn action_for(saved: &StartupItem, current: Option<&StartupItem>) -> Option<RestoreAction> {
+        let Some(current) = current else {
+            // RunOnce values delete themselves once they have run
+            return (saved.status != ItemStatus::Pending).then_some(RestoreAction::Recreate);
+        };
+
+        // Statuses nobody can change from here are not worth a step
+        if !saved.status.can_toggle() || !current.status.can_toggle() {
+            return None;
+        }
+
+        if saved.status.is_enabled() && current.status.is_disabled() {
+            Some(RestoreAction::Enable)
+        } else if saved.status.is_disabled() && current.status.is_enabled() {
+            Some(RestoreAction::Disable)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn selected_count(&self) -> usize {
+        self.steps.iter().filter(|s| s.selected).count()
+    }
+
+    pub fn toggle(&mut self, index: usize) {
+        if let Some(step) = self.steps.get_mut(index) {
+            step.selected = !step.selected;
+        }
+    }
+
+    pub fn select_all(&mut self, selected: bool) {
+        for step in &mut self.steps {
+            step.selected = selected;
+        }
+    }
+
+    /// Apply the selected steps. Recreations are written first, then the
+    /// status changes are committed as one [`Transaction`] in `mode`.
+    pub fn apply(&self, mode: TransactionMode) -> Vec<RestoreResult> {
+        let selected: Vec<&RestoreStep> = self.steps.iter().filter(|step| step.selected).collect();
+        let mut results = Vec::with_capacity(selected.len());
+
+        let mut transaction = Transaction::new(mode);
+        let mut toggled = Vec::new();
+        for step in selected {
+            match step.status_change() {
+                Some((current, status)) => {
+                    transaction.push(current, status);
+                    toggled.push(step);
+                }
+                None => {
+                    let error = recreate_item(&step.item, step.raw.as_ref()).err();
+                    results.push(step.result(error.map(|e| e.to_string())));
+                }
+            }
+        }
+
+        if !transaction.is_empty() {
+            let report = transaction.commit();
+            results.extend(
+                toggled
+                    .iter()
+                    .zip(&report.results)
+                    .map(|(step, result)| step.result(RestoreResult::error_of(result))),
+            );
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SourceType;
+
+    fn item(name: &str, status: ItemStatus) -> StartupItem {
+        StartupItem::new(
+            name.to_string(),
+            SourceType::CronReboot,
+            "/etc/crontab".to_string(),
+            format!("/usr/bin/{name}"),
+        )
+        .with_status(status)
+    }
+
+    fn actions(plan: &RestorePlan) -> Vec<(&str, RestoreAction)> {
+        plan.steps
+            .iter()
+            .map(|step| (step.item.name.as_str(), step.action))
+            .collect()
+    }
+
+    #[test]
+    fn plans_a_step_for_each_action() {
+        let backup = Backup::new(
+            vec![
+                item("deleted", ItemStatus::Disabled),
+                item("was-enabled", ItemStatus::Enabled),
+                item("was-disabled", ItemStatus::Disabled),
+                item("unchanged", ItemStatus::Enabled),
+            ],
+            None,
+        );
+        let live = [
+            item("was-enabled", ItemStatus::DisabledButTriggered),
+            item("was-disabled", ItemStatus::Pending),
+            item("unchanged", ItemStatus::Enabled),
+            item("added", ItemStatus::Enabled),
+        ];
+
+        let plan = RestorePlan::new(&backup, &live);
+        assert_eq!(
+            actions(&plan),
+            [
+                ("deleted", RestoreAction::Recreate),
+                ("was-enabled", RestoreAction::Enable),
+                ("was-disabled", RestoreAction::Disable),
+            ]
+        );
+        assert!(plan.steps[0].current.is_none());
+        assert_eq!(plan.steps[0].summary(), "Missing -> Disabled");
+        assert_eq!(
+            plan.steps[1].current.as_ref().unwrap().status,
+            ItemStatus::DisabledButTriggered
+        );
+        assert_eq!(plan.selected_count(), 3);
+    }
+
+    #[test]
+    fn run_once_items_that_ran_are_not_recreated() {
+        let backup = Backup::new(vec![item("once", ItemStatus::Pending)], None);
+        assert!(RestorePlan::new(&backup, &[]).is_empty());
+    }
+
+    #[test]
+    fn statuses_that_cannot_be_toggled_are_skipped() {
+        let blocked = item("blocked", ItemStatus::DisabledByPolicy);
+        let enabled = item("blocked", ItemStatus::Enabled);
+
+        assert_eq!(RestorePlan::action_for(&enabled, Some(&blocked)), None);
+        assert_eq!(RestorePlan::action_for(&blocked, Some(&enabled)), None);
+        assert_eq!(
+            RestorePlan::action_for(&blocked, None),
+            Some(RestoreAction::Recreate)
+        );
+    }
+
+    #[test]
+    fn status_changes_address_the_live_item() {
+        let backup = Backup::new(
+            vec![
+                item("deleted", ItemStatus::Enabled),
+                item("toggled", ItemStatus::Enabled),
+            ],
+            None,
+        );
+        let live = [item("toggled", ItemStatus::Disabled)];
+        let plan = RestorePlan::new(&backup, &live);
+
+        assert!(plan.steps[0].status_change().is_none());
+        let (current, status) = plan.steps[1].status_change().unwrap();
+        assert_eq!(current.status, ItemStatus::Disabled);
+        assert_eq!(status, ItemStatus::Enabled);
+    }
+
+    #[test]
+    fn rolled_back_changes_are_reported_as_failed() {
+        let result = |outcome, error: Option<&str>| ChangeResult {
+            item_id: String::new(),
+            name: String::new(),
+            old_status: ItemStatus::Disabled,
+            new_status: ItemStatus::Enabled,
+            outcome,
+            error: error.map(str::to_string),
+        };
+
+        assert_eq!(
+            RestoreResult::error_of(&result(ChangeOutcome::Applied, None)),
+            None
+        );
+        assert_eq!(
+            RestoreResult::error_of(&result(ChangeOutcome::Failed, Some("denied"))),
+            Some("denied".to_string())
+        );
+        assert_eq!(
+            RestoreResult::error_of(&result(ChangeOutcome::RolledBack, None)),
+            Some("Rolled back".to_string())
+        );
+        assert_eq!(
+            RestoreResult::error_of(&result(ChangeOutcome::RollbackFailed, Some("denied"))),
+            Some("Rollback failed: denied".to_string())
+        );
+    }
+}
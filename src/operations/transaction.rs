@@ -0,0 +1,367 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, StartupItem};
+use crate::sources::modify_item;
+
+/// What happens to applied changes when a later one fails
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionMode {
+    /// Undo every applied change, leaving the system as it was
+    #[default]
+    AllOrNothing,
+    /// Keep the changes that worked and carry on past failures
+    BestEffort,
+}
+
+impl TransactionMode {
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::AllOrNothing => "All or nothing",
+            Self::BestEffort => "Best effort",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            Self::AllOrNothing => Self::BestEffort,
+            Self::BestEffort => Self::AllOrNothing,
+        }
+    }
+}
+
+/// What became of one change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChangeOutcome {
+    Applied,
+    Failed,
+    /// Applied, then undone because another change failed
+    RolledBack,
+    /// Applied, but undoing it failed too, so it is still in effect
+    RollbackFailed,
+    /// Not attempted because an earlier change failed
+    Skipped,
+}
+
+impl ChangeOutcome {
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::Applied => "Applied",
+            Self::Failed => "Failed",
+            Self::RolledBack => "Rolled back",
+            Self::RollbackFailed => "Rollback failed",
+            Self::Skipped => "Skipped",
+        }
+    }
+}
+
+/// Outcome of one change in a transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeResult {
+    pub item_id: String,
+    pub name: String,
+    pub old_status: ItemStatus,
+    pub new_status: ItemStatus,
+    pub outcome: ChangeOutcome,
+    /// Why applying or undoing failed
+    pub error: Option<String>,
+}
+
+/// A status change and how to undo it
+#[derive(Debug, Clone)]
+struct Change {
+    item: StartupItem,
+    new_status: ItemStatus,
+}
+
+/// Sets an item to a status, [`modify_item`] outside of tests
+type Modify<'a> = dyn FnMut(&StartupItem, ItemStatus) -> Result<()> + 'a;
+
+impl Change {
+    fn apply(&self, modify: &mut Modify) -> Result<()> {
+        modify(&self.item, self.new_status)
+    }
+
+    /// Put the item back, addressing it as it is after the change
+    fn undo(&self, modify: &mut Modify) -> Result<()> {
+        let mut changed = self.item.clone();
+        changed.status = self.new_status;
+        modify(&changed, self.item.status)
+    }
+
+    fn result(&self, outcome: ChangeOutcome, error: Option<String>) -> ChangeResult {
+        ChangeResult {
+            item_id: self.item.id.clone(),
+            name: self.item.name.clone(),
+            old_status: self.item.status,
+            new_status: self.new_status,
+            outcome,
+            error,
+        }
+    }
+}
+
+/// Status changes applied together, in the order they were added
+#[derive(Debug, Clone, Default)]
+pub struct Transaction {
+    mode: TransactionMode,
+    changes: Vec<Change>,
+}
+
+impl Transaction {
+    pub fn new(mode: TransactionMode) -> Self {
+        Self {
+            mode,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Queue a change from the item's current status to `new_status`
+    pub fn push(&mut self, item: &StartupItem, new_status: ItemStatus) {
+        self.changes.push(Change {
+            item: item.clone(),
+            new_status,
+        });
+    }
+
+    pub fn with_change(mut self, item: &StartupItem, new_status: ItemStatus) -> Self {
+        self.push(item, new_status);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Apply the changes. In all-or-nothing mode the first failure undoes
+    /// the applied changes, newest first, and skips the rest.
+    pub fn commit(self) -> TransactionReport {
+        self.commit_with(&mut modify_item)
+    }
+
+    fn commit_with(self, modify: &mut Modify) -> TransactionReport {
+        let mut results: Vec<ChangeResult> = Vec::with_capacity(self.changes.len());
+        // Indexes into `changes` and `results` of the applied changes
+        let mut applied: Vec<usize> = Vec::new();
+        let mut failure = None;
+
+        for (index, change) in self.changes.iter().enumerate() {
+            if failure.is_some() && self.mode == TransactionMode::AllOrNothing {
+                results.push(change.result(ChangeOutcome::Skipped, None));
+                continue;
+            }
+
+            match change.apply(modify) {
+                Ok(()) => {
+                    applied.push(index);
+                    results.push(change.result(ChangeOutcome::Applied, None));
+                }
+                Err(e) => {
+                    failure.get_or_insert_with(|| (change.item.name.clone(), e.to_string()));
+                    results.push(change.result(ChangeOutcome::Failed, Some(e.to_string())));
+                }
+            }
+        }
+
+        let rolled_back = failure.is_some() && self.mode == TransactionMode::AllOrNothing;
+        if rolled_back {
+            for &index in applied.iter().rev() {
+                let result = &mut results[index];
+                match self.changes[index].undo(modify) {
+                    Ok(()) => result.outcome = ChangeOutcome::RolledBack,
+                    Err(e) => {
+                        result.outcome = ChangeOutcome::RollbackFailed;
+                        result.error = Some(e.to_string());
+                    }
+                }
+            }
+        }
+
+        TransactionReport {
+            mode: self.mode,
+            results,
+            failure: failure.filter(|_| rolled_back),
+        }
+    }
+}
+
+/// Per-item results of a committed transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionReport {
+    pub mode: TransactionMode,
+    pub results: Vec<ChangeResult>,
+
+    /// The change that triggered a rollback, and why it failed
+    #[serde(skip)]
+    failure: Option<(String, String)>,
+}
+
+impl TransactionReport {
+    pub fn count(&self, outcome: ChangeOutcome) -> usize {
+        self.results.iter().filter(|r| r.outcome == outcome).count()
+    }
+
+    /// Every change applied
+    pub fn succeeded(&self) -> bool {
+        self.results
+            .iter()
+            .all(|r| r.outcome == ChangeOutcome::Applied)
+    }
+
+    pub fn rolled_back(&self) -> bool {
+        self.failure.is_some()
+    }
+
+    /// `Error::ChangeFailed` when a failure rolled the transaction back
+    pub fn error(&self) -> Option<Error> {
+        self.failure
+            .as_ref()
+            .map(|(name, reason)| Error::ChangeFailed {
+                name: name.clone(),
+                reason: reason.clone(),
+            })
+    }
+
+    /// The report, or `Error::ChangeFailed` when it was rolled back
+    pub fn into_result(self) -> Result<Self> {
+        match self.error() {
+            Some(e) => Err(e),
+            None => Ok(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SourceType;
+
+    fn item(name: &str) -> StartupItem {
+        StartupItem::new(
+            name.to_string(),
+            SourceType::CronReboot,
+            "/etc/crontab".to_string(),
+            format!("/usr/bin/{name}"),
+        )
+    }
+
+    /// Commit with a fake that fails for `failing` and records every call
+    fn commit(
+        transaction: Transaction,
+        failing: &str,
+    ) -> (TransactionReport, Vec<(String, ItemStatus)>) {
+        let mut calls = Vec::new();
+        let report = transaction.commit_with(&mut |item, status| {
+            calls.push((item.name.clone(), status));
+            if item.name == failing {
+                Err(Error::InvalidPath(item.name.clone()))
+            } else {
+                Ok(())
+            }
+        });
+        (report, calls)
+    }
+
+    fn outcomes(report: &TransactionReport) -> Vec<ChangeOutcome> {
+        report.results.iter().map(|r| r.outcome).collect()
+    }
+
+    #[test]
+    fn applies_in_order() {
+        let transaction = Transaction::new(TransactionMode::AllOrNothing)
+            .with_change(&item("a"), ItemStatus::Disabled)
+            .with_change(&item("b"), ItemStatus::Disabled);
+        let (report, calls) = commit(transaction, "none");
+
+        assert!(report.succeeded());
+        assert!(report.into_result().is_ok());
+        assert_eq!(
+            calls,
+            [
+                ("a".to_string(), ItemStatus::Disabled),
+                ("b".to_string(), ItemStatus::Disabled),
+            ]
+        );
+    }
+
+    #[test]
+    fn rolls_back_newest_first_and_skips_the_rest() {
+        let transaction = Transaction::new(TransactionMode::AllOrNothing)
+            .with_change(&item("a"), ItemStatus::Disabled)
+            .with_change(&item("b"), ItemStatus::Disabled)
+            .with_change(&item("c"), ItemStatus::Disabled)
+            .with_change(&item("d"), ItemStatus::Disabled);
+        let (report, calls) = commit(transaction, "c");
+
+        let order: Vec<(&str, ItemStatus)> = calls.iter().map(|(n, s)| (n.as_str(), *s)).collect();
+        assert_eq!(
+            order,
+            [
+                ("a", ItemStatus::Disabled),
+                ("b", ItemStatus::Disabled),
+                ("c", ItemStatus::Disabled),
+                ("b", ItemStatus::Enabled),
+                ("a", ItemStatus::Enabled),
+            ]
+        );
+        assert_eq!(
+            outcomes(&report),
+            [
+                ChangeOutcome::RolledBack,
+                ChangeOutcome::RolledBack,
+                ChangeOutcome::Failed,
+                ChangeOutcome::Skipped,
+            ]
+        );
+        assert!(report.rolled_back());
+        assert!(matches!(report.error(), Some(Error::ChangeFailed { name, .. }) if name == "c"));
+    }
+
+    #[test]
+    fn failed_undo_is_reported() {
+        let transaction = Transaction::new(TransactionMode::AllOrNothing)
+            .with_change(&item("a"), ItemStatus::Disabled)
+            .with_change(&item("b"), ItemStatus::Disabled);
+        let mut calls = 0;
+        let report = transaction.commit_with(&mut |item, _| {
+            calls += 1;
+            // "a" applies, "b" fails, then undoing "a" fails
+            if item.name == "a" && calls == 1 {
+                Ok(())
+            } else {
+                Err(Error::InvalidPath(item.name.clone()))
+            }
+        });
+
+        assert_eq!(
+            outcomes(&report),
+            [ChangeOutcome::RollbackFailed, ChangeOutcome::Failed]
+        );
+        assert!(report.results[0].error.is_some());
+    }
+
+    #[test]
+    fn best_effort_keeps_going() {
+        let transaction = Transaction::new(TransactionMode::BestEffort)
+            .with_change(&item("a"), ItemStatus::Disabled)
+            .with_change(&item("b"), ItemStatus::Disabled)
+            .with_change(&item("c"), ItemStatus::Disabled);
+        let (report, calls) = commit(transaction, "b");
+
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            outcomes(&report),
+            [
+                ChangeOutcome::Applied,
+                ChangeOutcome::Failed,
+                ChangeOutcome::Applied,
+            ]
+        );
+        assert!(!report.rolled_back());
+        assert!(report.error().is_none());
+    }
+}
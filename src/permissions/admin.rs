@@ -1,13 +1,16 @@
 use std::process::Command;
 
-use windows::Win32::Foundation::HANDLE;
-use windows::Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY};
-use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
-
 use crate::error::Result;
 
 /// Check if the current process is running with elevated (administrator) privileges
+#[cfg(windows)]
 pub fn is_elevated() -> bool {
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
     unsafe {
         let mut token_handle = HANDLE::default();
         if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token_handle).is_err() {
@@ -33,6 +36,20 @@ pub fn is_elevated() -> bool {
     }
 }
 
+/// Check if the current process is running as root
+#[cfg(unix)]
+pub fn is_elevated() -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    // /proc/self is owned by the effective uid of the process
+    std::fs::metadata("/proc/self").is_ok_and(|m| m.uid() == 0)
+}
+
+#[cfg(not(any(windows, unix)))]
+pub fn is_elevated() -> bool {
+    false
+}
+
 /// Request elevation by restarting the process with admin privileges
 pub fn request_elevation() -> Result<()> {
     let exe = std::env::current_exe()?;
@@ -0,0 +1,158 @@
+/// Prefix written in front of a line that was disabled by this tool, so that
+/// `enable` can find it again and only ever uncomments lines it commented out
+pub const DISABLED_MARKER: &str = "# [startup-checker disabled] ";
+
+/// If the line was commented out by [`comment_out`], return the original text
+pub fn strip_marker(line: &str) -> Option<&str> {
+    line.trim_start().strip_prefix(DISABLED_MARKER)
+}
+
+/// Comment out a line with the recognizable marker
+pub fn comment_out(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    format!("{indent}{DISABLED_MARKER}{trimmed}")
+}
+
+/// Reverse [`comment_out`]. Lines without the marker are returned unchanged.
+pub fn uncomment(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = &line[..line.len() - trimmed.len()];
+    match trimmed.strip_prefix(DISABLED_MARKER) {
+        Some(original) => format!("{indent}{original}"),
+        None => line.to_string(),
+    }
+}
+
+/// Replace the line at `index` (0-based) using `f`, preserving line endings.
+/// Returns `None` if the file has fewer lines.
+pub fn replace_line(content: &str, index: usize, f: impl FnOnce(&str) -> String) -> Option<String> {
+    let mut result = String::with_capacity(content.len() + DISABLED_MARKER.len());
+    let mut f = Some(f);
+
+    for (i, raw) in content.split_inclusive('\n').enumerate() {
+        match f.take_if(|_| i == index) {
+            Some(f) => {
+                let body = raw.trim_end_matches(['\r', '\n']);
+                let ending = &raw[body.len()..];
+                result.push_str(&f(body));
+                result.push_str(ending);
+            }
+            None => result.push_str(raw),
+        }
+    }
+
+    // `f` is consumed only if the line was found
+    f.is_none().then_some(result)
+}
+
+/// Comment out or restore the line at `index`
+pub fn set_line_enabled(content: &str, index: usize, enabled: bool) -> Option<String> {
+    replace_line(content, index, |line| {
+        if enabled {
+            uncomment(line)
+        } else if strip_marker(line).is_some() {
+            line.to_string()
+        } else {
+            comment_out(line)
+        }
+    })
+}
+
+/// Pick the entry backing an item among the lines whose command matches it:
+/// the one on the item's own line, or the only match if the line moved
+pub fn pick_line(matches: &[usize], line: Option<usize>) -> Option<usize> {
+    match (matches, line) {
+        (_, Some(line)) if matches.contains(&line) => Some(line),
+        ([only], _) => Some(*only),
+        _ => None,
+    }
+}
+
+/// The line at `index` (0-based) without its line ending
+pub fn line_at(content: &str, index: usize) -> Option<&str> {
+    content.lines().nth(index)
+}
+
+/// Remove the line at `index` together with its line ending.
+/// Returns `None` if the file has fewer lines.
+pub fn remove_line(content: &str, index: usize) -> Option<String> {
+    let mut result = String::with_capacity(content.len());
+    let mut found = false;
+
+    for (i, raw) in content.split_inclusive('\n').enumerate() {
+        if i == index {
+            found = true;
+        } else {
+            result.push_str(raw);
+        }
+    }
+
+    found.then_some(result)
+}
+
+/// Insert a line before the line at `index`, or at the end if the file
+/// has fewer lines, using the file's line ending
+pub fn insert_line(content: &str, index: usize, line: &str) -> String {
+    let ending = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut result = String::with_capacity(content.len() + line.len() + ending.len());
+    let mut inserted = false;
+
+    for (i, raw) in content.split_inclusive('\n').enumerate() {
+        if i == index {
+            result.push_str(line);
+            result.push_str(ending);
+            inserted = true;
+        }
+        result.push_str(raw);
+    }
+
+    if !inserted {
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push_str(ending);
+        }
+        result.push_str(line);
+        result.push_str(ending);
+    }
+
+    result
+}
+
+/// The line as it should be written back for an item with this status
+pub fn line_for_status(line: &str, enabled: bool) -> String {
+    if enabled {
+        line.to_string()
+    } else {
+        comment_out(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_items_own_line() {
+        assert_eq!(pick_line(&[0, 3], Some(3)), Some(3));
+        assert_eq!(pick_line(&[0, 3], Some(1)), None);
+        assert_eq!(pick_line(&[0, 3], None), None);
+        assert_eq!(pick_line(&[5], Some(2)), Some(5));
+        assert_eq!(pick_line(&[], Some(2)), None);
+    }
+
+    #[test]
+    fn comments_out_and_restores_lines() {
+        let content = "a\nb\n";
+        let disabled = set_line_enabled(content, 1, false).unwrap();
+
+        assert_eq!(strip_marker(line_at(&disabled, 1).unwrap()), Some("b"));
+        assert_eq!(set_line_enabled(&disabled, 1, false).unwrap(), disabled);
+        assert_eq!(set_line_enabled(&disabled, 1, true).unwrap(), content);
+        assert_eq!(remove_line(content, 0).unwrap(), "b\n");
+        assert_eq!(remove_line(content, 2), None);
+    }
+}
@@ -0,0 +1,495 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+use super::comment_marker::{
+    insert_line, line_at, line_for_status, pick_line, remove_line, set_line_enabled, strip_marker,
+    uncomment,
+};
+use super::shell_script::{first_word, top_level_lines};
+use super::xdg_autostart::find_program;
+use super::StartupSource;
+
+/// `PATH` cron uses when the crontab does not set one
+const DEFAULT_CRON_PATH: &str = "/usr/bin:/bin";
+
+/// An `@reboot` line from a crontab
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronEntry {
+    /// 0-based line number in the file
+    pub line: usize,
+    /// Only present in system crontabs (`/etc/crontab`, `/etc/cron.d/*`)
+    pub user: Option<String>,
+    pub command: String,
+    /// Environment assignments that appear before the entry
+    pub env: Vec<(String, String)>,
+    /// False if the line was commented out by this tool
+    pub enabled: bool,
+}
+
+impl CronEntry {
+    pub fn env_var(&self, name: &str) -> Option<&str> {
+        self.env
+            .iter()
+            .rev()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Parse `NAME = value` environment lines, stripping matching quotes
+fn parse_env_line(line: &str) -> Option<(String, String)> {
+    let (name, value) = line.split_once('=')?;
+    let name = name.trim();
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    if !valid {
+        return None;
+    }
+
+    let value = value.trim();
+    let value = ['"', '\'']
+        .iter()
+        .find_map(|q| value.strip_prefix(*q).and_then(|v| v.strip_suffix(*q)))
+        .unwrap_or(value);
+
+    Some((name.to_string(), value.to_string()))
+}
+
+/// Find the `@reboot` entries in a crontab. System crontabs have a user
+/// field between the schedule and the command.
+pub fn parse_crontab(content: &str, system: bool) -> Vec<CronEntry> {
+    let mut entries = Vec::new();
+    let mut env = Vec::new();
+
+    for (index, raw) in content.lines().enumerate() {
+        let (line, enabled) = match strip_marker(raw) {
+            Some(original) => (original.trim(), false),
+            None => (raw.trim(), true),
+        };
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        // Schedules never start with a letter, so this cannot eat a job line
+        if enabled && !line.starts_with('@') {
+            if let Some(assignment) = parse_env_line(line) {
+                env.push(assignment);
+                continue;
+            }
+        }
+
+        let Some(rest) = line.strip_prefix("@reboot") else {
+            continue;
+        };
+        if !rest.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let rest = rest.trim_start();
+        let (user, command) = if system {
+            let Some((user, command)) = rest.split_once(char::is_whitespace) else {
+                continue;
+            };
+            (Some(user.to_string()), command.trim_start())
+        } else {
+            (None, rest)
+        };
+
+        if command.is_empty() {
+            continue;
+        }
+
+        entries.push(CronEntry {
+            line: index,
+            user,
+            command: command.to_string(),
+            env: env.clone(),
+            enabled,
+        });
+    }
+
+    entries
+}
+
+/// A top-level command in `/etc/rc.local`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RcLocalEntry {
+    pub line: usize,
+    pub command: String,
+    pub enabled: bool,
+}
+
+/// Find the simple commands in an `rc.local` script
+pub fn parse_rc_local(content: &str) -> Vec<RcLocalEntry> {
+    top_level_lines(content)
+        .into_iter()
+        .filter(|l| !l.compound && first_word(l.text) != "exit")
+        .map(|l| RcLocalEntry {
+            line: l.line,
+            command: l.text.to_string(),
+            enabled: l.enabled,
+        })
+        .collect()
+}
+
+pub struct CronScanner {
+    root: PathBuf,
+}
+
+impl CronScanner {
+    pub fn new() -> Self {
+        Self::with_root(PathBuf::from("/"))
+    }
+
+    /// Scan crontabs below a different root directory, e.g. a fake root in tests
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn under_root(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    fn files_in(dir: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            // Skip hidden files such as editor backups
+            .filter(|p| {
+                p.file_name()
+                    .is_some_and(|n| !n.to_string_lossy().starts_with('.'))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// `(path, is system crontab, owning user)` for every crontab to scan
+    fn crontabs(&self) -> Vec<(PathBuf, bool, Option<String>)> {
+        let mut crontabs = vec![(self.under_root("/etc/crontab"), true, None)];
+
+        crontabs.extend(
+            Self::files_in(&self.under_root("/etc/cron.d"))
+                .into_iter()
+                .map(|p| (p, true, None)),
+        );
+
+        // Debian keeps user crontabs in crontabs/, Red Hat directly in /var/spool/cron
+        for dir in ["/var/spool/cron/crontabs", "/var/spool/cron"] {
+            for path in Self::files_in(&self.under_root(dir)) {
+                let user = path.file_name().map(|n| n.to_string_lossy().to_string());
+                crontabs.push((path, false, user));
+            }
+        }
+
+        crontabs
+    }
+
+    fn scan_crontab(&self, path: &Path, system: bool, owner: Option<String>) -> Vec<StartupItem> {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Vec::new();
+        };
+
+        parse_crontab(&content, system)
+            .into_iter()
+            .map(|entry| {
+                let user = entry.user.clone().or_else(|| owner.clone());
+                let program = entry.command.split_whitespace().next().unwrap_or_default();
+                let name = Path::new(program)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| program.to_string());
+
+                let description = match &user {
+                    Some(user) => format!("@reboot as {user} in {}", path.display()),
+                    None => format!("@reboot in {}", path.display()),
+                };
+
+                let mut item = StartupItem::new(
+                    name,
+                    SourceType::CronReboot,
+                    path.display().to_string(),
+                    entry.command.clone(),
+                )
+                .with_status(if entry.enabled {
+                    ItemStatus::Enabled
+                } else {
+                    ItemStatus::Disabled
+                })
+                .with_description(Some(description))
+                .with_line(entry.line);
+
+                let path_var = entry.env_var("PATH").unwrap_or(DEFAULT_CRON_PATH);
+                self.resolve_program(&mut item, program, path_var);
+                item
+            })
+            .collect()
+    }
+
+    fn scan_rc_local(&self) -> Vec<StartupItem> {
+        let path = self.under_root("/etc/rc.local");
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Vec::new();
+        };
+
+        let executable = Self::is_executable(&path);
+
+        parse_rc_local(&content)
+            .into_iter()
+            .map(|entry| {
+                let program = entry.command.split_whitespace().next().unwrap_or_default();
+                let name = Path::new(program)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| program.to_string());
+
+                let description = if executable {
+                    "Runs at the end of boot from /etc/rc.local".to_string()
+                } else {
+                    "/etc/rc.local is not executable and will not run".to_string()
+                };
+
+                let mut item = StartupItem::new(
+                    name,
+                    SourceType::RcLocal,
+                    path.display().to_string(),
+                    entry.command.clone(),
+                )
+                .with_status(if entry.enabled {
+                    ItemStatus::Enabled
+                } else {
+                    ItemStatus::Disabled
+                })
+                .with_description(Some(description))
+                .with_line(entry.line);
+
+                self.resolve_program(&mut item, program, DEFAULT_CRON_PATH);
+                item
+            })
+            .collect()
+    }
+
+    /// Resolve the program inside the root, through `PATH` for bare names
+    fn resolve_program(&self, item: &mut StartupItem, program: &str, path_var: &str) {
+        let resolved = if program.starts_with('/') {
+            Some(self.under_root(program)).filter(|p| p.exists())
+        } else {
+            let rooted_path = std::env::join_paths(
+                std::env::split_paths(path_var).map(|d| self.under_root(&d.to_string_lossy())),
+            )
+            .ok()
+            .map(|p| p.to_string_lossy().to_string());
+            find_program(program, rooted_path.as_deref())
+        };
+
+        item.file_exists = resolved.is_some();
+        item.executable_path = Some(resolved.unwrap_or_else(|| PathBuf::from(program)));
+    }
+
+    #[cfg(unix)]
+    fn is_executable(path: &Path) -> bool {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+    }
+
+    #[cfg(not(unix))]
+    fn is_executable(_path: &Path) -> bool {
+        true
+    }
+
+    /// Line number of the entry backing `item`, matched by command text
+    /// and told apart from repeats by the line it was scanned on
+    fn find_line(item: &StartupItem, content: &str) -> Option<usize> {
+        let matches: Vec<usize> = match item.source {
+            SourceType::RcLocal => parse_rc_local(content)
+                .into_iter()
+                .filter(|e| e.command == item.command)
+                .map(|e| e.line)
+                .collect(),
+            _ => {
+                let system = !item.source_location.contains("/var/spool/cron");
+                parse_crontab(content, system)
+                    .into_iter()
+                    .filter(|e| e.command == item.command)
+                    .map(|e| e.line)
+                    .collect()
+            }
+        };
+        pick_line(&matches, item.line)
+    }
+
+    fn set_enabled(&self, item: &StartupItem, enabled: bool) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let content = fs::read_to_string(&path).map_err(Error::Io)?;
+
+        let updated = Self::find_line(item, &content)
+            .and_then(|line| set_line_enabled(&content, line, enabled))
+            .ok_or_else(|| Error::ItemNotFound {
+                id: item.id.clone(),
+            })?;
+
+        fs::write(&path, updated).map_err(Error::Io)
+    }
+
+    /// Where a recreated line goes: before the final `exit` of rc.local,
+    /// at the end of a crontab
+    fn insert_index(item: &StartupItem, content: &str) -> usize {
+        match item.source {
+            SourceType::RcLocal => content
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| line.trim_start().starts_with("exit"))
+                .map(|(index, _)| index)
+                .last()
+                .unwrap_or(usize::MAX),
+            _ => usize::MAX,
+        }
+    }
+}
+
+impl StartupSource for CronScanner {
+    fn scan(&self) -> Result<Vec<StartupItem>> {
+        let mut items = Vec::new();
+
+        for (path, system, owner) in self.crontabs() {
+            items.extend(self.scan_crontab(&path, system, owner));
+        }
+
+        items.extend(self.scan_rc_local());
+
+        Ok(items)
+    }
+
+    fn enable(&self, item: &StartupItem) -> Result<()> {
+        self.set_enabled(item, true)
+    }
+
+    fn disable(&self, item: &StartupItem) -> Result<()> {
+        self.set_enabled(item, false)
+    }
+
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        let content = fs::read_to_string(&item.source_location).map_err(Error::Io)?;
+        Ok(Self::find_line(item, &content)
+            .and_then(|index| line_at(&content, index))
+            .map(|line| RawPayload::Line {
+                line: uncomment(line),
+            }))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let Some(RawPayload::Line { line }) = raw else {
+            return Err(Error::RestoreFailed {
+                reason: format!("no crontab line was saved for {}", item.name),
+            });
+        };
+
+        let path = PathBuf::from(&item.source_location);
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        let line = line_for_status(line, item.status.is_enabled());
+        let updated = insert_line(&content, Self::insert_index(item, &content), &line);
+        fs::write(&path, updated).map_err(Error::Io)
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let content = fs::read_to_string(&path).map_err(Error::Io)?;
+
+        let updated = Self::find_line(item, &content)
+            .and_then(|index| remove_line(&content, index))
+            .ok_or_else(|| Error::ItemNotFound {
+                id: item.id.clone(),
+            })?;
+
+        fs::write(&path, updated).map_err(Error::Io)
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![SourceType::CronReboot, SourceType::RcLocal]
+    }
+}
+
+impl Default for CronScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::fake_root;
+
+    #[test]
+    fn parses_reboot_entries_with_their_environment() {
+        let content = "PATH=/opt/bin:/usr/bin\nSHELL = \"/bin/bash\"\n\
+                       @reboot root /opt/bin/agent --daemon\n\
+                       */5 * * * * root /usr/bin/other\n@rebootx root nope\n";
+        let entries = parse_crontab(content, true);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].line, 2);
+        assert_eq!(entries[0].user.as_deref(), Some("root"));
+        assert_eq!(entries[0].command, "/opt/bin/agent --daemon");
+        assert_eq!(entries[0].env_var("PATH"), Some("/opt/bin:/usr/bin"));
+        assert_eq!(entries[0].env_var("SHELL"), Some("/bin/bash"));
+    }
+
+    #[test]
+    fn user_crontabs_have_no_user_field() {
+        let entries = parse_crontab("@reboot   sleep 10 && ./start.sh\n", false);
+
+        assert_eq!(entries[0].user, None);
+        assert_eq!(entries[0].command, "sleep 10 && ./start.sh");
+    }
+
+    #[test]
+    fn rc_local_skips_exit_and_compound_commands() {
+        let content = "#!/bin/sh -e\n/usr/bin/one\nif true; then\n  /usr/bin/two\nfi\nexit 0\n";
+        let entries = parse_rc_local(content);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].command, "/usr/bin/one");
+        assert_eq!(entries[0].line, 1);
+    }
+
+    #[test]
+    fn repeated_commands_are_toggled_on_their_own_line() {
+        let root = fake_root("cron-repeat");
+        fs::create_dir_all(root.join("etc")).unwrap();
+        let crontab = root.join("etc/crontab");
+        fs::write(
+            &crontab,
+            "@reboot root /bin/echo hi\n@reboot root /bin/echo hi\n",
+        )
+        .unwrap();
+        let scanner = CronScanner::with_root(root.clone());
+
+        let items = scanner.scan().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].line, Some(1));
+
+        scanner.disable(&items[1]).unwrap();
+        let content = fs::read_to_string(&crontab).unwrap();
+        let entries = parse_crontab(&content, true);
+        assert!(entries[0].enabled);
+        assert!(!entries[1].enabled);
+
+        let items = scanner.scan().unwrap();
+        scanner.delete(&items[1]).unwrap();
+        let content = fs::read_to_string(&crontab).unwrap();
+        assert_eq!(content, "@reboot root /bin/echo hi\n");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,157 @@
+use super::comment_marker::strip_marker;
+
+/// A `key = value` line from an INI-style file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IniEntry {
+    /// Section name without brackets (empty for keys before the first section)
+    pub section: String,
+    pub key: String,
+    pub value: String,
+    /// 0-based line number in the file
+    pub line: usize,
+    /// False if the line was commented out by this tool
+    pub enabled: bool,
+}
+
+impl IniEntry {
+    /// The value with one level of surrounding quotes removed
+    pub fn unquoted_value(&self) -> &str {
+        let value = self.value.as_str();
+        for quote in ['"', '\''] {
+            if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+                return &value[1..value.len() - 1];
+            }
+        }
+        value
+    }
+
+    pub fn is(&self, section: &str, key: &str) -> bool {
+        self.section.eq_ignore_ascii_case(section) && self.key.eq_ignore_ascii_case(key)
+    }
+}
+
+/// Parse an INI-style file as used by `wsl.conf`, `.wslconfig` and `.desktop` files.
+///
+/// Lines commented out with the tool's marker are returned with `enabled: false`;
+/// ordinary `#` and `;` comments are skipped.
+pub fn parse(content: &str) -> Vec<IniEntry> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+
+    for (index, raw) in content.lines().enumerate() {
+        let (line, enabled) = match strip_marker(raw) {
+            Some(original) => (original.trim(), false),
+            None => (raw.trim(), true),
+        };
+
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if enabled {
+                section = name.trim().to_string();
+            }
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+
+        entries.push(IniEntry {
+            section: section.clone(),
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+            line: index,
+            enabled,
+        });
+    }
+
+    entries
+}
+
+/// 0-based index just past the last non-empty line of `section`, where a
+/// new key belongs. `None` if the file has no such section.
+pub fn section_end(content: &str, section: &str) -> Option<usize> {
+    let mut end = None;
+
+    for (index, raw) in content.lines().enumerate() {
+        let line = raw.trim();
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if end.is_some() {
+                break;
+            }
+            if name.trim().eq_ignore_ascii_case(section) {
+                end = Some(index + 1);
+            }
+            continue;
+        }
+        if end.is_some() && !line.is_empty() {
+            end = Some(index + 1);
+        }
+    }
+
+    end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::comment_marker::DISABLED_MARKER;
+
+    #[test]
+    fn parses_sections_keys_and_disabled_lines() {
+        let content = format!(
+            "top = 1\n# comment\n; other comment\n[boot]\n  command = \"echo hi\"\n\
+             {DISABLED_MARKER}command=mount -a\n\n[network]\nhostname=box\n"
+        );
+        let entries = parse(&content);
+
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].section, "");
+        assert_eq!(entries[0].key, "top");
+        assert!(entries[1].is("BOOT", "Command"));
+        assert_eq!(entries[1].value, "\"echo hi\"");
+        assert_eq!(entries[1].unquoted_value(), "echo hi");
+        assert_eq!(entries[1].line, 4);
+        assert!(entries[1].enabled);
+        assert_eq!(entries[2].value, "mount -a");
+        assert_eq!(entries[2].line, 5);
+        assert!(!entries[2].enabled);
+        assert!(entries[3].is("network", "hostname"));
+    }
+
+    #[test]
+    fn disabled_section_headers_do_not_switch_sections() {
+        let content = format!("[boot]\n{DISABLED_MARKER}[other]\ncommand=a\n");
+        let entries = parse(&content);
+
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].is("boot", "command"));
+    }
+
+    #[test]
+    fn unquotes_only_matching_quotes() {
+        let entry = |value: &str| IniEntry {
+            section: String::new(),
+            key: "k".to_string(),
+            value: value.to_string(),
+            line: 0,
+            enabled: true,
+        };
+
+        assert_eq!(entry("'a b'").unquoted_value(), "a b");
+        assert_eq!(entry("\"a b'").unquoted_value(), "\"a b'");
+        assert_eq!(entry("\"").unquoted_value(), "\"");
+    }
+
+    #[test]
+    fn section_end_is_after_the_last_key() {
+        let content = "[boot]\ncommand=a\n\n[wsl2]\nkernel=x\n\n";
+
+        assert_eq!(section_end(content, "boot"), Some(2));
+        assert_eq!(section_end(content, "WSL2"), Some(5));
+        assert_eq!(section_end(content, "network"), None);
+    }
+}
@@ -0,0 +1,736 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use plist::{Dictionary, Value};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+use super::{capture_file, write_captured_file, StartupSource};
+
+/// Where launchd keeps `launchctl disable` overrides
+const OVERRIDES_DIR: &str = "/private/var/db/com.apple.xpc.launchd";
+
+/// `loginwindow.plist` key listing legacy login items
+const LOGIN_ITEMS_KEY: &str = "AutoLaunchedApplicationDictionary";
+
+/// Where disabled login items are kept, next to the active ones
+const DISABLED_LOGIN_ITEMS_KEY: &str = "StartupCheckerDisabledApplications";
+
+/// On-disk encoding of a property list, kept when writing it back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlistFormat {
+    Xml,
+    Binary,
+}
+
+/// Read a property list in either XML or binary form
+pub fn read_plist(path: &Path) -> Result<(Value, PlistFormat)> {
+    let data = fs::read(path).map_err(Error::Io)?;
+    let format = if data.starts_with(b"bplist") {
+        PlistFormat::Binary
+    } else {
+        PlistFormat::Xml
+    };
+    let value = Value::from_reader(std::io::Cursor::new(data))?;
+    Ok((value, format))
+}
+
+pub fn write_plist(path: &Path, value: &Value, format: PlistFormat) -> Result<()> {
+    match format {
+        PlistFormat::Xml => value.to_file_xml(path)?,
+        PlistFormat::Binary => value.to_file_binary(path)?,
+    }
+    Ok(())
+}
+
+/// The parts of a launchd job definition that decide what runs at startup
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchdJob {
+    pub label: Option<String>,
+    pub program: Option<String>,
+    pub program_arguments: Vec<String>,
+    pub run_at_load: bool,
+    /// `KeepAlive` is true or a dictionary of conditions
+    pub keep_alive: bool,
+    pub disabled: bool,
+}
+
+impl LaunchdJob {
+    pub fn from_dictionary(dict: &Dictionary) -> Self {
+        let string = |key: &str| dict.get(key).and_then(Value::as_string).map(str::to_string);
+        let boolean = |key: &str| dict.get(key).and_then(Value::as_boolean).unwrap_or(false);
+
+        let program_arguments = dict
+            .get("ProgramArguments")
+            .and_then(Value::as_array)
+            .map(|args| {
+                args.iter()
+                    .filter_map(Value::as_string)
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let keep_alive = match dict.get("KeepAlive") {
+            Some(Value::Boolean(b)) => *b,
+            Some(Value::Dictionary(conditions)) => !conditions.is_empty(),
+            _ => false,
+        };
+
+        Self {
+            label: string("Label"),
+            program: string("Program"),
+            program_arguments,
+            run_at_load: boolean("RunAtLoad"),
+            keep_alive,
+            disabled: boolean("Disabled"),
+        }
+    }
+
+    /// `Program` wins over the first element of `ProgramArguments`
+    pub fn executable(&self) -> Option<&str> {
+        self.program
+            .as_deref()
+            .or_else(|| self.program_arguments.first().map(String::as_str))
+    }
+
+    /// The full command line, quoting arguments that contain spaces
+    pub fn command_line(&self) -> String {
+        let quote = |arg: &String| {
+            if arg.contains(char::is_whitespace) {
+                format!("\"{arg}\"")
+            } else {
+                arg.clone()
+            }
+        };
+
+        match (&self.program, self.program_arguments.is_empty()) {
+            (Some(program), true) => quote(program),
+            // With both set, `Program` runs and `ProgramArguments` become its argv
+            (Some(program), false) => std::iter::once(quote(program))
+                .chain(self.program_arguments.iter().skip(1).map(quote))
+                .collect::<Vec<_>>()
+                .join(" "),
+            (None, _) => self
+                .program_arguments
+                .iter()
+                .map(quote)
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+
+    fn summary(&self) -> String {
+        match (self.run_at_load, self.keep_alive) {
+            (true, true) => "Runs at load and is kept alive",
+            (true, false) => "Runs at load",
+            (false, true) => "Kept alive by launchd",
+            (false, false) => "Loaded at startup, started on demand",
+        }
+        .to_string()
+    }
+}
+
+pub struct LaunchdScanner {
+    root: PathBuf,
+    home: Option<PathBuf>,
+}
+
+impl LaunchdScanner {
+    pub fn new() -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            home: dirs::home_dir(),
+        }
+    }
+
+    /// Scan property lists below a different root directory.
+    /// `home` is the real path of the user's home directory, e.g. inside the root.
+    pub fn with_root(root: PathBuf, home: Option<PathBuf>) -> Self {
+        Self { root, home }
+    }
+
+    fn under_root(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    fn job_dir(&self, source: SourceType) -> Option<PathBuf> {
+        match source {
+            SourceType::LaunchAgentsUser => self
+                .home
+                .as_ref()
+                .map(|h| h.join("Library").join("LaunchAgents")),
+            SourceType::LaunchAgentsSystem => Some(self.under_root("/Library/LaunchAgents")),
+            SourceType::LaunchDaemons => Some(self.under_root("/Library/LaunchDaemons")),
+            _ => None,
+        }
+    }
+
+    /// `loginwindow.plist` files holding login items, with whether they are system-wide
+    fn login_item_files(&self) -> Vec<(PathBuf, bool)> {
+        let mut files = Vec::new();
+        if let Some(home) = &self.home {
+            files.push((
+                home.join("Library")
+                    .join("Preferences")
+                    .join("loginwindow.plist"),
+                false,
+            ));
+        }
+        files.push((
+            self.under_root("/Library/Preferences/loginwindow.plist"),
+            true,
+        ));
+        files
+    }
+
+    /// launchd keeps per-user overrides in `disabled.<uid>.plist`
+    #[cfg(unix)]
+    fn user_id(&self) -> Option<u32> {
+        use std::os::unix::fs::MetadataExt;
+        self.home
+            .as_ref()
+            .and_then(|h| fs::metadata(h).ok())
+            .map(|m| m.uid())
+    }
+
+    #[cfg(not(unix))]
+    fn user_id(&self) -> Option<u32> {
+        None
+    }
+
+    fn overrides_path(&self, source: SourceType) -> Option<PathBuf> {
+        let dir = self.under_root(OVERRIDES_DIR);
+        match source {
+            SourceType::LaunchAgentsUser => self
+                .user_id()
+                .map(|uid| dir.join(format!("disabled.{uid}.plist"))),
+            _ => Some(dir.join("disabled.plist")),
+        }
+    }
+
+    /// `launchctl disable`/`enable` overrides by label; they win over the `Disabled` key
+    fn overrides(&self, source: SourceType) -> HashMap<String, bool> {
+        self.overrides_path(source)
+            .and_then(|path| read_plist(&path).ok())
+            .and_then(|(value, _)| value.into_dictionary())
+            .map(|dict| {
+                dict.into_iter()
+                    .filter_map(|(label, v)| v.as_boolean().map(|b| (label, b)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn plist_files(dir: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "plist"))
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Resolve an absolute program path inside the root
+    fn resolve_program(&self, item: &mut StartupItem, program: Option<&str>) {
+        match program {
+            Some(program) if program.starts_with('/') => {
+                let path = self.under_root(program);
+                item.file_exists = path.exists();
+                item.executable_path = Some(path);
+            }
+            // launchd does not search PATH for relative programs
+            Some(program) => {
+                item.file_exists = false;
+                item.executable_path = Some(PathBuf::from(program));
+            }
+            None => {
+                item.file_exists = false;
+                item.executable_path = None;
+            }
+        }
+    }
+
+    fn scan_jobs(&self, source: SourceType) -> Vec<StartupItem> {
+        let Some(dir) = self.job_dir(source) else {
+            return Vec::new();
+        };
+        let overrides = self.overrides(source);
+
+        Self::plist_files(&dir)
+            .into_iter()
+            .filter_map(|path| {
+                let (value, _) = read_plist(&path).ok()?;
+                let job = LaunchdJob::from_dictionary(value.as_dictionary()?);
+
+                let name = job.label.clone().unwrap_or_else(|| {
+                    path.file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                });
+
+                let disabled = overrides.get(&name).copied().unwrap_or(job.disabled);
+
+                let mut item =
+                    StartupItem::new(name, source, path.display().to_string(), job.command_line())
+                        .with_status(if disabled {
+                            ItemStatus::Disabled
+                        } else {
+                            ItemStatus::Enabled
+                        })
+                        .with_description(Some(job.summary()));
+
+                self.resolve_program(&mut item, job.executable());
+                Some(item)
+            })
+            .collect()
+    }
+
+    fn scan_login_items(&self) -> Vec<StartupItem> {
+        let mut items = Vec::new();
+
+        for (path, system) in self.login_item_files() {
+            let Ok((value, _)) = read_plist(&path) else {
+                continue;
+            };
+            let Some(dict) = value.as_dictionary() else {
+                continue;
+            };
+
+            for (key, status) in [
+                (LOGIN_ITEMS_KEY, ItemStatus::Enabled),
+                (DISABLED_LOGIN_ITEMS_KEY, ItemStatus::Disabled),
+            ] {
+                let entries = dict.get(key).and_then(Value::as_array);
+                for app in entries
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_dictionary)
+                {
+                    let Some(app_path) = app.get("Path").and_then(Value::as_string) else {
+                        continue;
+                    };
+
+                    let name = Path::new(app_path)
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_else(|| app_path.to_string());
+                    let hidden = app.get("Hide").and_then(Value::as_boolean) == Some(true);
+
+                    let mut item = StartupItem::new(
+                        name,
+                        SourceType::LoginItems,
+                        path.display().to_string(),
+                        app_path.to_string(),
+                    )
+                    .with_status(status)
+                    .with_description(Some(if hidden {
+                        "Opens hidden at login".to_string()
+                    } else {
+                        "Opens at login".to_string()
+                    }));
+
+                    item.requires_admin = system;
+                    self.resolve_program(&mut item, Some(app_path));
+                    items.push(item);
+                }
+            }
+        }
+
+        items
+    }
+
+    fn set_job_enabled(&self, item: &StartupItem, enabled: bool) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let (mut value, format) = read_plist(&path)?;
+        let dict = value.as_dictionary_mut().ok_or_else(|| {
+            Error::InvalidPath(format!("{} is not a job definition", path.display()))
+        })?;
+
+        if enabled {
+            dict.remove("Disabled");
+        } else {
+            dict.insert("Disabled".to_string(), Value::Boolean(true));
+        }
+        write_plist(&path, &value, format)?;
+
+        // A `launchctl disable` override would keep the job off regardless
+        if enabled {
+            self.clear_override(item)?;
+        }
+
+        Ok(())
+    }
+
+    fn clear_override(&self, item: &StartupItem) -> Result<()> {
+        let Some(path) = self.overrides_path(item.source) else {
+            return Ok(());
+        };
+        let Ok((mut value, format)) = read_plist(&path) else {
+            return Ok(());
+        };
+        let Some(dict) = value.as_dictionary_mut() else {
+            return Ok(());
+        };
+
+        if dict.get(&item.name).and_then(Value::as_boolean) == Some(true) {
+            dict.insert(item.name.clone(), Value::Boolean(false));
+            write_plist(&path, &value, format)?;
+        }
+
+        Ok(())
+    }
+
+    /// Move a login item between the active and disabled lists
+    fn set_login_item_enabled(&self, item: &StartupItem, enabled: bool) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let (mut value, format) = read_plist(&path)?;
+        let not_found = || Error::ItemNotFound {
+            id: item.id.clone(),
+        };
+        let dict = value.as_dictionary_mut().ok_or_else(not_found)?;
+
+        let (from, to) = if enabled {
+            (DISABLED_LOGIN_ITEMS_KEY, LOGIN_ITEMS_KEY)
+        } else {
+            (LOGIN_ITEMS_KEY, DISABLED_LOGIN_ITEMS_KEY)
+        };
+
+        let entries = dict
+            .get_mut(from)
+            .and_then(Value::as_array_mut)
+            .ok_or_else(not_found)?;
+        let index = entries
+            .iter()
+            .position(|app| {
+                app.as_dictionary()
+                    .and_then(|a| a.get("Path"))
+                    .and_then(Value::as_string)
+                    == Some(item.command.as_str())
+            })
+            .ok_or_else(not_found)?;
+        let app = entries.remove(index);
+        if entries.is_empty() && from == DISABLED_LOGIN_ITEMS_KEY {
+            dict.remove(from);
+        }
+
+        match dict.get_mut(to).and_then(Value::as_array_mut) {
+            Some(target) => target.push(app),
+            None => {
+                dict.insert(to.to_string(), Value::Array(vec![app]));
+            }
+        }
+
+        write_plist(&path, &value, format)
+    }
+
+    /// The list holding a login item, and its position in it
+    fn find_login_item(dict: &Dictionary, item: &StartupItem) -> Option<(&'static str, usize)> {
+        [LOGIN_ITEMS_KEY, DISABLED_LOGIN_ITEMS_KEY]
+            .into_iter()
+            .find_map(|key| {
+                let index = dict.get(key)?.as_array()?.iter().position(|app| {
+                    app.as_dictionary()
+                        .and_then(|a| a.get("Path"))
+                        .and_then(Value::as_string)
+                        == Some(item.command.as_str())
+                })?;
+                Some((key, index))
+            })
+    }
+
+    fn capture_login_item(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        let (value, _) = read_plist(Path::new(&item.source_location))?;
+        let Some(dict) = value.as_dictionary() else {
+            return Ok(None);
+        };
+        let Some(app) = Self::find_login_item(dict, item)
+            .and_then(|(key, index)| dict.get(key)?.as_array()?.get(index))
+        else {
+            return Ok(None);
+        };
+
+        let mut xml = Vec::new();
+        app.to_writer_xml(&mut xml)?;
+        Ok(Some(RawPayload::PlistEntry {
+            xml: String::from_utf8_lossy(&xml).to_string(),
+        }))
+    }
+
+    fn recreate_login_item(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let Some(RawPayload::PlistEntry { xml }) = raw else {
+            return Err(Error::RestoreFailed {
+                reason: format!("no login item entry was saved for {}", item.name),
+            });
+        };
+        let app = Value::from_reader(std::io::Cursor::new(xml.as_bytes()))?;
+
+        let path = PathBuf::from(&item.source_location);
+        let (mut value, format) = read_plist(&path)?;
+        let dict = value.as_dictionary_mut().ok_or_else(|| {
+            Error::InvalidPath(format!(
+                "{} is not a property list dictionary",
+                path.display()
+            ))
+        })?;
+
+        let key = if item.status.is_disabled() {
+            DISABLED_LOGIN_ITEMS_KEY
+        } else {
+            LOGIN_ITEMS_KEY
+        };
+        match dict.get_mut(key).and_then(Value::as_array_mut) {
+            Some(entries) => entries.push(app),
+            None => {
+                dict.insert(key.to_string(), Value::Array(vec![app]));
+            }
+        }
+
+        write_plist(&path, &value, format)
+    }
+
+    fn delete_login_item(&self, item: &StartupItem) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let (mut value, format) = read_plist(&path)?;
+        let not_found = || Error::ItemNotFound {
+            id: item.id.clone(),
+        };
+        let dict = value.as_dictionary_mut().ok_or_else(not_found)?;
+
+        let (key, index) = Self::find_login_item(dict, item).ok_or_else(not_found)?;
+        let entries = dict
+            .get_mut(key)
+            .and_then(Value::as_array_mut)
+            .ok_or_else(not_found)?;
+        entries.remove(index);
+        if entries.is_empty() && key == DISABLED_LOGIN_ITEMS_KEY {
+            dict.remove(key);
+        }
+
+        write_plist(&path, &value, format)
+    }
+
+    fn set_enabled(&self, item: &StartupItem, enabled: bool) -> Result<()> {
+        match item.source {
+            SourceType::LoginItems => self.set_login_item_enabled(item, enabled),
+            _ => self.set_job_enabled(item, enabled),
+        }
+    }
+}
+
+impl StartupSource for LaunchdScanner {
+    fn scan(&self) -> Result<Vec<StartupItem>> {
+        let mut items = Vec::new();
+
+        for source in [
+            SourceType::LaunchAgentsUser,
+            SourceType::LaunchAgentsSystem,
+            SourceType::LaunchDaemons,
+        ] {
+            items.extend(self.scan_jobs(source));
+        }
+
+        items.extend(self.scan_login_items());
+
+        Ok(items)
+    }
+
+    fn enable(&self, item: &StartupItem) -> Result<()> {
+        self.set_enabled(item, true)
+    }
+
+    fn disable(&self, item: &StartupItem) -> Result<()> {
+        self.set_enabled(item, false)
+    }
+
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        match item.source {
+            SourceType::LoginItems => self.capture_login_item(item),
+            _ => capture_file(Path::new(&item.source_location)),
+        }
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        match item.source {
+            SourceType::LoginItems => self.recreate_login_item(item, raw),
+            _ => {
+                write_captured_file(item, Path::new(&item.source_location), raw)?;
+                self.set_job_enabled(item, item.status.is_enabled())
+            }
+        }
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        match item.source {
+            SourceType::LoginItems => self.delete_login_item(item),
+            _ => fs::remove_file(&item.source_location).map_err(Error::Io),
+        }
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![
+            SourceType::LaunchAgentsUser,
+            SourceType::LaunchAgentsSystem,
+            SourceType::LaunchDaemons,
+            SourceType::LoginItems,
+        ]
+    }
+}
+
+impl Default for LaunchdScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::fake_root;
+
+    fn job(label: &str, program: &str) -> Value {
+        let mut dict = Dictionary::new();
+        dict.insert("Label".to_string(), Value::String(label.to_string()));
+        dict.insert(
+            "ProgramArguments".to_string(),
+            Value::Array(vec![
+                Value::String(program.to_string()),
+                Value::String("--at login".to_string()),
+            ]),
+        );
+        dict.insert("RunAtLoad".to_string(), Value::Boolean(true));
+        Value::Dictionary(dict)
+    }
+
+    #[test]
+    fn builds_the_command_line() {
+        let job = LaunchdJob::from_dictionary(
+            job("com.example.sync", "/opt/sync")
+                .as_dictionary()
+                .unwrap(),
+        );
+
+        assert_eq!(job.executable(), Some("/opt/sync"));
+        assert_eq!(job.command_line(), "/opt/sync \"--at login\"");
+        assert!(job.run_at_load);
+
+        let with_program = LaunchdJob {
+            program: Some("/opt/real".to_string()),
+            ..job
+        };
+        assert_eq!(with_program.executable(), Some("/opt/real"));
+        assert_eq!(with_program.command_line(), "/opt/real \"--at login\"");
+    }
+
+    #[test]
+    fn keeps_the_plist_format_when_writing() {
+        let root = fake_root("launchd-format");
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("job.plist");
+
+        for format in [PlistFormat::Binary, PlistFormat::Xml] {
+            write_plist(&path, &job("com.example.a", "/bin/a"), format).unwrap();
+            let (value, read_format) = read_plist(&path).unwrap();
+            assert_eq!(read_format, format);
+            assert_eq!(value, job("com.example.a", "/bin/a"));
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn toggles_jobs_and_clears_launchctl_overrides() {
+        let root = fake_root("launchd-jobs");
+        let agents = root.join("Library/LaunchAgents");
+        fs::create_dir_all(&agents).unwrap();
+        let path = agents.join("com.example.sync.plist");
+        write_plist(
+            &path,
+            &job("com.example.sync", "/opt/sync"),
+            PlistFormat::Binary,
+        )
+        .unwrap();
+
+        let overrides_dir = root.join(OVERRIDES_DIR.trim_start_matches('/'));
+        fs::create_dir_all(&overrides_dir).unwrap();
+        let mut overrides = Dictionary::new();
+        overrides.insert("com.example.sync".to_string(), Value::Boolean(true));
+        write_plist(
+            &overrides_dir.join("disabled.plist"),
+            &Value::Dictionary(overrides),
+            PlistFormat::Xml,
+        )
+        .unwrap();
+
+        let scanner = LaunchdScanner::with_root(root.clone(), None);
+        let items = scanner.scan().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].source, SourceType::LaunchAgentsSystem);
+        assert_eq!(items[0].status, ItemStatus::Disabled);
+
+        scanner.enable(&items[0]).unwrap();
+        let items = scanner.scan().unwrap();
+        assert_eq!(items[0].status, ItemStatus::Enabled);
+        assert_eq!(read_plist(&path).unwrap().1, PlistFormat::Binary);
+
+        scanner.disable(&items[0]).unwrap();
+        let (value, _) = read_plist(&path).unwrap();
+        assert!(LaunchdJob::from_dictionary(value.as_dictionary().unwrap()).disabled);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn moves_login_items_between_lists() {
+        let root = fake_root("launchd-login");
+        let home = root.join("Users/me");
+        let prefs = home.join("Library/Preferences");
+        fs::create_dir_all(&prefs).unwrap();
+        let path = prefs.join("loginwindow.plist");
+
+        let mut app = Dictionary::new();
+        app.insert(
+            "Path".to_string(),
+            Value::String("/Applications/Notes.app".to_string()),
+        );
+        app.insert("Hide".to_string(), Value::Boolean(true));
+        let mut dict = Dictionary::new();
+        dict.insert(
+            LOGIN_ITEMS_KEY.to_string(),
+            Value::Array(vec![Value::Dictionary(app)]),
+        );
+        write_plist(&path, &Value::Dictionary(dict), PlistFormat::Xml).unwrap();
+
+        let scanner = LaunchdScanner::with_root(root.clone(), Some(home));
+        let items = scanner.scan().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "Notes");
+        assert_eq!(items[0].status, ItemStatus::Enabled);
+
+        let raw = scanner.capture(&items[0]).unwrap();
+        scanner.disable(&items[0]).unwrap();
+        let items = scanner.scan().unwrap();
+        assert_eq!(items[0].status, ItemStatus::Disabled);
+
+        scanner.delete(&items[0]).unwrap();
+        assert!(scanner.scan().unwrap().is_empty());
+
+        scanner.recreate(&items[0], raw.as_ref()).unwrap();
+        let items = scanner.scan().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].status, ItemStatus::Disabled);
+        assert_eq!(
+            items[0].description.as_deref(),
+            Some("Opens hidden at login")
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
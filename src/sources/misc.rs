@@ -0,0 +1,277 @@
+use windows::Win32::System::Registry::{HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, REG_SZ};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+use super::registry::RegistryScanner;
+use super::{StartupSource, DISABLED_SUBKEY};
+
+/// How a value is switched off without breaking the key it lives in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DisableMethod {
+    /// The value is optional: move it into `AutorunsDisabled`
+    Move,
+    /// Windows expects the value to exist: stash it in `AutorunsDisabled`
+    /// and leave an empty string behind
+    Clear,
+}
+
+struct MiscValue {
+    label: &'static str,
+    root: HKEY,
+    root_name: &'static str,
+    key: &'static str,
+    value: &'static str,
+    method: DisableMethod,
+}
+
+impl MiscValue {
+    fn location(&self) -> String {
+        format!("{}\\{}", self.root_name, self.key)
+    }
+
+    fn disabled_key(&self) -> String {
+        format!("{}\\{DISABLED_SUBKEY}", self.key)
+    }
+
+    /// Key holding the command of an item with this status
+    fn value_key(&self, status: ItemStatus) -> String {
+        if status.is_disabled() {
+            self.disabled_key()
+        } else {
+            self.key.to_string()
+        }
+    }
+}
+
+const MISC_VALUES: &[MiscValue] = &[
+    MiscValue {
+        label: "Screensaver",
+        root: HKEY_CURRENT_USER,
+        root_name: "HKCU",
+        key: r"Control Panel\Desktop",
+        value: "SCRNSAVE.EXE",
+        method: DisableMethod::Move,
+    },
+    MiscValue {
+        label: "RDP InitialProgram",
+        root: HKEY_LOCAL_MACHINE,
+        root_name: "HKLM",
+        key: r"SYSTEM\CurrentControlSet\Control\Terminal Server\WinStations\RDP-Tcp",
+        value: "InitialProgram",
+        method: DisableMethod::Clear,
+    },
+    MiscValue {
+        label: "Command Processor AutoRun (HKCU)",
+        root: HKEY_CURRENT_USER,
+        root_name: "HKCU",
+        key: r"Software\Microsoft\Command Processor",
+        value: "AutoRun",
+        method: DisableMethod::Move,
+    },
+    MiscValue {
+        label: "Command Processor AutoRun (HKLM)",
+        root: HKEY_LOCAL_MACHINE,
+        root_name: "HKLM",
+        key: r"SOFTWARE\Microsoft\Command Processor",
+        value: "AutoRun",
+        method: DisableMethod::Move,
+    },
+    MiscValue {
+        label: "Windows Load",
+        root: HKEY_CURRENT_USER,
+        root_name: "HKCU",
+        key: r"Software\Microsoft\Windows NT\CurrentVersion\Windows",
+        value: "Load",
+        method: DisableMethod::Clear,
+    },
+    MiscValue {
+        label: "Windows Run",
+        root: HKEY_CURRENT_USER,
+        root_name: "HKCU",
+        key: r"Software\Microsoft\Windows NT\CurrentVersion\Windows",
+        value: "Run",
+        method: DisableMethod::Clear,
+    },
+];
+
+pub struct MiscScanner {
+    registry: RegistryScanner,
+}
+
+impl MiscScanner {
+    pub fn new() -> Self {
+        Self {
+            registry: RegistryScanner::new(),
+        }
+    }
+
+    fn find_value(item: &StartupItem) -> Result<&'static MiscValue> {
+        MISC_VALUES
+            .iter()
+            .find(|v| v.label == item.name && v.location() == item.source_location)
+            .ok_or_else(|| Error::ItemNotFound {
+                id: item.id.clone(),
+            })
+    }
+
+    fn scan_value(&self, entry: &MiscValue) -> Option<StartupItem> {
+        let active = self
+            .registry
+            .read_value(entry.root, entry.key, entry.value)
+            .filter(|v| !v.trim().is_empty());
+
+        let (command, status) = match active {
+            Some(command) => (command, ItemStatus::Enabled),
+            None => {
+                let stashed = self
+                    .registry
+                    .read_value(entry.root, &entry.disabled_key(), entry.value)
+                    .filter(|v| !v.trim().is_empty())?;
+                (stashed, ItemStatus::Disabled)
+            }
+        };
+
+        let mut item = StartupItem::new(
+            entry.label.to_string(),
+            SourceType::Miscellaneous,
+            entry.location(),
+            command,
+        )
+        .with_status(status)
+        .with_description(Some(format!("{}\\{}", entry.location(), entry.value)));
+
+        item.requires_admin = entry.root == HKEY_LOCAL_MACHINE;
+
+        Some(item)
+    }
+
+    /// Move the stashed value back over the empty one, keeping its type
+    fn restore_cleared(&self, entry: &MiscValue) -> Result<()> {
+        self.registry
+            .move_value(entry.root, &entry.disabled_key(), entry.key, entry.value)
+    }
+
+    /// Stash the value in `AutorunsDisabled` and leave an empty string of the
+    /// same type behind
+    fn stash_and_clear(&self, entry: &MiscValue) -> Result<()> {
+        let (value_type, data) = self
+            .registry
+            .read_raw_value(entry.root, entry.key, entry.value)
+            .ok_or_else(|| Error::ItemNotFound {
+                id: format!("Registry value not found: {}\\{}", entry.key, entry.value),
+            })?;
+
+        self.registry.write_raw_value(
+            entry.root,
+            &entry.disabled_key(),
+            entry.value,
+            value_type,
+            &data,
+        )?;
+        self.clear(entry, value_type)
+    }
+
+    /// Leave an empty string of `value_type` in the value Windows reads
+    fn clear(&self, entry: &MiscValue, value_type: u32) -> Result<()> {
+        self.registry
+            .write_raw_value(entry.root, entry.key, entry.value, value_type, &[0, 0])
+    }
+}
+
+impl StartupSource for MiscScanner {
+    fn scan(&self) -> Result<Vec<StartupItem>> {
+        Ok(MISC_VALUES
+            .iter()
+            .filter_map(|entry| self.scan_value(entry))
+            .collect())
+    }
+
+    fn enable(&self, item: &StartupItem) -> Result<()> {
+        let entry = Self::find_value(item)?;
+
+        match entry.method {
+            DisableMethod::Move => {
+                self.registry
+                    .move_value(entry.root, &entry.disabled_key(), entry.key, entry.value)
+            }
+            DisableMethod::Clear => self.restore_cleared(entry),
+        }
+    }
+
+    fn disable(&self, item: &StartupItem) -> Result<()> {
+        let entry = Self::find_value(item)?;
+
+        match entry.method {
+            DisableMethod::Move => {
+                self.registry
+                    .move_value(entry.root, entry.key, &entry.disabled_key(), entry.value)
+            }
+            DisableMethod::Clear => self.stash_and_clear(entry),
+        }
+    }
+
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        let entry = Self::find_value(item)?;
+        Ok(self
+            .registry
+            .read_raw_value(entry.root, &entry.value_key(item.status), entry.value)
+            .map(|(value_type, data)| RawPayload::RegistryValue { value_type, data }))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let entry = Self::find_value(item)?;
+        let (value_type, data) = match raw {
+            Some(RawPayload::RegistryValue { value_type, data }) => (*value_type, data.clone()),
+            _ => (
+                REG_SZ.0,
+                item.command
+                    .encode_utf16()
+                    .chain(std::iter::once(0))
+                    .flat_map(|w| w.to_le_bytes())
+                    .collect(),
+            ),
+        };
+
+        self.registry.write_raw_value(
+            entry.root,
+            &entry.value_key(item.status),
+            entry.value,
+            value_type,
+            &data,
+        )
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        let entry = Self::find_value(item)?;
+
+        match entry.method {
+            DisableMethod::Move => {
+                self.registry
+                    .remove_value(entry.root, &entry.value_key(item.status), entry.value)
+            }
+            // Windows expects the value, so empty it and drop the stash
+            DisableMethod::Clear => {
+                if item.status.is_disabled() {
+                    self.registry
+                        .remove_value(entry.root, &entry.disabled_key(), entry.value)?;
+                }
+                let value_type = self
+                    .registry
+                    .read_raw_value(entry.root, entry.key, entry.value)
+                    .map_or(REG_SZ.0, |(value_type, _)| value_type);
+                self.clear(entry, value_type)
+            }
+        }
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![SourceType::Miscellaneous]
+    }
+}
+
+impl Default for MiscScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,15 +1,57 @@
+pub mod comment_marker;
+mod cron;
+pub mod ini;
+mod launchd;
+#[cfg(windows)]
+mod misc;
+#[cfg(windows)]
+mod powershell;
+#[cfg(windows)]
 mod registry;
 mod scheduled_tasks;
+mod service_state;
+#[cfg(windows)]
 mod services;
+pub mod shell_script;
+mod shell_startup;
+mod shortcut;
 mod startup_folder;
+mod systemd;
+mod wsl;
+mod xdg_autostart;
 
+pub use cron::{parse_crontab, parse_rc_local, CronEntry, CronScanner, RcLocalEntry};
+pub use launchd::{read_plist, write_plist, LaunchdJob, LaunchdScanner, PlistFormat};
+#[cfg(windows)]
+pub use misc::MiscScanner;
+#[cfg(windows)]
+pub use powershell::PowerShellScanner;
+#[cfg(windows)]
 pub use registry::RegistryScanner;
-pub use scheduled_tasks::TaskSchedulerScanner;
+pub use scheduled_tasks::{exec_command, replace_exec, LogonTask, TaskSchedulerScanner};
+pub use service_state::{SavedStartType, ServiceDisableMode, ServiceState};
+#[cfg(windows)]
 pub use services::ServicesScanner;
+pub use shell_startup::{parse_startup_file, ShellEntry, ShellEntryKind, ShellStartupScanner};
+pub use shortcut::Shortcut;
 pub use startup_folder::StartupFolderScanner;
+pub use systemd::{template_name, SystemdScanner, UnitFile};
+pub use wsl::{boot_commands, wslconfig_boot_entries, WslDistribution, WslScanner};
+pub use xdg_autostart::{
+    find_program, parse_exec, set_desktop_key, DesktopEntry, XdgAutostartScanner,
+};
 
-use crate::error::Result;
-use crate::models::{ItemStatus, SourceType, StartupItem};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+#[cfg(test)]
+use std::path::PathBuf;
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+/// Subkey the registry sources move disabled values to, as Autoruns does
+pub(crate) const DISABLED_SUBKEY: &str = "AutorunsDisabled";
 
 /// Trait for startup item sources
 pub trait StartupSource: Send + Sync {
@@ -22,79 +64,339 @@ pub trait StartupSource: Send + Sync {
     /// Disable a startup item
     fn disable(&self, item: &StartupItem) -> Result<()>;
 
+    /// Read the source-specific data a backup needs to recreate the item
+    fn capture(&self, _item: &StartupItem) -> Result<Option<RawPayload>> {
+        Ok(None)
+    }
+
+    /// Recreate an item that no longer exists, e.g. from a backup, using
+    /// the captured payload where there is one. The item's status decides
+    /// whether it is written enabled or disabled.
+    fn recreate(&self, item: &StartupItem, _raw: Option<&RawPayload>) -> Result<()> {
+        Err(Error::RestoreFailed {
+            reason: format!("{} items cannot be recreated", item.source.display_name()),
+        })
+    }
+
+    /// Change the item's name and command line, keeping its status
+    fn edit(&self, item: &StartupItem, _name: &str, _command: &str) -> Result<()> {
+        Err(Error::Unsupported(format!(
+            "editing {} items",
+            item.source.display_name()
+        )))
+    }
+
+    /// Start the item after the others, for sources with their own way to
+    /// delay it, such as delayed automatic services
+    fn set_delayed_start(&self, item: &StartupItem, _delayed: bool) -> Result<()> {
+        Err(Error::Unsupported(format!(
+            "delaying {} items in place",
+            item.source.display_name()
+        )))
+    }
+
+    /// Remove the item for good. Callers capture it first so it can be
+    /// recreated from the quarantine.
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        Err(Error::Unsupported(format!(
+            "deleting {} items",
+            item.source.display_name()
+        )))
+    }
+
     /// Get the source types this scanner handles
     fn source_types(&self) -> Vec<SourceType>;
 }
 
-/// Scan all sources and return combined results
-pub fn scan_all_sources() -> Vec<StartupItem> {
-    let mut items = Vec::new();
+/// All scanners available on this platform, in display order
+pub fn all_scanners() -> Vec<Box<dyn StartupSource>> {
+    vec![
+        #[cfg(windows)]
+        Box::new(RegistryScanner::new()),
+        #[cfg(windows)]
+        Box::new(StartupFolderScanner::new()),
+        #[cfg(windows)]
+        Box::new(TaskSchedulerScanner::new()),
+        #[cfg(windows)]
+        Box::new(ServicesScanner::new()),
+        #[cfg(windows)]
+        Box::new(PowerShellScanner::new()),
+        #[cfg(windows)]
+        Box::new(MiscScanner::new()),
+        #[cfg(windows)]
+        Box::new(WslScanner::new()),
+        #[cfg(all(unix, not(target_os = "macos")))]
+        Box::new(XdgAutostartScanner::new()),
+        #[cfg(target_os = "linux")]
+        Box::new(SystemdScanner::new()),
+        #[cfg(target_os = "linux")]
+        Box::new(CronScanner::new()),
+        #[cfg(target_os = "linux")]
+        Box::new(ShellStartupScanner::new()),
+        #[cfg(target_os = "macos")]
+        Box::new(LaunchdScanner::new()),
+    ]
+}
 
-    // Registry
-    let registry = RegistryScanner::new();
-    if let Ok(registry_items) = registry.scan() {
-        items.extend(registry_items);
-    }
+/// Get the scanner responsible for a source type
+pub fn scanner_for(source: SourceType) -> Result<Box<dyn StartupSource>> {
+    all_scanners()
+        .into_iter()
+        .find(|scanner| scanner.source_types().contains(&source))
+        .ok_or_else(|| Error::Unsupported(source.display_name().to_string()))
+}
 
-    // Startup folders
-    let folders = StartupFolderScanner::new();
-    if let Ok(folder_items) = folders.scan() {
-        items.extend(folder_items);
+/// Capture the raw data behind a startup item for a backup
+pub fn capture_item(item: &StartupItem) -> Result<Option<RawPayload>> {
+    scanner_for(item.source)?.capture(item)
+}
+
+/// Recreate a deleted startup item
+pub fn recreate_item(item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+    scanner_for(item.source)?.recreate(item, raw)
+}
+
+/// Rename a startup item or change its command line
+pub fn edit_item(item: &StartupItem, name: &str, command: &str) -> Result<()> {
+    scanner_for(item.source)?.edit(item, name, command)
+}
+
+/// Turn a source's own delayed start on or off for an item
+pub fn set_delayed_start(item: &StartupItem, delayed: bool) -> Result<()> {
+    scanner_for(item.source)?.set_delayed_start(item, delayed)
+}
+
+/// Delete a startup item without keeping a copy
+pub fn delete_item(item: &StartupItem) -> Result<()> {
+    scanner_for(item.source)?.delete(item)
+}
+
+/// Capture a whole file, e.g. a profile script or unit file
+fn capture_file(path: &Path) -> Result<Option<RawPayload>> {
+    if !path.is_file() {
+        return Ok(None);
     }
 
-    // Scheduled tasks
-    let tasks = TaskSchedulerScanner::new();
-    if let Ok(task_items) = tasks.scan() {
-        items.extend(task_items);
+    let data = fs::read(path).map_err(Error::Io)?;
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(Some(RawPayload::File { file_name, data }))
+}
+
+/// Write a file captured by [`capture_file`] back to `path`
+fn write_captured_file(item: &StartupItem, path: &Path, raw: Option<&RawPayload>) -> Result<()> {
+    let Some(RawPayload::File { data, .. } | RawPayload::DesktopEntry { data, .. }) = raw else {
+        return Err(Error::RestoreFailed {
+            reason: format!("no file contents were saved for {}", item.name),
+        });
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(Error::Io)?;
     }
+    fs::write(path, data).map_err(Error::Io)
+}
+
+/// Scan all sources and return combined results
+pub fn scan_all_sources() -> Vec<StartupItem> {
+    let mut items = Vec::new();
 
-    // Services
-    let services = ServicesScanner::new();
-    if let Ok(service_items) = services.scan() {
-        items.extend(service_items);
+    for scanner in all_scanners() {
+        if let Ok(scanned) = scanner.scan() {
+            items.extend(scanned);
+        }
     }
 
+    StartupItem::disambiguate_ids(&mut items);
+    mark_still_triggered(&mut items);
     items
 }
 
-/// Modify a startup item's status
-pub fn modify_item(item: &StartupItem, new_status: ItemStatus) -> Result<()> {
-    match item.source {
-        SourceType::RegistryCurrentUserRun
-        | SourceType::RegistryCurrentUserRunOnce
-        | SourceType::RegistryLocalMachineRun
-        | SourceType::RegistryLocalMachineRunOnce
-        | SourceType::RegistryLocalMachineWow6432 => {
-            let scanner = RegistryScanner::new();
-            match new_status {
-                ItemStatus::Enabled => scanner.enable(item),
-                ItemStatus::Disabled => scanner.disable(item),
-                ItemStatus::Unknown => Ok(()),
-            }
-        }
-        SourceType::StartupFolderUser | SourceType::StartupFolderAllUsers => {
-            let scanner = StartupFolderScanner::new();
-            match new_status {
-                ItemStatus::Enabled => scanner.enable(item),
-                ItemStatus::Disabled => scanner.disable(item),
-                ItemStatus::Unknown => Ok(()),
-            }
-        }
-        SourceType::ScheduledTask => {
-            let scanner = TaskSchedulerScanner::new();
-            match new_status {
-                ItemStatus::Enabled => scanner.enable(item),
-                ItemStatus::Disabled => scanner.disable(item),
-                ItemStatus::Unknown => Ok(()),
+/// Programs that host or interpret other code. Two items running the same
+/// host say nothing about whether they start the same thing.
+const LAUNCHER_HOSTS: &[&str] = &[
+    "svchost",
+    "dllhost",
+    "rundll32",
+    "regsvr32",
+    "msiexec",
+    "cmd",
+    "powershell",
+    "pwsh",
+    "wscript",
+    "cscript",
+    "mshta",
+    "conhost",
+    "explorer",
+    "wsl",
+    "sh",
+    "bash",
+    "zsh",
+    "env",
+    "python",
+    "python3",
+    "pythonw",
+    "perl",
+    "ruby",
+    "node",
+    "java",
+    "javaw",
+    "osascript",
+    "open",
+];
+
+fn is_launcher_host(path: &Path) -> bool {
+    // Windows paths are split by hand so they are read the same on any OS
+    let path = path.to_string_lossy();
+    let file_name = path.rsplit(['\\', '/']).next().unwrap_or_default();
+    let stem = file_name
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    LAUNCHER_HOSTS.contains(&stem.as_str())
+}
+
+/// What an item ultimately starts: the payload a launcher runs when there
+/// is one, otherwise the whole command line. `None` for items that only
+/// name a launcher host, such as a `svchost` service.
+fn launch_target(item: &StartupItem) -> Option<String> {
+    let target = match item.payload.as_ref().and_then(|p| p.path.as_ref()) {
+        Some(path) if !is_launcher_host(path) => path.to_string_lossy().to_string(),
+        Some(_) => return None,
+        None => {
+            let program = item.executable_path.as_ref()?;
+            if is_launcher_host(program) {
+                return None;
             }
+            std::iter::once(program.to_string_lossy().to_string())
+                .chain(item.arguments.iter().cloned())
+                .collect::<Vec<_>>()
+                .join(" ")
         }
-        SourceType::WindowsService => {
-            let scanner = ServicesScanner::new();
-            match new_status {
-                ItemStatus::Enabled => scanner.enable(item),
-                ItemStatus::Disabled => scanner.disable(item),
-                ItemStatus::Unknown => Ok(()),
-            }
+    };
+
+    Some(if item.source.is_windows() {
+        target.to_lowercase()
+    } else {
+        target
+    })
+}
+
+/// Flag disabled items whose program another enabled item still starts,
+/// e.g. an updater disabled in the Run key but kept alive by a task
+fn mark_still_triggered(items: &mut [StartupItem]) {
+    let enabled: HashMap<String, String> = items
+        .iter()
+        .filter(|i| i.status.is_enabled() && i.file_exists)
+        .filter_map(|i| Some((launch_target(i)?, i.name.clone())))
+        .collect();
+
+    for item in items.iter_mut() {
+        if item.status != ItemStatus::Disabled {
+            continue;
         }
+        let Some(other) = launch_target(item).and_then(|t| enabled.get(&t)) else {
+            continue;
+        };
+
+        let note = format!("Still started by {other}");
+        item.description = Some(match item.description.take() {
+            Some(description) => format!("{description}. {note}"),
+            None => note,
+        });
+        item.status = ItemStatus::DisabledButTriggered;
+    }
+}
+
+/// Modify a startup item's status
+pub fn modify_item(item: &StartupItem, new_status: ItemStatus) -> Result<()> {
+    let scanner = scanner_for(item.source)?;
+    match new_status {
+        ItemStatus::Enabled | ItemStatus::Pending => scanner.enable(item),
+        ItemStatus::Disabled | ItemStatus::DisabledButTriggered => scanner.disable(item),
+        ItemStatus::DisabledByPolicy => Err(Error::PermissionDenied {
+            message: format!("{} is disabled by policy", item.name),
+        }),
+        ItemStatus::Unknown => Ok(()),
+    }
+}
+
+/// An empty directory standing in for `/` or a home directory in tests,
+/// unique to `name` and this test run
+#[cfg(test)]
+pub(crate) fn fake_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("startup-checker-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, source: SourceType, command: &str, status: ItemStatus) -> StartupItem {
+        let mut item = StartupItem::new(
+            name.to_string(),
+            source,
+            format!("test\\{name}"),
+            command.to_string(),
+        )
+        .with_status(status);
+        item.file_exists = true;
+        item
+    }
+
+    fn triggered(disabled: &str, enabled: &str) -> bool {
+        let mut items = vec![
+            item(
+                "a",
+                SourceType::RegistryCurrentUserRun,
+                disabled,
+                ItemStatus::Disabled,
+            ),
+            item("b", SourceType::ScheduledTask, enabled, ItemStatus::Enabled),
+        ];
+        mark_still_triggered(&mut items);
+        items[0].status == ItemStatus::DisabledButTriggered
+    }
+
+    #[test]
+    fn flags_a_program_another_item_starts() {
+        assert!(triggered(
+            r"C:\App\updater.exe /silent",
+            r"c:\app\UPDATER.exe /silent"
+        ));
+        assert!(!triggered(
+            r"C:\App\updater.exe /silent",
+            r"C:\App\updater.exe /check"
+        ));
+    }
+
+    #[test]
+    fn matches_launchers_by_their_payload() {
+        assert!(triggered(
+            r"rundll32.exe C:\App\app.dll,Start",
+            r"C:\Windows\System32\rundll32.exe C:\App\app.dll,Other"
+        ));
+        assert!(!triggered(
+            r"rundll32.exe C:\App\app.dll,Start",
+            r"rundll32.exe C:\Other\other.dll,Start"
+        ));
+    }
+
+    #[test]
+    fn never_matches_on_a_shared_host() {
+        assert!(!triggered(
+            r"C:\Windows\System32\svchost.exe -k netsvcs",
+            r"C:\Windows\System32\svchost.exe -k netsvcs"
+        ));
+        assert!(!triggered(
+            "powershell.exe -Command Start-App",
+            "powershell.exe -Command Start-App"
+        ));
+        assert!(!triggered("rundll32.exe", "rundll32.exe"));
     }
 }
@@ -0,0 +1,297 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use windows::Win32::System::Registry::HKEY_CURRENT_USER;
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+use super::registry::RegistryScanner;
+use super::{capture_file, write_captured_file, StartupSource, DISABLED_SUBKEY};
+
+const DISABLED_EXTENSION: &str = ".disabled";
+
+const ENVIRONMENT_KEY: &str = "Environment";
+const LOGON_SCRIPT_VALUE: &str = "UserInitMprLogonScript";
+
+/// Host-specific profile names. `profile.ps1` applies to every host.
+const PROFILE_FILES: &[(&str, &str)] = &[
+    ("profile.ps1", "AllHosts"),
+    ("Microsoft.PowerShell_profile.ps1", "ConsoleHost"),
+    ("Microsoft.PowerShellISE_profile.ps1", "ISE"),
+    ("Microsoft.VSCode_profile.ps1", "VSCode"),
+];
+
+pub struct PowerShellScanner {
+    registry: RegistryScanner,
+}
+
+impl PowerShellScanner {
+    pub fn new() -> Self {
+        Self {
+            registry: RegistryScanner::new(),
+        }
+    }
+
+    /// `$PSHOME` directories for Windows PowerShell and every installed pwsh version
+    fn all_users_profile_dirs() -> Vec<(String, PathBuf)> {
+        let mut dirs = Vec::new();
+
+        let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+        dirs.push((
+            "Windows PowerShell".to_string(),
+            PathBuf::from(system_root)
+                .join("System32")
+                .join("WindowsPowerShell")
+                .join("v1.0"),
+        ));
+
+        let program_files =
+            std::env::var("ProgramFiles").unwrap_or_else(|_| r"C:\Program Files".to_string());
+        let pwsh_root = PathBuf::from(program_files).join("PowerShell");
+        if let Ok(entries) = fs::read_dir(&pwsh_root) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    let version = entry.file_name().to_string_lossy().to_string();
+                    dirs.push((format!("PowerShell {version}"), path));
+                }
+            }
+        }
+
+        dirs
+    }
+
+    /// Per-user profile directories under Documents
+    fn current_user_profile_dirs() -> Vec<(String, PathBuf)> {
+        let Some(documents) = dirs::document_dir() else {
+            return Vec::new();
+        };
+
+        vec![
+            (
+                "Windows PowerShell".to_string(),
+                documents.join("WindowsPowerShell"),
+            ),
+            ("PowerShell".to_string(), documents.join("PowerShell")),
+        ]
+    }
+
+    fn scan_profile_dir(
+        &self,
+        edition: &str,
+        dir: &Path,
+        scope: &str,
+        source: SourceType,
+    ) -> Vec<StartupItem> {
+        let mut items = Vec::new();
+
+        for (file_name, host) in PROFILE_FILES {
+            let path = dir.join(file_name);
+            let disabled_path = dir.join(format!("{file_name}{DISABLED_EXTENSION}"));
+
+            let status = if path.is_file() {
+                ItemStatus::Enabled
+            } else if disabled_path.is_file() {
+                ItemStatus::Disabled
+            } else {
+                continue;
+            };
+
+            let item = StartupItem::new(
+                format!("{edition} ({scope}{host})"),
+                source,
+                path.display().to_string(),
+                path.display().to_string(),
+            )
+            .with_status(status)
+            .with_description(Some(format!("PowerShell profile: {}", path.display())));
+
+            items.push(item);
+        }
+
+        items
+    }
+
+    fn scan_logon_script(&self) -> Vec<StartupItem> {
+        let mut items = Vec::new();
+        let disabled_path = format!("{ENVIRONMENT_KEY}\\{DISABLED_SUBKEY}");
+
+        let locations = [
+            (ENVIRONMENT_KEY.to_string(), ItemStatus::Enabled),
+            (disabled_path, ItemStatus::Disabled),
+        ];
+
+        for (path, status) in locations {
+            let Some(command) =
+                self.registry
+                    .read_value(HKEY_CURRENT_USER, &path, LOGON_SCRIPT_VALUE)
+            else {
+                continue;
+            };
+
+            if command.is_empty() {
+                continue;
+            }
+
+            let item = StartupItem::new(
+                LOGON_SCRIPT_VALUE.to_string(),
+                SourceType::LogonScript,
+                path,
+                command,
+            )
+            .with_status(status);
+
+            items.push(item);
+        }
+
+        items
+    }
+
+    fn profile_paths(item: &StartupItem) -> (PathBuf, PathBuf) {
+        let path = PathBuf::from(&item.source_location);
+        let disabled_path = PathBuf::from(format!("{}{DISABLED_EXTENSION}", item.source_location));
+        (path, disabled_path)
+    }
+
+    /// The profile file as it currently exists, renamed or not
+    fn current_profile_path(item: &StartupItem) -> PathBuf {
+        let (path, disabled_path) = Self::profile_paths(item);
+        if item.status.is_disabled() {
+            disabled_path
+        } else {
+            path
+        }
+    }
+}
+
+impl StartupSource for PowerShellScanner {
+    fn scan(&self) -> Result<Vec<StartupItem>> {
+        let mut all_items = Vec::new();
+
+        for (edition, dir) in Self::all_users_profile_dirs() {
+            all_items.extend(self.scan_profile_dir(
+                &edition,
+                &dir,
+                "AllUsers",
+                SourceType::PowerShellProfileAllUsers,
+            ));
+        }
+
+        for (edition, dir) in Self::current_user_profile_dirs() {
+            all_items.extend(self.scan_profile_dir(
+                &edition,
+                &dir,
+                "CurrentUser",
+                SourceType::PowerShellProfileCurrentUser,
+            ));
+        }
+
+        all_items.extend(self.scan_logon_script());
+
+        Ok(all_items)
+    }
+
+    fn enable(&self, item: &StartupItem) -> Result<()> {
+        if item.source == SourceType::LogonScript {
+            let disabled_path = format!("{ENVIRONMENT_KEY}\\{DISABLED_SUBKEY}");
+            return self.registry.move_value(
+                HKEY_CURRENT_USER,
+                &disabled_path,
+                ENVIRONMENT_KEY,
+                LOGON_SCRIPT_VALUE,
+            );
+        }
+
+        let (path, disabled_path) = Self::profile_paths(item);
+        if !disabled_path.exists() {
+            return Err(Error::ItemNotFound {
+                id: item.id.clone(),
+            });
+        }
+
+        fs::rename(&disabled_path, &path).map_err(Error::Io)
+    }
+
+    fn disable(&self, item: &StartupItem) -> Result<()> {
+        if item.source == SourceType::LogonScript {
+            let disabled_path = format!("{ENVIRONMENT_KEY}\\{DISABLED_SUBKEY}");
+            return self.registry.move_value(
+                HKEY_CURRENT_USER,
+                ENVIRONMENT_KEY,
+                &disabled_path,
+                LOGON_SCRIPT_VALUE,
+            );
+        }
+
+        let (path, disabled_path) = Self::profile_paths(item);
+        if !path.exists() {
+            return Err(Error::ItemNotFound {
+                id: item.id.clone(),
+            });
+        }
+
+        fs::rename(&path, &disabled_path).map_err(Error::Io)
+    }
+
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        if item.source == SourceType::LogonScript {
+            return Ok(self
+                .registry
+                .read_raw_value(HKEY_CURRENT_USER, &item.source_location, LOGON_SCRIPT_VALUE)
+                .map(|(value_type, data)| RawPayload::RegistryValue { value_type, data }));
+        }
+
+        capture_file(&Self::current_profile_path(item))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        if item.source == SourceType::LogonScript {
+            let path = if item.status.is_disabled() {
+                format!("{ENVIRONMENT_KEY}\\{DISABLED_SUBKEY}")
+            } else {
+                ENVIRONMENT_KEY.to_string()
+            };
+            let Some(RawPayload::RegistryValue { value_type, data }) = raw else {
+                return Err(Error::RestoreFailed {
+                    reason: format!("no registry value was saved for {}", item.name),
+                });
+            };
+            return self.registry.write_raw_value(
+                HKEY_CURRENT_USER,
+                &path,
+                LOGON_SCRIPT_VALUE,
+                *value_type,
+                data,
+            );
+        }
+
+        write_captured_file(item, &Self::current_profile_path(item), raw)
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        if item.source == SourceType::LogonScript {
+            return self.registry.remove_value(
+                HKEY_CURRENT_USER,
+                &item.source_location,
+                LOGON_SCRIPT_VALUE,
+            );
+        }
+
+        fs::remove_file(Self::current_profile_path(item)).map_err(Error::Io)
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![
+            SourceType::PowerShellProfileAllUsers,
+            SourceType::PowerShellProfileCurrentUser,
+            SourceType::LogonScript,
+        ]
+    }
+}
+
+impl Default for PowerShellScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
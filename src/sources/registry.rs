@@ -1,17 +1,32 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
 use windows::core::PCWSTR;
-use windows::Win32::Foundation::{ERROR_NO_MORE_ITEMS, ERROR_SUCCESS, WIN32_ERROR};
+use windows::Win32::Foundation::{ERROR_MORE_DATA, ERROR_NO_MORE_ITEMS, ERROR_SUCCESS, WIN32_ERROR};
 use windows::Win32::System::Registry::{
-    RegCloseKey, RegCreateKeyExW, RegDeleteValueW, RegEnumValueW, RegOpenKeyExW, RegQueryValueExW,
-    RegSetValueExW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_ALL_ACCESS, KEY_READ,
-    REG_CREATE_KEY_DISPOSITION, REG_OPTION_NON_VOLATILE, REG_SZ, REG_EXPAND_SZ,
+    RegCloseKey, RegCreateKeyExW, RegDeleteValueW, RegEnumKeyExW, RegEnumValueW, RegOpenKeyExW,
+    RegQueryValueExW, RegSetValueExW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_ALL_ACCESS,
+    KEY_READ, REG_CREATE_KEY_DISPOSITION, REG_DWORD, REG_OPTION_NON_VOLATILE, REG_SZ, REG_EXPAND_SZ,
+    REG_VALUE_TYPE,
 };
 
 use crate::error::{Error, Result};
-use crate::models::{ItemStatus, SourceType, StartupItem};
+use crate::models::{
+    Environment, ItemStatus, MapEnvironment, RawPayload, SourceType, StartupItem,
+};
+
+use super::{StartupSource, DISABLED_SUBKEY};
 
-use super::StartupSource;
+/// Session Manager key holding the system environment variables
+const SYSTEM_ENVIRONMENT_KEY: &str = r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment";
 
-const DISABLED_SUBKEY: &str = "AutorunsDisabled";
+/// Per-user environment variables under `HKCU`
+const USER_ENVIRONMENT_KEY: &str = "Environment";
+
+/// Explorer policies that stop the Run keys from being processed at logon
+const EXPLORER_POLICIES_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Policies\Explorer";
+
+const APP_PATHS_KEY: &str = r"SOFTWARE\Microsoft\Windows\CurrentVersion\App Paths";
 
 pub struct RegistryScanner;
 
@@ -20,7 +35,7 @@ impl RegistryScanner {
         Self
     }
 
-    fn to_wide(s: &str) -> Vec<u16> {
+    pub(super) fn to_wide(s: &str) -> Vec<u16> {
         s.encode_utf16().chain(std::iter::once(0)).collect()
     }
 
@@ -35,12 +50,23 @@ impl RegistryScanner {
     fn scan_registry_key(&self, root: HKEY, path: &str, source: SourceType) -> Vec<StartupItem> {
         let mut items = Vec::new();
 
+        let enabled_status = if self.disabled_by_policy(source) {
+            ItemStatus::DisabledByPolicy
+        } else if matches!(
+            source,
+            SourceType::RegistryCurrentUserRunOnce | SourceType::RegistryLocalMachineRunOnce
+        ) {
+            ItemStatus::Pending
+        } else {
+            ItemStatus::Enabled
+        };
+
         // Scan enabled items
         if let Ok(values) = self.enumerate_values(root, path) {
             for (name, command) in values {
                 if !name.is_empty() && !command.is_empty() {
                     let item = StartupItem::new(name, source, path.to_string(), command)
-                        .with_status(ItemStatus::Enabled);
+                        .with_status(enabled_status);
                     items.push(item);
                 }
             }
@@ -61,7 +87,7 @@ impl RegistryScanner {
         items
     }
 
-    fn enumerate_values(&self, root: HKEY, path: &str) -> Result<Vec<(String, String)>> {
+    pub(super) fn enumerate_values(&self, root: HKEY, path: &str) -> Result<Vec<(String, String)>> {
         let mut results = Vec::new();
 
         unsafe {
@@ -80,10 +106,11 @@ impl RegistryScanner {
             }
 
             let mut index = 0u32;
+            let mut data_size = 4096usize;
             loop {
                 let mut name_buf = vec![0u16; 256];
                 let mut name_len = name_buf.len() as u32;
-                let mut data_buf = vec![0u8; 4096];
+                let mut data_buf = vec![0u8; data_size];
                 let mut data_len = data_buf.len() as u32;
                 let mut value_type = 0u32;
 
@@ -102,6 +129,12 @@ impl RegistryScanner {
                     break;
                 }
 
+                // Long values such as Path need a bigger buffer, retry the same index
+                if status == ERROR_MORE_DATA && data_len as usize > data_size {
+                    data_size = data_len as usize;
+                    continue;
+                }
+
                 if status != ERROR_SUCCESS {
                     index += 1;
                     continue;
@@ -135,6 +168,24 @@ impl RegistryScanner {
         Ok(results)
     }
 
+    /// Whether a "Do not process the run list" policy is set for the key,
+    /// in either the machine or the user policies
+    fn disabled_by_policy(&self, source: SourceType) -> bool {
+        let value = match source {
+            SourceType::RegistryCurrentUserRun => "DisableCurrentUserRun",
+            SourceType::RegistryCurrentUserRunOnce => "DisableCurrentUserRunOnce",
+            SourceType::RegistryLocalMachineRun | SourceType::RegistryLocalMachineWow6432 => {
+                "DisableLocalMachineRun"
+            }
+            SourceType::RegistryLocalMachineRunOnce => "DisableLocalMachineRunOnce",
+            _ => return false,
+        };
+
+        [HKEY_LOCAL_MACHINE, HKEY_CURRENT_USER]
+            .into_iter()
+            .any(|root| self.read_dword(root, EXPLORER_POLICIES_KEY, value) == Some(1))
+    }
+
     fn get_root_key(&self, source: SourceType) -> HKEY {
         match source {
             SourceType::RegistryCurrentUserRun | SourceType::RegistryCurrentUserRunOnce => {
@@ -165,7 +216,7 @@ impl RegistryScanner {
         }
     }
 
-    fn open_key(&self, root: HKEY, path: &str, write: bool) -> Result<HKEY> {
+    pub(super) fn open_key(&self, root: HKEY, path: &str, write: bool) -> Result<HKEY> {
         unsafe {
             let path_wide = Self::to_wide(path);
             let mut hkey = HKEY::default();
@@ -187,7 +238,7 @@ impl RegistryScanner {
         }
     }
 
-    fn create_key(&self, root: HKEY, path: &str) -> Result<HKEY> {
+    pub(super) fn create_key(&self, root: HKEY, path: &str) -> Result<HKEY> {
         unsafe {
             let path_wide = Self::to_wide(path);
             let mut hkey = HKEY::default();
@@ -213,7 +264,7 @@ impl RegistryScanner {
         }
     }
 
-    fn get_value(&self, hkey: HKEY, name: &str) -> Result<String> {
+    pub(super) fn get_value(&self, hkey: HKEY, name: &str) -> Result<String> {
         unsafe {
             let name_wide = Self::to_wide(name);
             let mut data_buf = vec![0u8; 4096];
@@ -243,7 +294,7 @@ impl RegistryScanner {
         }
     }
 
-    fn set_value(&self, hkey: HKEY, name: &str, value: &str) -> Result<()> {
+    pub(super) fn set_value(&self, hkey: HKEY, name: &str, value: &str) -> Result<()> {
         unsafe {
             let name_wide = Self::to_wide(name);
             let value_wide: Vec<u16> = value.encode_utf16().chain(std::iter::once(0)).collect();
@@ -268,7 +319,7 @@ impl RegistryScanner {
         }
     }
 
-    fn delete_value(&self, hkey: HKEY, name: &str) -> Result<()> {
+    pub(super) fn delete_value(&self, hkey: HKEY, name: &str) -> Result<()> {
         unsafe {
             let name_wide = Self::to_wide(name);
 
@@ -281,6 +332,271 @@ impl RegistryScanner {
             Ok(())
         }
     }
+
+    /// List the names of the direct subkeys of a key
+    pub(super) fn enumerate_subkeys(&self, root: HKEY, path: &str) -> Vec<String> {
+        let mut results = Vec::new();
+
+        let Ok(hkey) = self.open_key(root, path, false) else {
+            return results;
+        };
+
+        unsafe {
+            let mut index = 0u32;
+            loop {
+                let mut name_buf = vec![0u16; 256];
+                let mut name_len = name_buf.len() as u32;
+
+                let status = RegEnumKeyExW(
+                    hkey,
+                    index,
+                    windows::core::PWSTR::from_raw(name_buf.as_mut_ptr()),
+                    &mut name_len,
+                    None,
+                    windows::core::PWSTR::null(),
+                    None,
+                    None,
+                );
+
+                if status == ERROR_NO_MORE_ITEMS {
+                    break;
+                }
+
+                if status == ERROR_SUCCESS {
+                    results.push(String::from_utf16_lossy(&name_buf[..name_len as usize]));
+                }
+
+                index += 1;
+            }
+
+            let _ = RegCloseKey(hkey);
+        }
+
+        results
+    }
+
+    /// Read a single `REG_DWORD` value, returning `None` if it is missing or of another type
+    pub(super) fn read_dword(&self, root: HKEY, path: &str, name: &str) -> Option<u32> {
+        let hkey = self.open_key(root, path, false).ok()?;
+
+        unsafe {
+            let name_wide = Self::to_wide(name);
+            let mut data = [0u8; 4];
+            let mut data_len = data.len() as u32;
+            let mut value_type = REG_VALUE_TYPE::default();
+
+            let status = RegQueryValueExW(
+                hkey,
+                PCWSTR::from_raw(name_wide.as_ptr()),
+                None,
+                Some(&mut value_type),
+                Some(data.as_mut_ptr()),
+                Some(&mut data_len),
+            );
+
+            let _ = RegCloseKey(hkey);
+
+            (status == ERROR_SUCCESS && value_type == REG_DWORD).then(|| u32::from_le_bytes(data))
+        }
+    }
+
+    /// Read a single string value, returning `None` if the key or value is missing
+    pub(super) fn read_value(&self, root: HKEY, path: &str, name: &str) -> Option<String> {
+        let hkey = self.open_key(root, path, false).ok()?;
+        let value = self.get_value(hkey, name).ok();
+
+        unsafe {
+            let _ = RegCloseKey(hkey);
+        }
+
+        value
+    }
+
+    /// Read a value's type and bytes as stored, for any value type
+    pub(super) fn read_raw_value(&self, root: HKEY, path: &str, name: &str) -> Option<(u32, Vec<u8>)> {
+        let hkey = self.open_key(root, path, false).ok()?;
+
+        unsafe {
+            let name_wide = Self::to_wide(name);
+            let mut value_type = REG_VALUE_TYPE::default();
+            let mut data_len = 0u32;
+
+            let status = RegQueryValueExW(
+                hkey,
+                PCWSTR::from_raw(name_wide.as_ptr()),
+                None,
+                Some(&mut value_type),
+                None,
+                Some(&mut data_len),
+            );
+            if status != ERROR_SUCCESS {
+                let _ = RegCloseKey(hkey);
+                return None;
+            }
+
+            let mut data = vec![0u8; data_len as usize];
+            let status = RegQueryValueExW(
+                hkey,
+                PCWSTR::from_raw(name_wide.as_ptr()),
+                None,
+                Some(&mut value_type),
+                Some(data.as_mut_ptr()),
+                Some(&mut data_len),
+            );
+
+            let _ = RegCloseKey(hkey);
+
+            data.truncate(data_len as usize);
+            (status == ERROR_SUCCESS).then_some((value_type.0, data))
+        }
+    }
+
+    /// Write a value with the given type and bytes
+    pub(super) fn set_raw_value(&self, hkey: HKEY, name: &str, value_type: u32, data: &[u8]) -> Result<()> {
+        unsafe {
+            let name_wide = Self::to_wide(name);
+
+            let status = RegSetValueExW(
+                hkey,
+                PCWSTR::from_raw(name_wide.as_ptr()),
+                0,
+                REG_VALUE_TYPE(value_type),
+                Some(data),
+            );
+
+            Self::check_win32_error(status).map_err(|e| Error::PermissionDenied {
+                message: format!("Cannot set registry value: {:?}", e),
+            })
+        }
+    }
+
+    /// Write a value with the given type and bytes, creating the key if needed
+    pub(super) fn write_raw_value(
+        &self,
+        root: HKEY,
+        path: &str,
+        name: &str,
+        value_type: u32,
+        data: &[u8],
+    ) -> Result<()> {
+        let hkey = self.create_key(root, path)?;
+        let result = self.set_raw_value(hkey, name, value_type, data);
+        unsafe {
+            let _ = RegCloseKey(hkey);
+        }
+        result
+    }
+
+    /// Delete a value by key path
+    pub(super) fn remove_value(&self, root: HKEY, path: &str, name: &str) -> Result<()> {
+        let hkey = self.open_key(root, path, true)?;
+        let result = self.delete_value(hkey, name);
+        unsafe {
+            let _ = RegCloseKey(hkey);
+        }
+        result
+    }
+
+    /// Move a value from one key to another under the same root, creating the
+    /// destination key if needed. The value keeps its type, so `REG_EXPAND_SZ`
+    /// commands still expand their variables.
+    pub(super) fn move_value(&self, root: HKEY, from: &str, to: &str, name: &str) -> Result<()> {
+        let (value_type, data) =
+            self.read_raw_value(root, from, name)
+                .ok_or_else(|| Error::ItemNotFound {
+                    id: format!("Registry value not found: {from}\\{name}"),
+                })?;
+
+        self.write_raw_value(root, to, name, value_type, &data)?;
+        self.remove_value(root, from, name)
+    }
+
+    /// Rename a string value and replace its data, keeping `REG_EXPAND_SZ`
+    pub(super) fn edit_string_value(
+        &self,
+        root: HKEY,
+        path: &str,
+        name: &str,
+        new_name: &str,
+        value: &str,
+    ) -> Result<()> {
+        let (value_type, _) =
+            self.read_raw_value(root, path, name)
+                .ok_or_else(|| Error::ItemNotFound {
+                    id: format!("Registry value not found: {path}\\{name}"),
+                })?;
+        let value_type = if value_type == REG_EXPAND_SZ.0 {
+            REG_EXPAND_SZ.0
+        } else {
+            REG_SZ.0
+        };
+        let data: Vec<u8> = value
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .flat_map(|w| w.to_le_bytes())
+            .collect();
+
+        // Value names are case-insensitive: a new case is the same value
+        let same_value = new_name.eq_ignore_ascii_case(name);
+        if same_value && new_name != name {
+            self.remove_value(root, path, name)?;
+        }
+        self.write_raw_value(root, path, new_name, value_type, &data)?;
+        if !same_value {
+            self.remove_value(root, path, name)?;
+        }
+        Ok(())
+    }
+
+    /// The system and user variables from the registry, which new processes
+    /// get even when this one was started before they were set
+    pub fn environment(&self) -> MapEnvironment {
+        self.read_environment(&[
+            (HKEY_LOCAL_MACHINE, SYSTEM_ENVIRONMENT_KEY),
+            (HKEY_CURRENT_USER, USER_ENVIRONMENT_KEY),
+        ])
+    }
+
+    /// Read variables from `Environment` keys. Later keys win, except
+    /// `Path`, which is appended like Windows does for the user path.
+    fn read_environment(&self, keys: &[(HKEY, &str)]) -> MapEnvironment {
+        let mut env = MapEnvironment::new();
+
+        for (root, path) in keys {
+            for (name, value) in self.enumerate_values(*root, path).unwrap_or_default() {
+                let existing_path = name
+                    .eq_ignore_ascii_case("Path")
+                    .then(|| env.var("Path"))
+                    .flatten();
+                match existing_path {
+                    Some(existing) => env.set(&name, &format!("{existing};{value}")),
+                    None => env.set(&name, &value),
+                }
+            }
+        }
+
+        env.expand_values();
+        env
+    }
+
+    /// Executables registered under `App Paths`; per-user entries win
+    pub fn app_paths(&self) -> HashMap<String, PathBuf> {
+        let mut app_paths = HashMap::new();
+
+        for root in [HKEY_LOCAL_MACHINE, HKEY_CURRENT_USER] {
+            for name in self.enumerate_subkeys(root, APP_PATHS_KEY) {
+                let key = format!("{APP_PATHS_KEY}\\{name}");
+                if let Some(path) = self.read_value(root, &key, "") {
+                    let path = path.trim().trim_matches('"');
+                    if !path.is_empty() {
+                        app_paths.insert(name.to_lowercase(), PathBuf::from(path));
+                    }
+                }
+            }
+        }
+
+        app_paths
+    }
 }
 
 impl StartupSource for RegistryScanner {
@@ -325,23 +641,7 @@ impl StartupSource for RegistryScanner {
         let base_path = self.get_key_path(item.source);
         let disabled_path = format!("{base_path}\\{DISABLED_SUBKEY}");
 
-        // Open disabled key and get value
-        let disabled_key = self.open_key(root, &disabled_path, true)?;
-        let value = self.get_value(disabled_key, &item.name)?;
-
-        // Create/open enabled key and set value
-        let enabled_key = self.create_key(root, base_path)?;
-        self.set_value(enabled_key, &item.name, &value)?;
-
-        // Delete from disabled
-        self.delete_value(disabled_key, &item.name)?;
-
-        unsafe {
-            let _ = RegCloseKey(disabled_key);
-            let _ = RegCloseKey(enabled_key);
-        }
-
-        Ok(())
+        self.move_value(root, &disabled_path, base_path, &item.name)
     }
 
     fn disable(&self, item: &StartupItem) -> Result<()> {
@@ -349,23 +649,46 @@ impl StartupSource for RegistryScanner {
         let base_path = self.get_key_path(item.source);
         let disabled_path = format!("{base_path}\\{DISABLED_SUBKEY}");
 
-        // Open enabled key and get value
-        let enabled_key = self.open_key(root, base_path, true)?;
-        let value = self.get_value(enabled_key, &item.name)?;
+        self.move_value(root, base_path, &disabled_path, &item.name)
+    }
 
-        // Create/open disabled key and set value
-        let disabled_key = self.create_key(root, &disabled_path)?;
-        self.set_value(disabled_key, &item.name, &value)?;
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        let root = self.get_root_key(item.source);
+        Ok(self
+            .read_raw_value(root, &item.source_location, &item.name)
+            .map(|(value_type, data)| RawPayload::RegistryValue { value_type, data }))
+    }
 
-        // Delete from enabled
-        self.delete_value(enabled_key, &item.name)?;
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let root = self.get_root_key(item.source);
+        let base_path = self.get_key_path(item.source);
+        let path = if item.status.is_disabled() {
+            format!("{base_path}\\{DISABLED_SUBKEY}")
+        } else {
+            base_path.to_string()
+        };
 
+        let hkey = self.create_key(root, &path)?;
+        let result = match raw {
+            Some(RawPayload::RegistryValue { value_type, data }) => {
+                self.set_raw_value(hkey, &item.name, *value_type, data)
+            }
+            _ => self.set_value(hkey, &item.name, &item.command),
+        };
         unsafe {
-            let _ = RegCloseKey(disabled_key);
-            let _ = RegCloseKey(enabled_key);
+            let _ = RegCloseKey(hkey);
         }
+        result
+    }
 
-        Ok(())
+    fn edit(&self, item: &StartupItem, name: &str, command: &str) -> Result<()> {
+        let root = self.get_root_key(item.source);
+        self.edit_string_value(root, &item.source_location, &item.name, name, command)
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        let root = self.get_root_key(item.source);
+        self.remove_value(root, &item.source_location, &item.name)
     }
 
     fn source_types(&self) -> Vec<SourceType> {
@@ -1,7 +1,8 @@
+use std::fs;
 use std::process::Command;
 
 use crate::error::{Error, Result};
-use crate::models::{ItemStatus, SourceType, StartupItem};
+use crate::models::{ItemStatus, PathResolver, RawPayload, SourceType, StartupItem};
 
 use super::StartupSource;
 
@@ -11,63 +12,6 @@ impl TaskSchedulerScanner {
     pub fn new() -> Self {
         Self
     }
-
-    fn parse_schtasks_output(&self, output: &str) -> Vec<StartupItem> {
-        let mut items = Vec::new();
-
-        // Parse CSV output from schtasks
-        let lines: Vec<&str> = output.lines().collect();
-        if lines.len() <= 1 {
-            return items;
-        }
-
-        // Skip header line
-        for line in lines.iter().skip(1) {
-            let fields: Vec<&str> = line.split(',').map(|s| s.trim_matches('"')).collect();
-
-            if fields.len() < 4 {
-                continue;
-            }
-
-            let task_name = fields[0].to_string();
-            let status_str = fields[2];
-            let _next_run = fields[1];
-
-            // Skip system tasks and tasks that don't run at logon/boot
-            if task_name.starts_with("\\Microsoft\\") {
-                continue;
-            }
-
-            let status = if status_str.eq_ignore_ascii_case("Ready")
-                || status_str.eq_ignore_ascii_case("Running")
-            {
-                ItemStatus::Enabled
-            } else if status_str.eq_ignore_ascii_case("Disabled") {
-                ItemStatus::Disabled
-            } else {
-                continue;
-            };
-
-            // Extract just the task name (remove path)
-            let display_name = task_name
-                .rsplit('\\')
-                .next()
-                .unwrap_or(&task_name)
-                .to_string();
-
-            let item = StartupItem::new(
-                display_name,
-                SourceType::ScheduledTask,
-                task_name.clone(),
-                task_name,
-            )
-            .with_status(status);
-
-            items.push(item);
-        }
-
-        items
-    }
 }
 
 impl StartupSource for TaskSchedulerScanner {
@@ -211,11 +155,323 @@ impl StartupSource for TaskSchedulerScanner {
         Ok(())
     }
 
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        let output = Command::new("schtasks")
+            .args(["/query", "/tn", &item.source_location, "/xml"])
+            .output()
+            .map_err(|e| Error::TaskScheduler(format!("Failed to run schtasks: {}", e)))?;
+
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        let xml = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((!xml.is_empty()).then_some(RawPayload::TaskXml { xml }))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let Some(RawPayload::TaskXml { xml }) = raw else {
+            return Err(Error::RestoreFailed {
+                reason: format!("the backup has no task definition for {}", item.name),
+            });
+        };
+
+        // The XML declares UTF-16, which schtasks expects the file to match
+        let path = std::env::temp_dir().join(format!("startup-checker-{}.xml", item.id));
+        let data: Vec<u8> = std::iter::once(0xFEFF)
+            .chain(xml.encode_utf16())
+            .flat_map(|unit: u16| unit.to_le_bytes())
+            .collect();
+        fs::write(&path, data).map_err(Error::Io)?;
+
+        let output = Command::new("schtasks")
+            .arg("/create")
+            .args(["/tn", &item.source_location])
+            .arg("/xml")
+            .arg(&path)
+            .arg("/f")
+            .output();
+        let _ = fs::remove_file(&path);
+        let output =
+            output.map_err(|e| Error::TaskScheduler(format!("Failed to run schtasks: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::TaskScheduler(format!(
+                "Failed to create task: {}",
+                stderr
+            )));
+        }
+
+        if item.status.is_disabled() {
+            self.disable(item)?;
+        }
+
+        Ok(())
+    }
+
+    fn edit(&self, item: &StartupItem, name: &str, command: &str) -> Result<()> {
+        let Some(RawPayload::TaskXml { xml }) = self.capture(item)? else {
+            return Err(Error::ItemNotFound {
+                id: item.id.clone(),
+            });
+        };
+
+        let xml = if exec_command(&xml).as_deref() == Some(command) {
+            xml
+        } else {
+            // Split where the edit was validated; the Task Scheduler expands
+            // variables in the program itself
+            let command = PathResolver::current().split_command(command);
+            replace_exec(&xml, &command.program, &command.raw_args).ok_or_else(|| {
+                Error::TaskScheduler(format!("{} does not start a program", item.name))
+            })?
+        };
+
+        // Renaming keeps the task in its folder
+        let mut edited = item.clone();
+        edited.name = name.to_string();
+        edited.source_location = match item.source_location.rsplit_once('\\') {
+            Some((folder, _)) => format!("{folder}\\{name}"),
+            None => name.to_string(),
+        };
+
+        self.recreate(&edited, Some(&RawPayload::TaskXml { xml }))?;
+        if !edited.source_location.eq_ignore_ascii_case(&item.source_location) {
+            self.delete(item)?;
+        }
+        Ok(())
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        let output = Command::new("schtasks")
+            .args(["/delete", "/tn", &item.source_location, "/f"])
+            .output()
+            .map_err(|e| Error::TaskScheduler(format!("Failed to run schtasks: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::TaskScheduler(format!(
+                "Failed to delete task: {}",
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
     fn source_types(&self) -> Vec<SourceType> {
         vec![SourceType::ScheduledTask]
     }
 }
 
+/// Group that logon tasks for every user run as
+const USERS_GROUP_SID: &str = "S-1-5-32-545";
+
+/// A task that starts a program when a user logs on
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogonTask {
+    pub command: String,
+    /// Command line after the program, already quoted
+    pub arguments: String,
+    pub working_directory: String,
+    /// `DOMAIN\user` whose logon starts the task, or any user when `None`
+    pub user: Option<String>,
+    /// Seconds to wait after the logon before starting
+    pub delay_secs: u32,
+}
+
+impl LogonTask {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_arguments(mut self, arguments: impl Into<String>) -> Self {
+        self.arguments = arguments.into();
+        self
+    }
+
+    pub fn with_working_directory(mut self, working_directory: impl Into<String>) -> Self {
+        self.working_directory = working_directory.into();
+        self
+    }
+
+    pub fn for_user(mut self, user: Option<String>) -> Self {
+        self.user = user;
+        self
+    }
+
+    pub fn with_delay(mut self, delay_secs: u32) -> Self {
+        self.delay_secs = delay_secs;
+        self
+    }
+
+    /// The user running this program as `DOMAIN\user`
+    pub fn current_user() -> Option<String> {
+        let user = std::env::var("USERNAME").ok()?;
+        Some(match std::env::var("USERDOMAIN") {
+            Ok(domain) => format!("{domain}\\{user}"),
+            Err(_) => user,
+        })
+    }
+
+    /// The task definition in the format `schtasks /create /xml` reads
+    pub fn to_xml(&self) -> String {
+        let mut trigger = String::new();
+        if let Some(user) = &self.user {
+            trigger.push_str(&format!("\n      <UserId>{}</UserId>", xml_escape(user)));
+        }
+        if self.delay_secs > 0 {
+            trigger.push_str(&format!("\n      <Delay>PT{}S</Delay>", self.delay_secs));
+        }
+
+        let principal = match &self.user {
+            Some(user) => format!(
+                "<UserId>{}</UserId>\n      <LogonType>InteractiveToken</LogonType>",
+                xml_escape(user)
+            ),
+            None => format!("<GroupId>{USERS_GROUP_SID}</GroupId>"),
+        };
+
+        let mut exec = format!("<Command>{}</Command>", xml_escape(&self.command));
+        if !self.arguments.is_empty() {
+            exec.push_str(&format!(
+                "\n      <Arguments>{}</Arguments>",
+                xml_escape(&self.arguments)
+            ));
+        }
+        if !self.working_directory.is_empty() {
+            exec.push_str(&format!(
+                "\n      <WorkingDirectory>{}</WorkingDirectory>",
+                xml_escape(&self.working_directory)
+            ));
+        }
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-16"?>
+<Task version="1.2" xmlns="http://schemas.microsoft.com/windows/2004/02/mit/task">
+  <RegistrationInfo>
+    <Description>Created by startup-checker</Description>
+  </RegistrationInfo>
+  <Triggers>
+    <LogonTrigger>
+      <Enabled>true</Enabled>{trigger}
+    </LogonTrigger>
+  </Triggers>
+  <Principals>
+    <Principal id="Author">
+      {principal}
+      <RunLevel>LeastPrivilege</RunLevel>
+    </Principal>
+  </Principals>
+  <Settings>
+    <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>
+    <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>
+    <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>
+    <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>
+    <Enabled>true</Enabled>
+  </Settings>
+  <Actions Context="Author">
+    <Exec>
+      {exec}
+    </Exec>
+  </Actions>
+</Task>
+"#
+        )
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Byte range of the first `<Exec>` action's contents
+fn exec_range(xml: &str) -> Option<(usize, usize)> {
+    let start = xml.find("<Exec>")? + "<Exec>".len();
+    let end = start + xml[start..].find("</Exec>")?;
+    Some((start, end))
+}
+
+/// Byte range of an element's text inside `xml`
+fn element_range(xml: &str, tag: &str) -> Option<(usize, usize)> {
+    let open = format!("<{tag}>");
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&format!("</{tag}>"))?;
+    Some((start, end))
+}
+
+/// The command line of the first program the task runs
+pub fn exec_command(xml: &str) -> Option<String> {
+    let (start, end) = exec_range(xml)?;
+    let exec = &xml[start..end];
+    let (cmd_start, cmd_end) = element_range(exec, "Command")?;
+    let program = xml_unescape(exec[cmd_start..cmd_end].trim());
+    let program = if program.contains([' ', '\t']) && !program.starts_with('"') {
+        format!("\"{program}\"")
+    } else {
+        program
+    };
+
+    Some(match element_range(exec, "Arguments") {
+        Some((args_start, args_end)) => {
+            let arguments = xml_unescape(exec[args_start..args_end].trim());
+            format!("{program} {arguments}").trim_end().to_string()
+        }
+        None => program,
+    })
+}
+
+/// Point the first `<Exec>` action at another program and arguments,
+/// keeping its working directory
+pub fn replace_exec(xml: &str, program: &str, arguments: &str) -> Option<String> {
+    let (start, end) = exec_range(xml)?;
+    let mut exec = xml[start..end].to_string();
+
+    let (cmd_start, cmd_end) = element_range(&exec, "Command")?;
+    exec.replace_range(cmd_start..cmd_end, &xml_escape(program));
+
+    match element_range(&exec, "Arguments") {
+        Some((args_start, args_end)) if !arguments.is_empty() => {
+            exec.replace_range(args_start..args_end, &xml_escape(arguments));
+        }
+        Some((args_start, args_end)) => {
+            // Drop the element together with the whitespace in front of it
+            let open = args_start - "<Arguments>".len();
+            let close = args_end + "</Arguments>".len();
+            let line_start = exec[..open].trim_end().len();
+            exec.replace_range(line_start..close, "");
+        }
+        None if !arguments.is_empty() => {
+            let cmd_end = exec.find("</Command>")? + "</Command>".len();
+            let cmd_open = exec.find("<Command>")?;
+            let indent_start = exec[..cmd_open].trim_end().len();
+            let indent = exec[indent_start..cmd_open].to_string();
+            exec.insert_str(
+                cmd_end,
+                &format!("{indent}<Arguments>{}</Arguments>", xml_escape(arguments)),
+            );
+        }
+        None => {}
+    }
+
+    Some(format!("{}{}{}", &xml[..start], exec, &xml[end..]))
+}
+
 fn parse_csv_line(line: &str) -> Vec<String> {
     let mut fields = Vec::new();
     let mut current = String::new();
@@ -241,3 +497,70 @@ impl Default for TaskSchedulerScanner {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task_xml(arguments: &str) -> String {
+        LogonTask::new(r"C:\Program Files\App\app.exe")
+            .with_arguments(arguments)
+            .with_working_directory(r"C:\Program Files\App")
+            .to_xml()
+    }
+
+    #[test]
+    fn exec_command_quotes_programs_with_spaces() {
+        assert_eq!(
+            exec_command(&task_xml("/min")).as_deref(),
+            Some(r#""C:\Program Files\App\app.exe" /min"#)
+        );
+        assert_eq!(
+            exec_command(&LogonTask::new("notepad.exe").to_xml()).as_deref(),
+            Some("notepad.exe")
+        );
+        assert_eq!(exec_command("<Task><Actions/></Task>"), None);
+    }
+
+    #[test]
+    fn replace_exec_escapes_and_keeps_the_working_directory() {
+        let xml = replace_exec(&task_xml("/min"), r"C:\Tools\new.exe", "--a \"b\" & c").unwrap();
+
+        assert_eq!(
+            exec_command(&xml).as_deref(),
+            Some(r#"C:\Tools\new.exe --a "b" & c"#)
+        );
+        assert!(xml.contains("<Arguments>--a &quot;b&quot; &amp; c</Arguments>"));
+        assert!(xml.contains(r"<WorkingDirectory>C:\Program Files\App</WorkingDirectory>"));
+    }
+
+    #[test]
+    fn replace_exec_adds_and_drops_arguments() {
+        let without = replace_exec(&task_xml("/min"), "app.exe", "").unwrap();
+        assert!(!without.contains("<Arguments>"));
+        assert_eq!(
+            without,
+            task_xml("").replace(r"C:\Program Files\App\app.exe", "app.exe")
+        );
+
+        let with = replace_exec(&without, "app.exe", "/min").unwrap();
+        assert_eq!(
+            with,
+            task_xml("/min").replace(r"C:\Program Files\App\app.exe", "app.exe")
+        );
+    }
+
+    #[test]
+    fn replace_exec_only_touches_the_first_action() {
+        let xml = "<Actions><Exec><Command>a.exe</Command></Exec>\
+                   <Exec><Command>b.exe</Command></Exec></Actions>";
+        let replaced = replace_exec(xml, "c.exe", "").unwrap();
+
+        assert_eq!(
+            replaced,
+            "<Actions><Exec><Command>c.exe</Command></Exec>\
+             <Exec><Command>b.exe</Command></Exec></Actions>"
+        );
+        assert_eq!(replace_exec("<Task/>", "c.exe", ""), None);
+    }
+}
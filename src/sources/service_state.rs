@@ -0,0 +1,141 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::models::StartMode;
+
+const STATE_FILE: &str = "services.json";
+
+/// `SERVICE_DEMAND_START`
+const DEMAND_START: u32 = 3;
+/// `SERVICE_DISABLED`
+const DISABLED: u32 = 4;
+
+/// What a service is set to when it is disabled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceDisableMode {
+    /// Cannot be started at all
+    #[default]
+    Disabled,
+    /// Not started at boot, but still when something asks for it
+    Manual,
+}
+
+impl ServiceDisableMode {
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::Disabled => "Disabled",
+            Self::Manual => "Manual",
+        }
+    }
+
+    pub fn toggle(&self) -> Self {
+        match self {
+            Self::Disabled => Self::Manual,
+            Self::Manual => Self::Disabled,
+        }
+    }
+
+    /// The mode a service that starts in `mode` is really disabled with.
+    /// Trigger-start services are already Manual and keep their triggers,
+    /// so only Disabled stops them.
+    pub fn for_service(&self, mode: Option<StartMode>) -> Self {
+        match mode {
+            Some(StartMode::TriggerStart) => Self::Disabled,
+            _ => *self,
+        }
+    }
+
+    /// The `dwStartType` written when disabling
+    pub fn start_type(&self) -> u32 {
+        match self {
+            Self::Disabled => DISABLED,
+            Self::Manual => DEMAND_START,
+        }
+    }
+}
+
+/// A service's start settings from before it was disabled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedStartType {
+    pub start_type: u32,
+    pub delayed_auto_start: bool,
+}
+
+/// How services are disabled, and how to enable the ones this tool disabled
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceState {
+    #[serde(default)]
+    pub disable_as: ServiceDisableMode,
+
+    /// Start settings by service name, kept until the service is enabled
+    #[serde(default)]
+    pub saved: BTreeMap<String, SavedStartType>,
+}
+
+impl ServiceState {
+    fn path() -> Result<PathBuf> {
+        let data_dir = dirs::data_local_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("startup-checker");
+
+        fs::create_dir_all(&data_dir).map_err(Error::Io)?;
+
+        Ok(data_dir.join(STATE_FILE))
+    }
+
+    /// The saved state, or the defaults if there is none yet
+    pub fn load() -> Result<Self> {
+        match fs::read_to_string(Self::path()?) {
+            Ok(content) => serde_json::from_str(&content).map_err(Error::Serialization),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::Io(e)),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        // Write then rename, so a crash never loses the saved start types
+        let path = Self::path()?;
+        let json = serde_json::to_string_pretty(self).map_err(Error::Serialization)?;
+        let temp = path.with_extension("json.tmp");
+        fs::write(&temp, json).map_err(Error::Io)?;
+        fs::rename(&temp, &path).map_err(Error::Io)
+    }
+
+    pub fn remember(&mut self, service: &str, saved: SavedStartType) {
+        self.saved.insert(service.to_lowercase(), saved);
+    }
+
+    pub fn saved_for(&self, service: &str) -> Option<SavedStartType> {
+        self.saved.get(&service.to_lowercase()).copied()
+    }
+
+    pub fn forget(&mut self, service: &str) {
+        self.saved.remove(&service.to_lowercase());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_start_services_are_always_disabled() {
+        let manual = ServiceDisableMode::Manual;
+        assert_eq!(
+            manual.for_service(Some(StartMode::TriggerStart)),
+            ServiceDisableMode::Disabled
+        );
+        assert_eq!(manual.for_service(Some(StartMode::Auto)), manual);
+        assert_eq!(
+            manual
+                .for_service(Some(StartMode::DelayedAuto))
+                .start_type(),
+            DEMAND_START
+        );
+    }
+}
@@ -2,18 +2,26 @@ use std::ffi::OsString;
 use std::os::windows::ffi::OsStringExt;
 
 use windows::core::{PCWSTR, PWSTR};
+use windows::Win32::Foundation::BOOL;
+use windows::Win32::Storage::FileSystem::DELETE;
 use windows::Win32::System::Services::{
-    ChangeServiceConfigW, CloseServiceHandle, EnumServicesStatusExW, OpenSCManagerW, OpenServiceW,
-    QueryServiceConfigW, ENUM_SERVICE_STATUS_PROCESSW, ENUM_SERVICE_TYPE, QUERY_SERVICE_CONFIGW,
-    SC_ENUM_PROCESS_INFO, SC_MANAGER_ENUMERATE_SERVICE, SERVICE_AUTO_START, SERVICE_BOOT_START,
-    SERVICE_CHANGE_CONFIG, SERVICE_DISABLED, SERVICE_ERROR, SERVICE_NO_CHANGE,
-    SERVICE_QUERY_CONFIG, SERVICE_STATE_ALL, SERVICE_SYSTEM_START, SERVICE_WIN32,
+    ChangeServiceConfig2W, ChangeServiceConfigW, CloseServiceHandle, CreateServiceW, DeleteService,
+    EnumServicesStatusExW, OpenSCManagerW, OpenServiceW, QueryServiceConfig2W, QueryServiceConfigW,
+    ENUM_SERVICE_STATUS_PROCESSW, ENUM_SERVICE_TYPE, QUERY_SERVICE_CONFIGW, SC_ENUM_PROCESS_INFO,
+    SC_HANDLE, SC_MANAGER_CREATE_SERVICE, SC_MANAGER_ENUMERATE_SERVICE, SERVICE_ALL_ACCESS,
+    SERVICE_AUTO_START, SERVICE_CHANGE_CONFIG, SERVICE_CONFIG,
+    SERVICE_CONFIG_DELAYED_AUTO_START_INFO, SERVICE_CONFIG_TRIGGER_INFO,
+    SERVICE_DELAYED_AUTO_START_INFO, SERVICE_DISABLED, SERVICE_ERROR, SERVICE_NO_CHANGE,
+    SERVICE_QUERY_CONFIG, SERVICE_START_TYPE, SERVICE_STATE_ALL, SERVICE_TRIGGER_INFO,
+    SERVICE_WIN32,
 };
 
 use crate::error::{Error, Result};
-use crate::models::{ItemStatus, SourceType, StartupItem};
+use crate::models::{
+    ItemStatus, RawPayload, ServiceConfig, SourceType, StartMode, StartupItem,
+};
 
-use super::StartupSource;
+use super::{SavedStartType, ServiceState, StartupSource};
 
 pub struct ServicesScanner;
 
@@ -34,16 +42,100 @@ impl ServicesScanner {
         }
     }
 
-    fn pcwstr_to_string(ptr: PCWSTR) -> String {
+    /// Split a double-null-terminated list such as `lpDependencies`
+    fn multi_sz_to_strings(ptr: PWSTR) -> Vec<String> {
+        let mut strings = Vec::new();
         if ptr.is_null() {
-            return String::new();
+            return strings;
         }
 
         unsafe {
-            let len = (0..).take_while(|&i| *ptr.0.add(i) != 0).count();
-            let slice = std::slice::from_raw_parts(ptr.0, len);
-            OsString::from_wide(slice).to_string_lossy().to_string()
+            let mut current = ptr.0;
+            loop {
+                let len = (0..).take_while(|&i| *current.add(i) != 0).count();
+                if len == 0 {
+                    break;
+                }
+                let slice = std::slice::from_raw_parts(current, len);
+                strings.push(OsString::from_wide(slice).to_string_lossy().to_string());
+                current = current.add(len + 1);
+            }
+        }
+
+        strings
+    }
+
+    fn strings_to_multi_sz(strings: &[String]) -> Vec<u16> {
+        strings
+            .iter()
+            .flat_map(|s| s.encode_utf16().chain(std::iter::once(0)))
+            .chain(std::iter::once(0))
+            .collect()
+    }
+
+    /// Read everything needed to create the service again
+    unsafe fn query_service_config(service: SC_HANDLE) -> Option<ServiceConfig> {
+        let mut bytes_needed = 0u32;
+        let _ = QueryServiceConfigW(service, None, 0, &mut bytes_needed);
+        if bytes_needed == 0 {
+            return None;
         }
+
+        // u64 storage keeps the structure's pointers aligned
+        let mut buffer = vec![0u64; (bytes_needed as usize).div_ceil(8)];
+        QueryServiceConfigW(
+            service,
+            Some(buffer.as_mut_ptr() as *mut QUERY_SERVICE_CONFIGW),
+            (buffer.len() * 8) as u32,
+            &mut bytes_needed,
+        )
+        .ok()?;
+        let config = &*(buffer.as_ptr() as *const QUERY_SERVICE_CONFIGW);
+
+        let delayed_auto_start = Self::query_config2::<SERVICE_DELAYED_AUTO_START_INFO>(
+            service,
+            SERVICE_CONFIG_DELAYED_AUTO_START_INFO,
+        )
+        .is_some_and(|info| info.fDelayedAutostart.as_bool());
+
+        Some(ServiceConfig {
+            display_name: Self::pwstr_to_string(config.lpDisplayName),
+            service_type: config.dwServiceType.0,
+            start_type: config.dwStartType.0,
+            delayed_auto_start,
+            error_control: config.dwErrorControl.0,
+            image_path: Self::pwstr_to_string(config.lpBinaryPathName),
+            load_order_group: Self::pwstr_to_string(config.lpLoadOrderGroup),
+            dependencies: Self::multi_sz_to_strings(config.lpDependencies),
+            account: Self::pwstr_to_string(config.lpServiceStartName),
+        })
+    }
+
+    /// Read a `QueryServiceConfig2W` structure, `None` if the level is unavailable
+    unsafe fn query_config2<T: Copy>(service: SC_HANDLE, level: SERVICE_CONFIG) -> Option<T> {
+        let mut bytes_needed = 0u32;
+        let _ = QueryServiceConfig2W(service, level, None, &mut bytes_needed);
+        if (bytes_needed as usize) < std::mem::size_of::<T>() {
+            return None;
+        }
+
+        let mut buffer = vec![0u8; bytes_needed as usize];
+        QueryServiceConfig2W(service, level, Some(&mut buffer), &mut bytes_needed).ok()?;
+        Some(std::ptr::read_unaligned(buffer.as_ptr() as *const T))
+    }
+
+    /// The start mode, including the delayed-auto and trigger settings
+    unsafe fn start_mode(service: SC_HANDLE, start_type: u32) -> Option<StartMode> {
+        let delayed = Self::query_config2::<SERVICE_DELAYED_AUTO_START_INFO>(
+            service,
+            SERVICE_CONFIG_DELAYED_AUTO_START_INFO,
+        )
+        .is_some_and(|info| info.fDelayedAutostart.as_bool());
+        let triggered =
+            Self::query_config2::<SERVICE_TRIGGER_INFO>(service, SERVICE_CONFIG_TRIGGER_INFO)
+                .is_some_and(|info| info.cTriggers > 0);
+
+        StartMode::from_service(start_type, delayed, triggered)
     }
 
     fn to_wide(s: &str) -> Vec<u16> {
@@ -54,6 +146,7 @@ impl ServicesScanner {
 impl StartupSource for ServicesScanner {
     fn scan(&self) -> Result<Vec<StartupItem>> {
         let mut items = Vec::new();
+        let state = ServiceState::load().unwrap_or_default();
 
         unsafe {
             // Open Service Control Manager
@@ -144,19 +237,21 @@ impl StartupSource for ServicesScanner {
 
                 let config = &*(config_buffer.as_ptr() as *const QUERY_SERVICE_CONFIGW);
 
-                // Include auto-start services and disabled services
-                let start_type = config.dwStartType;
-                let status = if start_type == SERVICE_AUTO_START
-                    || start_type == SERVICE_BOOT_START
-                    || start_type == SERVICE_SYSTEM_START
-                {
-                    ItemStatus::Enabled
-                } else if start_type == SERVICE_DISABLED {
-                    ItemStatus::Disabled
-                } else {
-                    // Skip demand-start and other non-startup services
-                    let _ = CloseServiceHandle(service_handle);
-                    continue;
+                // Include automatic, trigger-start and disabled services, and
+                // manual ones that were automatic before being disabled here
+                let start_mode = Self::start_mode(service_handle, config.dwStartType.0);
+                let disabled_here = state
+                    .saved_for(&service_name)
+                    .is_some_and(|saved| saved.start_type != config.dwStartType.0);
+                let status = match start_mode {
+                    Some(StartMode::Disabled) => ItemStatus::Disabled,
+                    Some(StartMode::Manual) if disabled_here => ItemStatus::Disabled,
+                    Some(mode) if mode.is_automatic() => ItemStatus::Enabled,
+                    _ => {
+                        // Skip demand-start and other non-startup services
+                        let _ = CloseServiceHandle(service_handle);
+                        continue;
+                    }
                 };
                 let binary_path = Self::pwstr_to_string(config.lpBinaryPathName);
 
@@ -167,6 +262,7 @@ impl StartupSource for ServicesScanner {
                     binary_path,
                 )
                 .with_status(status)
+                .with_start_mode(start_mode)
                 .with_description(Some(format!("Service: {}", service_name)));
 
                 items.push(item);
@@ -181,6 +277,13 @@ impl StartupSource for ServicesScanner {
     }
 
     fn enable(&self, item: &StartupItem) -> Result<()> {
+        // Services disabled elsewhere have no saved settings and start automatically
+        let mut state = ServiceState::load()?;
+        let saved = state.saved_for(&item.source_location);
+        let start_type = saved.map_or(SERVICE_AUTO_START, |saved| {
+            SERVICE_START_TYPE(saved.start_type)
+        });
+
         unsafe {
             let scm = OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE)
                 .map_err(|_| Error::ScmAccessDenied)?;
@@ -199,10 +302,10 @@ impl StartupSource for ServicesScanner {
                 }
             })?;
 
-            let result = ChangeServiceConfigW(
+            let mut result = ChangeServiceConfigW(
                 service_handle,
                 ENUM_SERVICE_TYPE(SERVICE_NO_CHANGE),
-                SERVICE_AUTO_START,
+                start_type,
                 SERVICE_ERROR(SERVICE_NO_CHANGE),
                 PCWSTR::null(),
                 PCWSTR::null(),
@@ -213,16 +316,39 @@ impl StartupSource for ServicesScanner {
                 PCWSTR::null(),
             );
 
+            // The delayed-auto flag only means something for automatic services
+            let delayed = saved
+                .filter(|_| start_type == SERVICE_AUTO_START)
+                .map(|saved| saved.delayed_auto_start);
+            if let (Ok(()), Some(delayed)) = (&result, delayed) {
+                let info = SERVICE_DELAYED_AUTO_START_INFO {
+                    fDelayedAutostart: BOOL::from(delayed),
+                };
+                result = ChangeServiceConfig2W(
+                    service_handle,
+                    SERVICE_CONFIG_DELAYED_AUTO_START_INFO,
+                    Some(&info as *const _ as *const std::ffi::c_void),
+                );
+            }
+
             let _ = CloseServiceHandle(service_handle);
             let _ = CloseServiceHandle(scm);
 
             result.map_err(|e| Error::PermissionDenied {
                 message: format!("Failed to enable service: {}", e),
-            })
+            })?;
+        }
+
+        if saved.is_some() {
+            state.forget(&item.source_location);
+            state.save()?;
         }
+        Ok(())
     }
 
     fn disable(&self, item: &StartupItem) -> Result<()> {
+        let mut state = ServiceState::load()?;
+
         unsafe {
             let scm = OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE)
                 .map_err(|_| Error::ScmAccessDenied)?;
@@ -232,7 +358,7 @@ impl StartupSource for ServicesScanner {
             let service_handle = OpenServiceW(
                 scm,
                 PCWSTR::from_raw(service_name_wide.as_ptr()),
-                SERVICE_CHANGE_CONFIG,
+                SERVICE_CHANGE_CONFIG | SERVICE_QUERY_CONFIG,
             )
             .map_err(|e| {
                 let _ = CloseServiceHandle(scm);
@@ -241,10 +367,26 @@ impl StartupSource for ServicesScanner {
                 }
             })?;
 
+            // Remember how an enabled service starts, so enabling puts it back
+            let mut mode = item.start_mode;
+            if let Some(config) = Self::query_service_config(service_handle) {
+                mode = Self::start_mode(service_handle, config.start_type);
+                if mode.is_some_and(|mode| mode.is_automatic()) {
+                    state.remember(
+                        &item.source_location,
+                        SavedStartType {
+                            start_type: config.start_type,
+                            delayed_auto_start: config.delayed_auto_start,
+                        },
+                    );
+                }
+            }
+            let start_type = SERVICE_START_TYPE(state.disable_as.for_service(mode).start_type());
+
             let result = ChangeServiceConfigW(
                 service_handle,
                 ENUM_SERVICE_TYPE(SERVICE_NO_CHANGE),
-                SERVICE_DISABLED,
+                start_type,
                 SERVICE_ERROR(SERVICE_NO_CHANGE),
                 PCWSTR::null(),
                 PCWSTR::null(),
@@ -260,6 +402,225 @@ impl StartupSource for ServicesScanner {
 
             result.map_err(|e| Error::PermissionDenied {
                 message: format!("Failed to disable service: {}", e),
+            })?;
+        }
+
+        state.save()
+    }
+
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        unsafe {
+            let scm = OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE)
+                .map_err(|_| Error::ScmAccessDenied)?;
+
+            let service_name_wide = Self::to_wide(&item.source_location);
+            let config = match OpenServiceW(
+                scm,
+                PCWSTR::from_raw(service_name_wide.as_ptr()),
+                SERVICE_QUERY_CONFIG,
+            ) {
+                Ok(service_handle) => {
+                    let config = Self::query_service_config(service_handle);
+                    let _ = CloseServiceHandle(service_handle);
+                    config
+                }
+                Err(_) => None,
+            };
+
+            let _ = CloseServiceHandle(scm);
+            Ok(config.map(RawPayload::Service))
+        }
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let Some(RawPayload::Service(config)) = raw else {
+            return Err(Error::RestoreFailed {
+                reason: format!("the backup has no configuration for {}", item.name),
+            });
+        };
+
+        let start_type = if item.status.is_disabled() {
+            SERVICE_DISABLED
+        } else {
+            SERVICE_START_TYPE(config.start_type)
+        };
+
+        let optional = |s: &str| (!s.is_empty()).then(|| Self::to_wide(s));
+        let service_name = Self::to_wide(&item.source_location);
+        let display_name = Self::to_wide(&config.display_name);
+        let image_path = Self::to_wide(&config.image_path);
+        let load_order_group = optional(&config.load_order_group);
+        let dependencies = (!config.dependencies.is_empty())
+            .then(|| Self::strings_to_multi_sz(&config.dependencies));
+        let account = optional(&config.account);
+        let as_pcwstr = |wide: &Option<Vec<u16>>| {
+            wide.as_ref()
+                .map_or(PCWSTR::null(), |w| PCWSTR::from_raw(w.as_ptr()))
+        };
+
+        unsafe {
+            let scm = OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_CREATE_SERVICE)
+                .map_err(|_| Error::ScmAccessDenied)?;
+
+            let service_handle = CreateServiceW(
+                scm,
+                PCWSTR::from_raw(service_name.as_ptr()),
+                PCWSTR::from_raw(display_name.as_ptr()),
+                SERVICE_ALL_ACCESS,
+                ENUM_SERVICE_TYPE(config.service_type),
+                start_type,
+                SERVICE_ERROR(config.error_control),
+                PCWSTR::from_raw(image_path.as_ptr()),
+                as_pcwstr(&load_order_group),
+                None,
+                as_pcwstr(&dependencies),
+                as_pcwstr(&account),
+                PCWSTR::null(),
+            )
+            .map_err(|e| {
+                let _ = CloseServiceHandle(scm);
+                Error::PermissionDenied {
+                    message: format!("Cannot create service: {}", e),
+                }
+            })?;
+
+            if config.delayed_auto_start && start_type == SERVICE_AUTO_START {
+                let info = SERVICE_DELAYED_AUTO_START_INFO {
+                    fDelayedAutostart: BOOL::from(true),
+                };
+                let _ = ChangeServiceConfig2W(
+                    service_handle,
+                    SERVICE_CONFIG_DELAYED_AUTO_START_INFO,
+                    Some(&info as *const _ as *const std::ffi::c_void),
+                );
+            }
+
+            let _ = CloseServiceHandle(service_handle);
+            let _ = CloseServiceHandle(scm);
+        }
+
+        Ok(())
+    }
+
+    fn edit(&self, item: &StartupItem, name: &str, command: &str) -> Result<()> {
+        // Services are renamed by their display name; the key name stays
+        let display_name = (name != item.name).then(|| Self::to_wide(name));
+        let image_path = Self::to_wide(command);
+
+        unsafe {
+            let scm = OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE)
+                .map_err(|_| Error::ScmAccessDenied)?;
+
+            let service_name_wide = Self::to_wide(&item.source_location);
+
+            let service_handle = OpenServiceW(
+                scm,
+                PCWSTR::from_raw(service_name_wide.as_ptr()),
+                SERVICE_CHANGE_CONFIG,
+            )
+            .map_err(|e| {
+                let _ = CloseServiceHandle(scm);
+                Error::PermissionDenied {
+                    message: format!("Cannot modify service: {}", e),
+                }
+            })?;
+
+            let result = ChangeServiceConfigW(
+                service_handle,
+                ENUM_SERVICE_TYPE(SERVICE_NO_CHANGE),
+                SERVICE_START_TYPE(SERVICE_NO_CHANGE),
+                SERVICE_ERROR(SERVICE_NO_CHANGE),
+                PCWSTR::from_raw(image_path.as_ptr()),
+                PCWSTR::null(),
+                None,
+                PCWSTR::null(),
+                PCWSTR::null(),
+                PCWSTR::null(),
+                display_name
+                    .as_ref()
+                    .map_or(PCWSTR::null(), |w| PCWSTR::from_raw(w.as_ptr())),
+            );
+
+            let _ = CloseServiceHandle(service_handle);
+            let _ = CloseServiceHandle(scm);
+
+            result.map_err(|e| Error::PermissionDenied {
+                message: format!("Failed to change service: {}", e),
+            })
+        }
+    }
+
+    fn set_delayed_start(&self, item: &StartupItem, delayed: bool) -> Result<()> {
+        if !matches!(item.start_mode, Some(StartMode::Auto | StartMode::DelayedAuto)) {
+            return Err(Error::Unsupported(format!(
+                "delaying {}, which is not an automatic service",
+                item.name
+            )));
+        }
+
+        unsafe {
+            let scm = OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE)
+                .map_err(|_| Error::ScmAccessDenied)?;
+
+            let service_name_wide = Self::to_wide(&item.source_location);
+
+            let service_handle = OpenServiceW(
+                scm,
+                PCWSTR::from_raw(service_name_wide.as_ptr()),
+                SERVICE_CHANGE_CONFIG,
+            )
+            .map_err(|e| {
+                let _ = CloseServiceHandle(scm);
+                Error::PermissionDenied {
+                    message: format!("Cannot modify service: {}", e),
+                }
+            })?;
+
+            let info = SERVICE_DELAYED_AUTO_START_INFO {
+                fDelayedAutostart: BOOL::from(delayed),
+            };
+            let result = ChangeServiceConfig2W(
+                service_handle,
+                SERVICE_CONFIG_DELAYED_AUTO_START_INFO,
+                Some(&info as *const _ as *const std::ffi::c_void),
+            );
+
+            let _ = CloseServiceHandle(service_handle);
+            let _ = CloseServiceHandle(scm);
+
+            result.map_err(|e| Error::PermissionDenied {
+                message: format!("Failed to change the service's delayed start: {}", e),
+            })
+        }
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        unsafe {
+            let scm = OpenSCManagerW(PCWSTR::null(), PCWSTR::null(), SC_MANAGER_ENUMERATE_SERVICE)
+                .map_err(|_| Error::ScmAccessDenied)?;
+
+            let service_name_wide = Self::to_wide(&item.source_location);
+
+            let service_handle = OpenServiceW(
+                scm,
+                PCWSTR::from_raw(service_name_wide.as_ptr()),
+                DELETE.0,
+            )
+            .map_err(|e| {
+                let _ = CloseServiceHandle(scm);
+                Error::PermissionDenied {
+                    message: format!("Cannot delete service: {}", e),
+                }
+            })?;
+
+            // A running service is removed once it stops
+            let result = DeleteService(service_handle);
+
+            let _ = CloseServiceHandle(service_handle);
+            let _ = CloseServiceHandle(scm);
+
+            result.map_err(|e| Error::PermissionDenied {
+                message: format!("Failed to delete service: {}", e),
             })
         }
     }
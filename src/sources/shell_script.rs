@@ -0,0 +1,84 @@
+use super::comment_marker::strip_marker;
+
+/// A line of a shell script that can be commented out without breaking the
+/// rest of the script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptLine<'a> {
+    /// 0-based line number in the file
+    pub line: usize,
+    /// The trimmed line, without the disabled marker
+    pub text: &'a str,
+    /// True for `if`/`for`/`while`/`case` statements opened and closed on this line
+    pub compound: bool,
+    /// False if the line was commented out by this tool
+    pub enabled: bool,
+}
+
+const OPENERS: &[&str] = &["if", "for", "while", "until", "case", "select"];
+const CLOSERS: &[&str] = &["fi", "done", "esac", "}"];
+
+/// Find the top-level lines of a script. Lines inside compound statements
+/// or spanning several lines are left out, since commenting out one of them
+/// would break the script.
+pub fn top_level_lines(content: &str) -> Vec<ScriptLine<'_>> {
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    let mut continued = false;
+
+    for (index, raw) in content.lines().enumerate() {
+        let (line, enabled) = match strip_marker(raw) {
+            Some(original) => (original.trim(), false),
+            None => (raw.trim(), true),
+        };
+
+        let was_continued = continued;
+        continued = line.ends_with('\\');
+        if was_continued || continued {
+            continue;
+        }
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let first_word = first_word(line);
+
+        if CLOSERS.contains(&first_word) {
+            depth = depth.saturating_sub(1);
+            continue;
+        }
+
+        let mut compound = false;
+        if OPENERS.contains(&first_word) || line.ends_with('{') {
+            // One-line compound statements close on the same line
+            let closed = CLOSERS
+                .iter()
+                .any(|c| line.ends_with(&format!("; {c}")) || line.ends_with(&format!(";{c}")));
+            if !closed {
+                depth += 1;
+                continue;
+            }
+            compound = true;
+        }
+
+        if depth > 0 {
+            continue;
+        }
+
+        lines.push(ScriptLine {
+            line: index,
+            text: line,
+            compound,
+            enabled,
+        });
+    }
+
+    lines
+}
+
+/// The first word of a command, up to whitespace or `;`
+pub fn first_word(line: &str) -> &str {
+    line.split(|c: char| c.is_whitespace() || c == ';')
+        .next()
+        .unwrap_or("")
+}
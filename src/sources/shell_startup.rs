@@ -0,0 +1,482 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+use super::comment_marker::{
+    insert_line, line_at, line_for_status, pick_line, remove_line, set_line_enabled, uncomment,
+};
+use super::shell_script::{first_word, top_level_lines};
+use super::xdg_autostart::find_program;
+use super::StartupSource;
+
+/// Startup files read by bash, zsh and POSIX login shells, relative to the home directory
+const USER_FILES: &[&str] = &[
+    ".profile",
+    ".bash_profile",
+    ".bash_login",
+    ".bashrc",
+    ".zshenv",
+    ".zprofile",
+    ".zshrc",
+    ".zlogin",
+];
+
+const SYSTEM_FILES: &[&str] = &[
+    "/etc/environment",
+    "/etc/profile",
+    "/etc/bash.bashrc",
+    "/etc/bashrc",
+    "/etc/zshenv",
+    "/etc/zprofile",
+    "/etc/zshrc",
+    "/etc/zsh/zshenv",
+    "/etc/zsh/zprofile",
+    "/etc/zsh/zshrc",
+];
+
+const PROFILE_D: &str = "/etc/profile.d";
+
+/// `PATH` used to look up programs started from startup files
+const DEFAULT_PATH: &str = "/usr/local/bin:/usr/bin:/bin";
+
+/// Commands that detach the program they start
+const DETACHERS: &[&str] = &["nohup", "setsid", "disown", "daemonize"];
+
+/// Why a startup file line is reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellEntryKind {
+    /// Starts a program in the background (`&`, `nohup`, `setsid`)
+    Background,
+    /// Sets `LD_PRELOAD`
+    Preload,
+    /// Sources another script with `.` or `source`
+    Source,
+}
+
+impl ShellEntryKind {
+    pub fn display(&self) -> &'static str {
+        match self {
+            Self::Background => "Starts a background program",
+            Self::Preload => "Sets LD_PRELOAD",
+            Self::Source => "Sources an external script",
+        }
+    }
+}
+
+/// A line from a shell startup file worth reporting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellEntry {
+    /// 0-based line number in the file
+    pub line: usize,
+    pub text: String,
+    pub kind: ShellEntryKind,
+    /// The program, library or script the line refers to
+    pub target: String,
+    /// False if the line was commented out by this tool
+    pub enabled: bool,
+}
+
+fn unquote(word: &str) -> &str {
+    let word = word.trim_end_matches(';');
+    ['"', '\'']
+        .iter()
+        .find_map(|q| word.strip_prefix(*q).and_then(|w| w.strip_suffix(*q)))
+        .unwrap_or(word)
+}
+
+fn is_separator(word: &str) -> bool {
+    matches!(word, "then" | "do" | "else" | "&&" | "||" | "{" | "(") || word.ends_with(';')
+}
+
+/// The script loaded by `. path` or `source path`
+fn sourced_script(text: &str) -> Option<&str> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    words.iter().enumerate().find_map(|(i, word)| {
+        let command = matches!(*word, "." | "source");
+        let at_start = i == 0 || is_separator(words[i - 1]);
+        (command && at_start)
+            .then(|| words.get(i + 1).map(|w| unquote(w)))
+            .flatten()
+    })
+}
+
+/// The first library in an `LD_PRELOAD=` assignment
+fn preloaded_library(text: &str) -> Option<&str> {
+    let (_, value) = text.split_once("LD_PRELOAD=")?;
+    let value = value.trim_start_matches(['"', '\'']);
+    value
+        .split(|c: char| c.is_whitespace() || matches!(c, ':' | ';' | '"' | '\''))
+        .find(|s| !s.is_empty())
+}
+
+/// The program started in the background, skipping detach helpers and variable assignments
+fn background_program(text: &str) -> Option<&str> {
+    let statement = text.trim_end_matches(';').trim_end();
+    let backgrounded = statement.ends_with('&') && !statement.ends_with("&&");
+    let detached = DETACHERS.contains(&first_word(text));
+    if !backgrounded && !detached {
+        return None;
+    }
+
+    // In `[ -x prog ] && prog &` the program is the last command of the list
+    let command = ["&&", "||", ";"]
+        .iter()
+        .filter_map(|sep| statement.rfind(sep).map(|i| i + sep.len()))
+        .max()
+        .map_or(statement, |start| &statement[start..]);
+
+    command
+        .split_whitespace()
+        .map(|w| w.trim_start_matches('('))
+        .find(|w| {
+            !w.is_empty() && !DETACHERS.contains(w) && !w.contains('=') && !w.starts_with('-')
+        })
+        .map(unquote)
+        .filter(|w| *w != "&")
+}
+
+/// Find the lines of a startup file that start programs, set `LD_PRELOAD` or
+/// source other scripts. Only top-level lines are considered, so commenting
+/// one out never breaks the surrounding script.
+pub fn parse_startup_file(content: &str) -> Vec<ShellEntry> {
+    top_level_lines(content)
+        .into_iter()
+        .filter_map(|line| {
+            let (kind, target) = if let Some(library) = preloaded_library(line.text) {
+                (ShellEntryKind::Preload, library)
+            } else if let Some(script) = sourced_script(line.text) {
+                (ShellEntryKind::Source, script)
+            } else {
+                (ShellEntryKind::Background, background_program(line.text)?)
+            };
+
+            Some(ShellEntry {
+                line: line.line,
+                text: line.text.to_string(),
+                kind,
+                target: target.to_string(),
+                enabled: line.enabled,
+            })
+        })
+        .collect()
+}
+
+/// A login account with a home directory
+struct UserHome {
+    name: String,
+    home: PathBuf,
+}
+
+pub struct ShellStartupScanner {
+    root: PathBuf,
+}
+
+impl ShellStartupScanner {
+    pub fn new() -> Self {
+        Self::with_root(PathBuf::from("/"))
+    }
+
+    /// Scan startup files below a different root directory, e.g. a fake root in tests
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn under_root(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    /// Regular users and root from `/etc/passwd`
+    fn users(&self) -> Vec<UserHome> {
+        const NOBODY_UID: u32 = 65534;
+
+        let Ok(passwd) = fs::read_to_string(self.under_root("/etc/passwd")) else {
+            return Vec::new();
+        };
+
+        let mut users: Vec<UserHome> = Vec::new();
+        for line in passwd.lines() {
+            let fields: Vec<&str> = line.split(':').collect();
+            let [name, _, uid, _, _, home, ..] = fields.as_slice() else {
+                continue;
+            };
+            let Ok(uid) = uid.parse::<u32>() else {
+                continue;
+            };
+            if uid != 0 && (uid < 1000 || uid == NOBODY_UID) {
+                continue;
+            }
+
+            let home = self.under_root(home);
+            if home.is_dir() && !users.iter().any(|u| u.home == home) {
+                users.push(UserHome {
+                    name: name.to_string(),
+                    home,
+                });
+            }
+        }
+
+        users
+    }
+
+    fn system_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = SYSTEM_FILES.iter().map(|f| self.under_root(f)).collect();
+
+        if let Ok(entries) = fs::read_dir(self.under_root(PROFILE_D)) {
+            let mut scripts: Vec<PathBuf> = entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && p.extension().is_some_and(|e| e == "sh"))
+                .collect();
+            scripts.sort();
+            files.extend(scripts);
+        }
+
+        files
+    }
+
+    /// Expand `~` and `$HOME` for user files, then resolve the path inside the root
+    fn resolve_target(&self, entry: &ShellEntry, home: Option<&Path>) -> Option<PathBuf> {
+        let mut target = entry.target.clone();
+        if let Some(home) = home {
+            let home = home.to_string_lossy();
+            for prefix in ["~/", "$HOME/", "${HOME}/"] {
+                if let Some(rest) = target.strip_prefix(prefix) {
+                    target = format!("{home}/{rest}");
+                    break;
+                }
+            }
+            if target.starts_with(&*home) {
+                return Some(PathBuf::from(target));
+            }
+        }
+
+        if target.contains('$') || target.starts_with('~') {
+            return None;
+        }
+
+        if target.starts_with('/') {
+            Some(self.under_root(&target))
+        } else if entry.kind == ShellEntryKind::Background {
+            let rooted_path = std::env::join_paths(
+                std::env::split_paths(DEFAULT_PATH).map(|d| self.under_root(&d.to_string_lossy())),
+            )
+            .ok()
+            .map(|p| p.to_string_lossy().to_string());
+            find_program(&target, rooted_path.as_deref()).or_else(|| Some(PathBuf::from(&target)))
+        } else {
+            Some(PathBuf::from(target))
+        }
+    }
+
+    fn scan_file(
+        &self,
+        path: &Path,
+        source: SourceType,
+        user: Option<&UserHome>,
+    ) -> Vec<StartupItem> {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Vec::new();
+        };
+
+        // /etc/environment is read by pam_env, only the LD_PRELOAD assignment matters there
+        let environment_file = path.ends_with("etc/environment");
+
+        parse_startup_file(&content)
+            .into_iter()
+            .filter(|e| !environment_file || e.kind == ShellEntryKind::Preload)
+            .map(|entry| {
+                let name = Path::new(&entry.target)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| entry.target.clone());
+
+                let scope = match user {
+                    Some(user) => format!("for {}", user.name),
+                    None => "for every user".to_string(),
+                };
+
+                let mut item =
+                    StartupItem::new(name, source, path.display().to_string(), entry.text.clone())
+                        .with_status(if entry.enabled {
+                            ItemStatus::Enabled
+                        } else {
+                            ItemStatus::Disabled
+                        })
+                        .with_description(Some(format!("{} {scope}", entry.kind.display())))
+                        .with_line(entry.line);
+
+                match self.resolve_target(&entry, user.map(|u| u.home.as_path())) {
+                    Some(target) => {
+                        item.file_exists = target.exists();
+                        item.executable_path = Some(target);
+                    }
+                    // Depends on the environment of each session, cannot be checked here
+                    None => {
+                        item.file_exists = true;
+                        item.executable_path = None;
+                    }
+                }
+
+                item
+            })
+            .collect()
+    }
+
+    /// Line number of the entry backing `item`, matched by its text and
+    /// told apart from repeats by the line it was scanned on
+    fn find_line(item: &StartupItem, content: &str) -> Option<usize> {
+        let matches: Vec<usize> = parse_startup_file(content)
+            .into_iter()
+            .filter(|e| e.text == item.command)
+            .map(|e| e.line)
+            .collect();
+        pick_line(&matches, item.line)
+    }
+
+    fn set_enabled(&self, item: &StartupItem, enabled: bool) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let content = fs::read_to_string(&path).map_err(Error::Io)?;
+
+        let updated = Self::find_line(item, &content)
+            .and_then(|line| set_line_enabled(&content, line, enabled))
+            .ok_or_else(|| Error::ItemNotFound {
+                id: item.id.clone(),
+            })?;
+
+        fs::write(&path, updated).map_err(Error::Io)
+    }
+}
+
+impl StartupSource for ShellStartupScanner {
+    fn scan(&self) -> Result<Vec<StartupItem>> {
+        let mut items = Vec::new();
+
+        for user in self.users() {
+            for file in USER_FILES {
+                let path = user.home.join(file);
+                items.extend(self.scan_file(&path, SourceType::ShellStartupUser, Some(&user)));
+            }
+        }
+
+        for path in self.system_files() {
+            items.extend(self.scan_file(&path, SourceType::ShellStartupSystem, None));
+        }
+
+        Ok(items)
+    }
+
+    fn enable(&self, item: &StartupItem) -> Result<()> {
+        self.set_enabled(item, true)
+    }
+
+    fn disable(&self, item: &StartupItem) -> Result<()> {
+        self.set_enabled(item, false)
+    }
+
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        let content = fs::read_to_string(&item.source_location).map_err(Error::Io)?;
+        Ok(Self::find_line(item, &content)
+            .and_then(|index| line_at(&content, index))
+            .map(|line| RawPayload::Line {
+                line: uncomment(line),
+            }))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let Some(RawPayload::Line { line }) = raw else {
+            return Err(Error::RestoreFailed {
+                reason: format!("no line was saved for {}", item.name),
+            });
+        };
+
+        let path = PathBuf::from(&item.source_location);
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        let line = line_for_status(line, item.status.is_enabled());
+        fs::write(&path, insert_line(&content, usize::MAX, &line)).map_err(Error::Io)
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let content = fs::read_to_string(&path).map_err(Error::Io)?;
+
+        let updated = Self::find_line(item, &content)
+            .and_then(|index| remove_line(&content, index))
+            .ok_or_else(|| Error::ItemNotFound {
+                id: item.id.clone(),
+            })?;
+
+        fs::write(&path, updated).map_err(Error::Io)
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![SourceType::ShellStartupUser, SourceType::ShellStartupSystem]
+    }
+}
+
+impl Default for ShellStartupScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_background_preload_and_sourced_lines() {
+        let content = "export PATH=$HOME/bin:$PATH\n\
+                       nohup /opt/sync/agent --quiet\n\
+                       [ -x /usr/bin/tray ] && /usr/bin/tray &\n\
+                       export LD_PRELOAD=/usr/lib/libhook.so\n\
+                       . ~/.config/extra.sh\n\
+                       if true; then\n  /usr/bin/nested &\nfi\n\
+                       make && make install\n";
+        let entries = parse_startup_file(content);
+
+        let found: Vec<(usize, ShellEntryKind, &str)> = entries
+            .iter()
+            .map(|e| (e.line, e.kind, e.target.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (1, ShellEntryKind::Background, "/opt/sync/agent"),
+                (2, ShellEntryKind::Background, "/usr/bin/tray"),
+                (3, ShellEntryKind::Preload, "/usr/lib/libhook.so"),
+                (4, ShellEntryKind::Source, "~/.config/extra.sh"),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_lines_are_toggled_on_their_own_line() {
+        let root =
+            std::env::temp_dir().join(format!("startup-checker-shell-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let home = root.join("home/user");
+        fs::create_dir_all(root.join("etc")).unwrap();
+        fs::create_dir_all(&home).unwrap();
+        fs::write(
+            root.join("etc/passwd"),
+            "user:x:1000:1000::/home/user:/bin/bash\n",
+        )
+        .unwrap();
+        let profile = home.join(".profile");
+        fs::write(&profile, "/usr/bin/agent &\n/usr/bin/agent &\n").unwrap();
+        let scanner = ShellStartupScanner::with_root(root.clone());
+
+        let items = scanner.scan().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].line, Some(1));
+
+        scanner.disable(&items[1]).unwrap();
+        let entries = parse_startup_file(&fs::read_to_string(&profile).unwrap());
+        assert!(entries[0].enabled);
+        assert!(!entries[1].enabled);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
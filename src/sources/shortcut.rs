@@ -0,0 +1,460 @@
+/// `{00021401-0000-0000-C000-000000000046}` in its on-disk byte order
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+
+const HEADER_SIZE: u32 = 0x4C;
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x01;
+const HAS_LINK_INFO: u32 = 0x02;
+const HAS_NAME: u32 = 0x04;
+const HAS_RELATIVE_PATH: u32 = 0x08;
+const HAS_WORKING_DIR: u32 = 0x10;
+const HAS_ARGUMENTS: u32 = 0x20;
+const HAS_ICON_LOCATION: u32 = 0x40;
+const IS_UNICODE: u32 = 0x80;
+const FORCE_NO_LINK_INFO: u32 = 0x100;
+const HAS_EXP_STRING: u32 = 0x200;
+const HAS_DARWIN_ID: u32 = 0x1000;
+const PREFER_ENVIRONMENT_PATH: u32 = 0x0200_0000;
+
+/// Flags describing how the old target was found, cleared when retargeting
+const TARGET_FLAGS: u32 = HAS_LINK_TARGET_ID_LIST
+    | HAS_RELATIVE_PATH
+    | HAS_WORKING_DIR
+    | HAS_ARGUMENTS
+    | FORCE_NO_LINK_INFO
+    | HAS_EXP_STRING
+    | HAS_DARWIN_ID
+    | PREFER_ENVIRONMENT_PATH;
+
+/// Extra data blocks that do not point at the target: console settings,
+/// the icon's environment path, compatibility shims and the property store
+const KEPT_BLOCKS: &[u32] = &[
+    0xA000_0002,
+    0xA000_0004,
+    0xA000_0007,
+    0xA000_0008,
+    0xA000_0009,
+];
+
+const FILE_ATTRIBUTE_NORMAL: u32 = 0x80;
+const SW_SHOWNORMAL: u32 = 1;
+
+/// LinkInfo header with the optional Unicode offsets
+const LINK_INFO_HEADER_SIZE: u32 = 0x24;
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x01;
+const DRIVE_FIXED: u32 = 3;
+
+/// A shortcut to a local program in the shell link (`.lnk`) format
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Shortcut {
+    /// Absolute path of the program
+    pub target: String,
+    /// Command line after the program, already quoted
+    pub arguments: String,
+    pub working_dir: String,
+}
+
+impl Shortcut {
+    pub fn new(target: impl Into<String>) -> Self {
+        Self {
+            target: target.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_arguments(mut self, arguments: impl Into<String>) -> Self {
+        self.arguments = arguments.into();
+        self
+    }
+
+    pub fn with_working_dir(mut self, working_dir: impl Into<String>) -> Self {
+        self.working_dir = working_dir.into();
+        self
+    }
+
+    /// Read the target, arguments and working directory of a `.lnk` file.
+    /// Links that only identify their target by a shell ID list, such as
+    /// ones to Control Panel items, give `None`.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if read_u32(data, 0)? != HEADER_SIZE || data.get(4..20)? != LINK_CLSID {
+            return None;
+        }
+        let flags = read_u32(data, 20)?;
+        let unicode = flags & IS_UNICODE != 0;
+        let mut offset = HEADER_SIZE as usize;
+
+        if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            offset += 2 + read_u16(data, offset)? as usize;
+        }
+
+        let mut target = String::new();
+        if flags & HAS_LINK_INFO != 0 {
+            let info = data.get(offset..)?;
+            let size = read_u32(info, 0)? as usize;
+            target = link_info_path(info.get(..size)?).unwrap_or_default();
+            offset += size;
+        }
+
+        let mut shortcut = Self::new(target);
+        for (flag, field) in [
+            (HAS_NAME, None),
+            (HAS_RELATIVE_PATH, None),
+            (HAS_WORKING_DIR, Some(&mut shortcut.working_dir)),
+            (HAS_ARGUMENTS, Some(&mut shortcut.arguments)),
+        ] {
+            if flags & flag == 0 {
+                continue;
+            }
+            let (text, len) = read_counted_string(data.get(offset..)?, unicode)?;
+            if let Some(field) = field {
+                *field = text;
+            }
+            offset += len;
+        }
+
+        (!shortcut.target.is_empty()).then_some(shortcut)
+    }
+
+    /// The `.lnk` file contents. The target is stored as a local path in
+    /// the LinkInfo, which the shell resolves without an ID list.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = HAS_LINK_INFO | IS_UNICODE;
+        if !self.working_dir.is_empty() {
+            flags |= HAS_WORKING_DIR;
+        }
+        if !self.arguments.is_empty() {
+            flags |= HAS_ARGUMENTS;
+        }
+
+        let mut data = Vec::new();
+        put_u32(&mut data, HEADER_SIZE);
+        data.extend_from_slice(&LINK_CLSID);
+        put_u32(&mut data, flags);
+        put_u32(&mut data, FILE_ATTRIBUTE_NORMAL);
+        // Creation, access and write times, file size and icon index are
+        // refreshed by the shell when it resolves the link
+        data.extend_from_slice(&[0; 24]);
+        put_u32(&mut data, 0);
+        put_u32(&mut data, 0);
+        put_u32(&mut data, SW_SHOWNORMAL);
+        // Hot key and reserved fields
+        data.extend_from_slice(&[0; 12]);
+
+        data.extend_from_slice(&self.link_info());
+
+        // StringData in the order the format defines
+        if !self.working_dir.is_empty() {
+            put_counted_string(&mut data, &self.working_dir);
+        }
+        if !self.arguments.is_empty() {
+            put_counted_string(&mut data, &self.arguments);
+        }
+
+        // Terminal block
+        put_u32(&mut data, 0);
+        data
+    }
+
+    /// Point an existing `.lnk` file at this target, arguments and working
+    /// directory. Its description, icon, show command, hot key and other
+    /// settings are kept; the ID list and tracking data that located the old
+    /// target are dropped. `None` if `data` is not a shell link.
+    pub fn retarget(&self, data: &[u8]) -> Option<Vec<u8>> {
+        if read_u32(data, 0)? != HEADER_SIZE || data.get(4..20)? != LINK_CLSID {
+            return None;
+        }
+        let old_flags = read_u32(data, 20)?;
+        let unicode = old_flags & IS_UNICODE != 0;
+        let mut offset = HEADER_SIZE as usize;
+
+        if old_flags & HAS_LINK_TARGET_ID_LIST != 0 {
+            offset += 2 + read_u16(data, offset)? as usize;
+        }
+        if old_flags & HAS_LINK_INFO != 0 {
+            offset += read_u32(data, offset)? as usize;
+        }
+
+        let (mut name, mut icon_location) = (None, None);
+        for flag in [
+            HAS_NAME,
+            HAS_RELATIVE_PATH,
+            HAS_WORKING_DIR,
+            HAS_ARGUMENTS,
+            HAS_ICON_LOCATION,
+        ] {
+            if old_flags & flag == 0 {
+                continue;
+            }
+            let (text, len) = read_counted_string(data.get(offset..)?, unicode)?;
+            match flag {
+                HAS_NAME => name = Some(text),
+                HAS_ICON_LOCATION => icon_location = Some(text),
+                _ => {}
+            }
+            offset += len;
+        }
+
+        // Extra data blocks run until one smaller than a block header
+        let mut blocks = Vec::new();
+        while let Some(size) = read_u32(data, offset).filter(|&size| size >= 8) {
+            let block = data.get(offset..offset + size as usize)?;
+            if KEPT_BLOCKS.contains(&read_u32(block, 4)?) {
+                blocks.extend_from_slice(block);
+            }
+            offset += size as usize;
+        }
+
+        let mut flags = (old_flags & !TARGET_FLAGS) | HAS_LINK_INFO | IS_UNICODE;
+        if !self.working_dir.is_empty() {
+            flags |= HAS_WORKING_DIR;
+        }
+        if !self.arguments.is_empty() {
+            flags |= HAS_ARGUMENTS;
+        }
+
+        let mut patched = data[..HEADER_SIZE as usize].to_vec();
+        patched[20..24].copy_from_slice(&flags.to_le_bytes());
+        // The attributes, times and size describe the old target
+        patched[24..28].copy_from_slice(&FILE_ATTRIBUTE_NORMAL.to_le_bytes());
+        patched[28..56].fill(0);
+
+        patched.extend_from_slice(&self.link_info());
+        for text in [
+            name.as_deref(),
+            Some(self.working_dir.as_str()).filter(|dir| !dir.is_empty()),
+            Some(self.arguments.as_str()).filter(|args| !args.is_empty()),
+            icon_location.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            put_counted_string(&mut patched, text);
+        }
+        patched.extend_from_slice(&blocks);
+
+        // Terminal block
+        put_u32(&mut patched, 0);
+        Some(patched)
+    }
+
+    fn link_info(&self) -> Vec<u8> {
+        let mut volume_id = Vec::new();
+        // Size, drive type, serial number, label offset and an empty label
+        put_u32(&mut volume_id, 17);
+        put_u32(&mut volume_id, DRIVE_FIXED);
+        put_u32(&mut volume_id, 0);
+        put_u32(&mut volume_id, 0x10);
+        volume_id.push(0);
+
+        // ANSI copies for old readers; characters outside ASCII only
+        // survive in the Unicode copies
+        let ansi_path: Vec<u8> = self
+            .target
+            .chars()
+            .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+            .chain(std::iter::once(0))
+            .collect();
+        let ansi_suffix = [0u8];
+        let unicode_path = utf16_nul(&self.target);
+        let unicode_suffix = utf16_nul("");
+
+        let volume_id_offset = LINK_INFO_HEADER_SIZE;
+        let base_path_offset = volume_id_offset + volume_id.len() as u32;
+        let suffix_offset = base_path_offset + ansi_path.len() as u32;
+        let unicode_path_offset = suffix_offset + ansi_suffix.len() as u32;
+        let unicode_suffix_offset = unicode_path_offset + unicode_path.len() as u32;
+        let size = unicode_suffix_offset + unicode_suffix.len() as u32;
+
+        let mut info = Vec::with_capacity(size as usize);
+        put_u32(&mut info, size);
+        put_u32(&mut info, LINK_INFO_HEADER_SIZE);
+        put_u32(&mut info, VOLUME_ID_AND_LOCAL_BASE_PATH);
+        put_u32(&mut info, volume_id_offset);
+        put_u32(&mut info, base_path_offset);
+        // No network share
+        put_u32(&mut info, 0);
+        put_u32(&mut info, suffix_offset);
+        put_u32(&mut info, unicode_path_offset);
+        put_u32(&mut info, unicode_suffix_offset);
+        info.extend_from_slice(&volume_id);
+        info.extend_from_slice(&ansi_path);
+        info.extend_from_slice(&ansi_suffix);
+        info.extend_from_slice(&unicode_path);
+        info.extend_from_slice(&unicode_suffix);
+        info
+    }
+}
+
+/// The local path a LinkInfo structure points at, preferring the Unicode copy
+fn link_info_path(info: &[u8]) -> Option<String> {
+    let header_size = read_u32(info, 4)?;
+    if read_u32(info, 8)? & VOLUME_ID_AND_LOCAL_BASE_PATH == 0 {
+        return None;
+    }
+
+    if header_size >= LINK_INFO_HEADER_SIZE {
+        let base = read_utf16_nul(info, read_u32(info, 28)? as usize)?;
+        let suffix = read_utf16_nul(info, read_u32(info, 32)? as usize).unwrap_or_default();
+        return Some(base + &suffix);
+    }
+
+    let base = read_ansi_nul(info, read_u32(info, 16)? as usize)?;
+    let suffix = read_ansi_nul(info, read_u32(info, 24)? as usize).unwrap_or_default();
+    Some(base + &suffix)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_utf16_nul(data: &[u8], offset: usize) -> Option<String> {
+    let units: Vec<u16> = data
+        .get(offset..)?
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+fn read_ansi_nul(data: &[u8], offset: usize) -> Option<String> {
+    let bytes: Vec<u8> = data
+        .get(offset..)?
+        .iter()
+        .copied()
+        .take_while(|&b| b != 0)
+        .collect();
+    Some(String::from_utf8_lossy(&bytes).to_string())
+}
+
+/// A StringData entry and the number of bytes it takes
+fn read_counted_string(data: &[u8], unicode: bool) -> Option<(String, usize)> {
+    let count = read_u16(data, 0)? as usize;
+    if unicode {
+        let bytes = data.get(2..2 + count * 2)?;
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        Some((String::from_utf16_lossy(&units), 2 + count * 2))
+    } else {
+        let bytes = data.get(2..2 + count)?;
+        Some((String::from_utf8_lossy(bytes).to_string(), 2 + count))
+    }
+}
+
+fn put_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_le_bytes());
+}
+
+fn utf16_nul(text: &str) -> Vec<u8> {
+    text.encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect()
+}
+
+/// A StringData entry: the length in UTF-16 units, then the text without a
+/// terminator
+fn put_counted_string(data: &mut Vec<u8>, text: &str) {
+    let units: Vec<u16> = text.encode_utf16().collect();
+    data.extend_from_slice(&(units.len() as u16).to_le_bytes());
+    for unit in units {
+        data.extend_from_slice(&unit.to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A link with an ID list, description, icon, minimized show command,
+    /// hot key, a tracker block and a property store block
+    fn shell_link(target: &str) -> Vec<u8> {
+        let flags =
+            HAS_LINK_TARGET_ID_LIST | HAS_LINK_INFO | HAS_NAME | HAS_ICON_LOCATION | IS_UNICODE;
+        let mut data = Shortcut::new(target).to_bytes()[..HEADER_SIZE as usize].to_vec();
+        data[20..24].copy_from_slice(&flags.to_le_bytes());
+        data[60..64].copy_from_slice(&7u32.to_le_bytes());
+        data[64..66].copy_from_slice(&0x0641u16.to_le_bytes());
+
+        // An empty ID list
+        data.extend_from_slice(&2u16.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data.extend_from_slice(&Shortcut::new(target).link_info());
+        put_counted_string(&mut data, "My App");
+        put_counted_string(&mut data, r"C:\Icons\app.ico");
+
+        put_u32(&mut data, 16);
+        put_u32(&mut data, 0xA000_0003);
+        data.extend_from_slice(&[0xAA; 8]);
+        put_u32(&mut data, 12);
+        put_u32(&mut data, 0xA000_0009);
+        data.extend_from_slice(&[0xBB; 4]);
+        put_u32(&mut data, 0);
+        data
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let shortcut = Shortcut::new(r"C:\Program Files\App\app.exe")
+            .with_arguments("--minimized \"a b\"")
+            .with_working_dir(r"C:\Program Files\App");
+
+        assert_eq!(Shortcut::parse(&shortcut.to_bytes()), Some(shortcut));
+    }
+
+    #[test]
+    fn round_trips_targets_outside_ascii() {
+        let shortcut = Shortcut::new(r"C:\Programme\Grüße\app.exe");
+        assert_eq!(Shortcut::parse(&shortcut.to_bytes()), Some(shortcut));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(Shortcut::parse(b"not a shortcut"), None);
+        assert_eq!(Shortcut::new("x").retarget(&[0; 16]), None);
+    }
+
+    #[test]
+    fn retarget_changes_only_the_target() {
+        let new = Shortcut::new(r"C:\New\new.exe")
+            .with_arguments("-x")
+            .with_working_dir(r"C:\New");
+        let patched = new.retarget(&shell_link(r"C:\Old\old.exe")).unwrap();
+
+        assert_eq!(Shortcut::parse(&patched), Some(new));
+
+        let flags = read_u32(&patched, 20).unwrap();
+        assert_eq!(flags & HAS_LINK_TARGET_ID_LIST, 0);
+        assert_ne!(flags & HAS_NAME, 0);
+        assert_ne!(flags & HAS_ICON_LOCATION, 0);
+        assert_eq!(read_u32(&patched, 60), Some(7));
+        assert_eq!(read_u16(&patched, 64), Some(0x0641));
+
+        // The tracker block is dropped, the property store kept
+        assert!(!patched.windows(8).any(|w| w == [0xAA; 8]));
+        assert!(patched.ends_with(&[0x09, 0x00, 0x00, 0xA0, 0xBB, 0xBB, 0xBB, 0xBB, 0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn retarget_keeps_the_description_and_icon() {
+        let new = Shortcut::new(r"C:\New\new.exe");
+        let patched = new.retarget(&shell_link(r"C:\Old\old.exe")).unwrap();
+
+        let offset = HEADER_SIZE as usize + new.link_info().len();
+        let (name, len) = read_counted_string(&patched[offset..], true).unwrap();
+        let (icon, _) = read_counted_string(&patched[offset + len..], true).unwrap();
+        assert_eq!(name, "My App");
+        assert_eq!(icon, r"C:\Icons\app.ico");
+    }
+}
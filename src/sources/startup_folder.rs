@@ -1,10 +1,10 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
-use crate::models::{ItemStatus, SourceType, StartupItem};
+use crate::models::{ItemStatus, PathResolver, RawPayload, SourceType, StartupItem};
 
-use super::StartupSource;
+use super::{Shortcut, StartupSource};
 
 const DISABLED_EXTENSION: &str = ".disabled";
 
@@ -29,6 +29,15 @@ impl StartupFolderScanner {
         PathBuf::from(r"C:\ProgramData\Microsoft\Windows\Start Menu\Programs\Startup")
     }
 
+    /// The folder a Startup folder source reads
+    pub fn folder_for(source: SourceType) -> Option<PathBuf> {
+        match source {
+            SourceType::StartupFolderUser => Self::get_user_startup_folder(),
+            SourceType::StartupFolderAllUsers => Some(Self::get_all_users_startup_folder()),
+            _ => None,
+        }
+    }
+
     fn scan_folder(&self, folder: &PathBuf, source: SourceType) -> Vec<StartupItem> {
         let mut items = Vec::new();
 
@@ -59,17 +68,11 @@ impl StartupFolderScanner {
                 continue;
             }
 
-            // Check if disabled
-            let (display_name, status) = if file_name.ends_with(DISABLED_EXTENSION) {
-                let name = file_name.trim_end_matches(DISABLED_EXTENSION).to_string();
-                (name, ItemStatus::Disabled)
+            let (display_name, disabled) = item_name(&file_name);
+            let status = if disabled {
+                ItemStatus::Disabled
             } else {
-                // Remove extension for display
-                let name = path
-                    .file_stem()
-                    .map(|s| s.to_string_lossy().to_string())
-                    .unwrap_or(file_name.clone());
-                (name, ItemStatus::Enabled)
+                ItemStatus::Enabled
             };
 
             // Get the target for shortcuts
@@ -98,6 +101,21 @@ impl StartupFolderScanner {
     }
 }
 
+/// The name an item is shown under and whether it is disabled. Both
+/// `App.lnk` and `App.lnk.disabled` are named `App`, so the item keeps
+/// its id when it is toggled.
+fn item_name(file_name: &str) -> (String, bool) {
+    let (base, disabled) = match file_name.strip_suffix(DISABLED_EXTENSION) {
+        Some(base) => (base, true),
+        None => (file_name, false),
+    };
+    let name = Path::new(base)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| base.to_string());
+    (name, disabled)
+}
+
 impl StartupSource for StartupFolderScanner {
     fn scan(&self) -> Result<Vec<StartupItem>> {
         let mut all_items = Vec::new();
@@ -125,9 +143,8 @@ impl StartupSource for StartupFolderScanner {
             let file_name = path.file_name().unwrap_or_default().to_string_lossy();
 
             // Check if this is the disabled version of our item
-            if file_name.ends_with(DISABLED_EXTENSION) {
-                let base_name = file_name.trim_end_matches(DISABLED_EXTENSION);
-                if base_name.starts_with(&item.name) {
+            if let Some(base_name) = file_name.strip_suffix(DISABLED_EXTENSION) {
+                if item_name(&file_name).0 == item.name {
                     // Rename to remove .disabled extension
                     let new_path = source_location.join(base_name);
                     fs::rename(&path, &new_path).map_err(Error::Io)?;
@@ -151,14 +168,9 @@ impl StartupSource for StartupFolderScanner {
             let path = entry.path();
             let file_name = path.file_name().unwrap_or_default().to_string_lossy();
 
-            // Skip already disabled files
-            if file_name.ends_with(DISABLED_EXTENSION) {
-                continue;
-            }
-
-            // Check if this is our item (match by stem)
-            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
-            if stem == item.name {
+            // Check if this is our item, skipping already disabled files
+            let (name, disabled) = item_name(&file_name);
+            if !disabled && name == item.name {
                 // Rename to add .disabled extension
                 let new_name = format!("{file_name}{DISABLED_EXTENSION}");
                 let new_path = source_location.join(new_name);
@@ -172,6 +184,106 @@ impl StartupSource for StartupFolderScanner {
         })
     }
 
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        // The command is the path of the file itself
+        let path = Path::new(&item.command);
+        let Some(file_name) = path.file_name() else {
+            return Ok(None);
+        };
+
+        let data = fs::read(path).map_err(Error::Io)?;
+        let file_name = file_name.to_string_lossy();
+        Ok(Some(RawPayload::File {
+            file_name: file_name.trim_end_matches(DISABLED_EXTENSION).to_string(),
+            data,
+        }))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let Some(RawPayload::File { file_name, data }) = raw else {
+            return Err(Error::RestoreFailed {
+                reason: format!("the backup has no copy of {}", item.name),
+            });
+        };
+
+        // Only ever write into the folder itself
+        let file_name = Path::new(file_name)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| Error::InvalidPath(file_name.clone()))?;
+        let file_name = if item.status.is_disabled() {
+            format!("{file_name}{DISABLED_EXTENSION}")
+        } else {
+            file_name
+        };
+
+        let folder = PathBuf::from(&item.source_location);
+        fs::create_dir_all(&folder).map_err(Error::Io)?;
+        fs::write(folder.join(file_name), data).map_err(Error::Io)
+    }
+
+    fn edit(&self, item: &StartupItem, name: &str, command: &str) -> Result<()> {
+        // The command is the path of the file itself, renamed or not
+        let path = PathBuf::from(&item.command);
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| Error::InvalidPath(item.command.clone()))?;
+        let (base, suffix) = match file_name.strip_suffix(DISABLED_EXTENSION) {
+            Some(base) => (base.to_string(), DISABLED_EXTENSION),
+            None => (file_name.clone(), ""),
+        };
+        let extension = Path::new(&base)
+            .extension()
+            .map(|e| format!(".{}", e.to_string_lossy()))
+            .unwrap_or_default();
+
+        if command != item.command {
+            // Only shortcuts have a target to change
+            if !extension.eq_ignore_ascii_case(".lnk") {
+                return Err(Error::Unsupported(format!(
+                    "changing the command of {base}, which is not a shortcut"
+                )));
+            }
+
+            // Split where the edit was validated; shortcuts need the
+            // program as a full path
+            let resolver = PathResolver::current();
+            let command = resolver.split_command(command);
+            let program = resolver
+                .resolve_program(&command.program)
+                .to_string_lossy()
+                .to_string();
+            let working_dir = Path::new(&program)
+                .parent()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let data = fs::read(&path).map_err(Error::Io)?;
+            let data = Shortcut::new(program)
+                .with_arguments(command.raw_args)
+                .with_working_dir(working_dir)
+                .retarget(&data)
+                .ok_or_else(|| {
+                    Error::Unsupported(format!(
+                        "changing the command of {base}, which is not a valid shortcut"
+                    ))
+                })?;
+            fs::write(&path, data).map_err(Error::Io)?;
+        }
+
+        let new_path = path.with_file_name(format!("{name}{extension}{suffix}"));
+        if new_path != path {
+            fs::rename(&path, &new_path).map_err(Error::Io)?;
+        }
+        Ok(())
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        // The command is the path of the file itself, renamed or not
+        fs::remove_file(&item.command).map_err(Error::Io)
+    }
+
     fn source_types(&self) -> Vec<SourceType> {
         vec![SourceType::StartupFolderUser, SourceType::StartupFolderAllUsers]
     }
@@ -182,3 +294,20 @@ impl Default for StartupFolderScanner {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enabled_and_disabled_files_share_a_name() {
+        assert_eq!(item_name("App.lnk"), ("App".to_string(), false));
+        assert_eq!(item_name("App.lnk.disabled"), ("App".to_string(), true));
+        assert_eq!(item_name("run me.bat.disabled"), ("run me".to_string(), true));
+    }
+
+    #[test]
+    fn similar_names_stay_apart() {
+        assert_ne!(item_name("App Helper.lnk.disabled").0, item_name("App.lnk").0);
+    }
+}
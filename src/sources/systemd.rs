@@ -0,0 +1,797 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+use super::ini;
+use super::{capture_file, write_captured_file, StartupSource};
+
+/// System unit search path, most important first
+const SYSTEM_UNIT_PATHS: &[&str] = &[
+    "/etc/systemd/system",
+    "/run/systemd/system",
+    "/usr/local/lib/systemd/system",
+    "/usr/lib/systemd/system",
+    "/lib/systemd/system",
+];
+
+/// System-wide part of the user unit search path
+const USER_UNIT_PATHS: &[&str] = &[
+    "/etc/systemd/user",
+    "/run/systemd/user",
+    "/usr/local/lib/systemd/user",
+    "/usr/lib/systemd/user",
+];
+
+/// Where `systemctl enable` writes links for system units
+const SYSTEM_CONFIG_DIR: &str = "/etc/systemd/system";
+
+/// Directories where a link to `/dev/null` masks a system unit
+const SYSTEM_MASK_PATHS: &[&str] = &["/etc/systemd/system", "/run/systemd/system"];
+
+/// Directories besides the user's own config where a user unit can be masked
+const USER_MASK_PATHS: &[&str] = &["/etc/systemd/user", "/run/systemd/user"];
+
+/// The parts of a unit file that matter for startup
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnitFile {
+    pub description: Option<String>,
+    pub exec_start: Vec<String>,
+    pub wanted_by: Vec<String>,
+    pub required_by: Vec<String>,
+    pub alias: Vec<String>,
+    pub default_instance: Option<String>,
+}
+
+impl UnitFile {
+    pub fn parse(content: &str) -> Self {
+        let mut unit = Self::default();
+
+        // Lines ending in a backslash continue on the next line
+        let mut joined = String::with_capacity(content.len());
+        for line in content.lines() {
+            match line.strip_suffix('\\') {
+                Some(continued) => {
+                    joined.push_str(continued.trim());
+                    joined.push(' ');
+                }
+                None => {
+                    joined.push_str(line.trim_start());
+                    joined.push('\n');
+                }
+            }
+        }
+
+        for entry in ini::parse(&joined) {
+            let value = entry.value.trim();
+            match (entry.section.as_str(), entry.key.as_str()) {
+                ("Unit", "Description") => unit.description = Some(value.to_string()),
+                ("Service", "ExecStart") => {
+                    // An empty assignment resets the list
+                    if value.is_empty() {
+                        unit.exec_start.clear();
+                    } else {
+                        unit.exec_start.push(strip_exec_prefixes(value).to_string());
+                    }
+                }
+                ("Install", "WantedBy") => unit.wanted_by.extend(split_words(value)),
+                ("Install", "RequiredBy") => unit.required_by.extend(split_words(value)),
+                ("Install", "Alias") => unit.alias.extend(split_words(value)),
+                ("Install", "DefaultInstance") => unit.default_instance = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        unit
+    }
+
+    /// Units without an `[Install]` section are static and cannot be enabled
+    pub fn is_installable(&self) -> bool {
+        !self.wanted_by.is_empty() || !self.required_by.is_empty() || !self.alias.is_empty()
+    }
+
+    /// `(target, link directory suffix)` pairs created by `systemctl enable`
+    fn install_links(&self) -> Vec<(String, &'static str)> {
+        self.wanted_by
+            .iter()
+            .map(|t| (t.clone(), "wants"))
+            .chain(self.required_by.iter().map(|t| (t.clone(), "requires")))
+            .collect()
+    }
+
+    /// The name `systemctl enable` links a unit under: templates are linked
+    /// as their `DefaultInstance`
+    pub fn instance_name(&self, unit: &str) -> String {
+        match (&self.default_instance, unit.split_once("@.")) {
+            (Some(instance), Some((prefix, suffix))) => format!("{prefix}@{instance}.{suffix}"),
+            _ => unit.to_string(),
+        }
+    }
+}
+
+fn split_words(value: &str) -> impl Iterator<Item = String> + '_ {
+    value.split_whitespace().map(str::to_string)
+}
+
+/// Remove the `-`, `@`, `:`, `+` and `!` prefixes that modify how `ExecStart` runs
+fn strip_exec_prefixes(exec: &str) -> &str {
+    exec.trim_start_matches(['-', '@', ':', '+', '!'])
+}
+
+/// Map an instance name such as `getty@tty1.service` to its template `getty@.service`
+pub fn template_name(unit: &str) -> Option<String> {
+    let (prefix, rest) = unit.split_once('@')?;
+    let (_, suffix) = rest.rsplit_once('.')?;
+    Some(format!("{prefix}@.{suffix}"))
+}
+
+/// How a unit is currently hooked into boot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UnitState {
+    Enabled,
+    Disabled,
+    Masked,
+}
+
+pub struct SystemdScanner {
+    root: PathBuf,
+    home: Option<PathBuf>,
+}
+
+impl SystemdScanner {
+    pub fn new() -> Self {
+        Self {
+            root: PathBuf::from("/"),
+            home: dirs::home_dir(),
+        }
+    }
+
+    /// Scan unit files below a different root directory, like `systemctl --root`.
+    /// `home` is the real path of the user's home directory, e.g. inside the root.
+    pub fn with_root(root: PathBuf, home: Option<PathBuf>) -> Self {
+        Self { root, home }
+    }
+
+    fn under_root(&self, path: &str) -> PathBuf {
+        self.root.join(path.trim_start_matches('/'))
+    }
+
+    /// Directory where enable links are created for a scope
+    fn config_dir(&self, source: SourceType) -> Option<PathBuf> {
+        match source {
+            SourceType::SystemdUser => self
+                .home
+                .as_ref()
+                .map(|h| h.join(".config").join("systemd").join("user")),
+            _ => Some(self.under_root(SYSTEM_CONFIG_DIR)),
+        }
+    }
+
+    fn search_path(&self, source: SourceType) -> Vec<PathBuf> {
+        match source {
+            SourceType::SystemdUser => {
+                let mut paths = Vec::new();
+                if let Some(config) = self.config_dir(source) {
+                    paths.push(config);
+                }
+                paths.extend(USER_UNIT_PATHS.iter().map(|p| self.under_root(p)));
+                if let Some(home) = &self.home {
+                    paths.push(
+                        home.join(".local")
+                            .join("share")
+                            .join("systemd")
+                            .join("user"),
+                    );
+                }
+                paths
+            }
+            _ => SYSTEM_UNIT_PATHS
+                .iter()
+                .map(|p| self.under_root(p))
+                .collect(),
+        }
+    }
+
+    /// Unit file names mapped to the first file found in the search path
+    fn unit_files(&self, source: SourceType) -> HashMap<String, PathBuf> {
+        let mut units = HashMap::new();
+
+        for dir in self.search_path(source) {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // Mask links hide the unit but the vendor file still describes it
+                if name.ends_with(".service") && !Self::is_masked(&entry.path()) {
+                    units.entry(name).or_insert_with(|| entry.path());
+                }
+            }
+        }
+
+        units
+    }
+
+    /// Resolve a symlink target the way it would be seen from inside the root
+    fn resolve_link(&self, link: &Path) -> Option<PathBuf> {
+        let target = fs::read_link(link).ok()?;
+        if target.is_absolute() {
+            Some(self.under_root(&target.to_string_lossy()))
+        } else {
+            link.parent().map(|p| p.join(target))
+        }
+    }
+
+    fn is_masked(path: &Path) -> bool {
+        fs::read_link(path).is_ok_and(|t| t == Path::new("/dev/null"))
+            || fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() == 0)
+    }
+
+    /// Unit names linked from the `*.wants`/`*.requires` directories in `dir`
+    fn links_in(dir: &Path) -> Vec<String> {
+        let mut linked = Vec::new();
+
+        let Ok(entries) = fs::read_dir(dir) else {
+            return linked;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !(name.ends_with(".wants") || name.ends_with(".requires")) {
+                continue;
+            }
+            let Ok(links) = fs::read_dir(entry.path()) else {
+                continue;
+            };
+            for link in links.flatten() {
+                let unit = link.file_name().to_string_lossy().to_string();
+                if unit.ends_with(".service") && !linked.contains(&unit) {
+                    linked.push(unit);
+                }
+            }
+        }
+
+        linked
+    }
+
+    /// Unit names that have a link in any `*.wants`/`*.requires` directory on the search path
+    fn linked_units(&self, source: SourceType) -> Vec<String> {
+        let mut linked = Vec::new();
+
+        for dir in self.search_path(source) {
+            for unit in Self::links_in(&dir) {
+                if !linked.contains(&unit) {
+                    linked.push(unit);
+                }
+            }
+        }
+
+        linked
+    }
+
+    /// Whether a link outside the config dir, such as one shipped in
+    /// `/usr/lib/systemd`, starts the unit. Only a mask can stop those.
+    fn linked_by_vendor(&self, source: SourceType, link_name: &str) -> bool {
+        let config_dir = self.config_dir(source);
+        self.search_path(source)
+            .iter()
+            .filter(|dir| Some(*dir) != config_dir.as_ref())
+            .any(|dir| Self::links_in(dir).iter().any(|l| l == link_name))
+    }
+
+    /// The config and runtime directories of a scope, the only ones that can mask
+    fn mask_dirs(&self, source: SourceType) -> Vec<PathBuf> {
+        match source {
+            SourceType::SystemdUser => self
+                .config_dir(source)
+                .into_iter()
+                .chain(USER_MASK_PATHS.iter().map(|p| self.under_root(p)))
+                .collect(),
+            _ => SYSTEM_MASK_PATHS
+                .iter()
+                .map(|p| self.under_root(p))
+                .collect(),
+        }
+    }
+
+    /// `link_name` is the name enable links use, see [`UnitFile::instance_name`]
+    fn unit_state(
+        &self,
+        source: SourceType,
+        unit: &str,
+        link_name: &str,
+        linked: &[String],
+    ) -> UnitState {
+        let masked = self
+            .mask_dirs(source)
+            .iter()
+            .any(|dir| Self::is_masked(&dir.join(unit)));
+
+        if masked {
+            UnitState::Masked
+        } else if linked.iter().any(|l| l == link_name) {
+            UnitState::Enabled
+        } else {
+            UnitState::Disabled
+        }
+    }
+
+    fn scan_scope(&self, source: SourceType) -> Vec<StartupItem> {
+        let files = self.unit_files(source);
+        let linked = self.linked_units(source);
+        let mut items = Vec::new();
+
+        // Instances linked as their template's DefaultInstance are reported as the template
+        let default_instances: Vec<String> = files
+            .iter()
+            .filter(|(name, _)| name.contains("@."))
+            .filter_map(|(name, path)| {
+                let unit = UnitFile::parse(&fs::read_to_string(path).ok()?);
+                unit.default_instance
+                    .is_some()
+                    .then(|| unit.instance_name(name))
+            })
+            .collect();
+
+        // Installable units, plus instances of templates that are linked somewhere
+        let mut names: Vec<String> = files.keys().cloned().collect();
+        names.extend(
+            linked
+                .iter()
+                .filter(|u| {
+                    !files.contains_key(*u)
+                        && !default_instances.contains(u)
+                        && template_name(u).is_some()
+                })
+                .cloned(),
+        );
+        names.sort();
+
+        for name in names {
+            let path = files
+                .get(&name)
+                .cloned()
+                .or_else(|| template_name(&name).and_then(|t| files.get(&t).cloned()));
+            let Some(path) = path else {
+                continue;
+            };
+
+            let content = fs::read_to_string(&path).unwrap_or_default();
+            let unit = UnitFile::parse(&content);
+
+            let link_name = unit.instance_name(&name);
+            let state = self.unit_state(source, &name, &link_name, &linked);
+
+            // Templates are only reported through their instances
+            if name.contains("@.") && unit.default_instance.is_none() {
+                continue;
+            }
+            // Static units show up while the vendor starts them, or would
+            let vendor_masked =
+                state == UnitState::Masked && self.linked_by_vendor(source, &link_name);
+            if !unit.is_installable() && state != UnitState::Enabled && !vendor_masked {
+                continue;
+            }
+
+            let command = unit
+                .exec_start
+                .first()
+                .cloned()
+                .unwrap_or_else(|| path.display().to_string());
+
+            let mut notes = Vec::new();
+            if let Some(description) = &unit.description {
+                notes.push(description.clone());
+            }
+            match state {
+                UnitState::Masked => notes.push("Masked".to_string()),
+                _ if !unit.is_installable() => {
+                    notes.push("Static unit, enabled by the vendor".to_string())
+                }
+                _ if !unit.wanted_by.is_empty() => {
+                    notes.push(format!("WantedBy={}", unit.wanted_by.join(" ")))
+                }
+                _ => {}
+            }
+
+            let status = match state {
+                UnitState::Enabled => ItemStatus::Enabled,
+                UnitState::Disabled | UnitState::Masked => ItemStatus::Disabled,
+            };
+
+            let mut item = StartupItem::new(name, source, path.display().to_string(), command)
+                .with_status(status)
+                .with_description((!notes.is_empty()).then(|| notes.join(". ")));
+
+            // ExecStart paths are absolute inside the root
+            if let Some(program) = unit
+                .exec_start
+                .first()
+                .and_then(|e| e.split_whitespace().next())
+            {
+                let program = self.under_root(program);
+                item.file_exists = program.exists();
+                item.executable_path = Some(program);
+            }
+
+            items.push(item);
+        }
+
+        items
+    }
+
+    /// Re-read the unit file behind an item (the template file for instances)
+    fn read_unit(&self, item: &StartupItem) -> Result<UnitFile> {
+        let content = fs::read_to_string(&item.source_location).map_err(Error::Io)?;
+        Ok(UnitFile::parse(&content))
+    }
+
+    /// The path written into enable links, as seen from inside the root
+    fn link_target(&self, item: &StartupItem) -> PathBuf {
+        let path = Path::new(&item.source_location);
+        match path.strip_prefix(&self.root) {
+            Ok(relative) => Path::new("/").join(relative),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+
+    fn unmask(&self, item: &StartupItem) -> Result<()> {
+        for dir in self.mask_dirs(item.source) {
+            let path = dir.join(&item.name);
+            if Self::is_masked(&path) {
+                fs::remove_file(&path).map_err(Error::Io)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    fn symlink(target: &Path, link: &Path) -> Result<()> {
+        std::os::unix::fs::symlink(target, link).map_err(Error::Io)
+    }
+
+    #[cfg(not(unix))]
+    fn symlink(_target: &Path, _link: &Path) -> Result<()> {
+        Err(Error::Unsupported("systemd unit links".to_string()))
+    }
+}
+
+impl StartupSource for SystemdScanner {
+    fn scan(&self) -> Result<Vec<StartupItem>> {
+        let mut items = self.scan_scope(SourceType::SystemdSystem);
+        items.extend(self.scan_scope(SourceType::SystemdUser));
+        Ok(items)
+    }
+
+    fn enable(&self, item: &StartupItem) -> Result<()> {
+        let config_dir = self
+            .config_dir(item.source)
+            .ok_or_else(|| Error::InvalidPath("no home directory for user units".to_string()))?;
+        let unit = self.read_unit(item)?;
+        let link_name = unit.instance_name(&item.name);
+        let linked_by_vendor = self.linked_by_vendor(item.source, &link_name);
+
+        if !unit.is_installable() && !linked_by_vendor {
+            return Err(Error::PermissionDenied {
+                message: format!("{} has no [Install] section", item.name),
+            });
+        }
+
+        self.unmask(item)?;
+
+        // The vendor's links start it again once the mask is gone
+        if linked_by_vendor {
+            return Ok(());
+        }
+
+        let target = self.link_target(item);
+        let mut links: Vec<PathBuf> = unit
+            .install_links()
+            .into_iter()
+            .map(|(t, suffix)| config_dir.join(format!("{t}.{suffix}")).join(&link_name))
+            .collect();
+        links.extend(unit.alias.iter().map(|a| config_dir.join(a)));
+
+        for link in links {
+            if fs::symlink_metadata(&link).is_ok() {
+                continue;
+            }
+            if let Some(parent) = link.parent() {
+                fs::create_dir_all(parent).map_err(Error::Io)?;
+            }
+            Self::symlink(&target, &link)?;
+        }
+
+        Ok(())
+    }
+
+    fn disable(&self, item: &StartupItem) -> Result<()> {
+        let config_dir = self
+            .config_dir(item.source)
+            .ok_or_else(|| Error::InvalidPath("no home directory for user units".to_string()))?;
+        let unit = self.read_unit(item).unwrap_or_default();
+        let target_name = Path::new(&item.source_location).file_name();
+        let link_name = unit.instance_name(&item.name);
+
+        // Remove every link in the config dir that points at this unit
+        let entries = fs::read_dir(&config_dir).into_iter().flatten();
+        let mut removed = false;
+        for entry in entries.flatten() {
+            let dir_name = entry.file_name().to_string_lossy().to_string();
+            if dir_name.ends_with(".wants") || dir_name.ends_with(".requires") {
+                let link = entry.path().join(&link_name);
+                if fs::symlink_metadata(&link).is_ok() {
+                    fs::remove_file(&link).map_err(Error::Io)?;
+                    removed = true;
+                }
+            }
+        }
+
+        for alias in &unit.alias {
+            let link = config_dir.join(alias);
+            let points_here = self
+                .resolve_link(&link)
+                .is_some_and(|t| t.file_name() == target_name);
+            if points_here {
+                fs::remove_file(&link).map_err(Error::Io)?;
+                removed = true;
+            }
+        }
+
+        // Links in vendor directories cannot be removed, so mask the unit
+        // in the config dir like `systemctl mask`
+        if self.linked_by_vendor(item.source, &link_name) {
+            let mask = config_dir.join(&item.name);
+            if fs::symlink_metadata(&mask).is_ok() {
+                return Err(Error::PermissionDenied {
+                    message: format!("{} cannot be masked over {}", item.name, mask.display()),
+                });
+            }
+            fs::create_dir_all(&config_dir).map_err(Error::Io)?;
+            return Self::symlink(Path::new("/dev/null"), &mask);
+        }
+
+        if removed {
+            Ok(())
+        } else {
+            Err(Error::PermissionDenied {
+                message: format!(
+                    "{} is not enabled through {}",
+                    item.name,
+                    config_dir.display()
+                ),
+            })
+        }
+    }
+
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        capture_file(Path::new(&item.source_location))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        write_captured_file(item, Path::new(&item.source_location), raw)?;
+        if item.status.is_enabled() {
+            self.enable(item)?;
+        }
+        Ok(())
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        let path = Path::new(&item.source_location);
+
+        // Instances share their template's file with every other instance
+        if path
+            .file_name()
+            .is_some_and(|name| name != item.name.as_str())
+        {
+            return Err(Error::PermissionDenied {
+                message: format!(
+                    "{} is an instance of {}; disable it instead",
+                    item.name,
+                    path.display()
+                ),
+            });
+        }
+
+        if item.status.is_enabled() {
+            self.disable(item)?;
+        }
+        fs::remove_file(path).map_err(Error::Io)
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![SourceType::SystemdSystem, SourceType::SystemdUser]
+    }
+}
+
+impl Default for SystemdScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::symlink;
+
+    use super::*;
+    use crate::sources::fake_root;
+
+    fn write_unit(dir: &Path, name: &str, content: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(name), content).unwrap();
+    }
+
+    fn find<'a>(items: &'a [StartupItem], name: &str) -> &'a StartupItem {
+        items.iter().find(|i| i.name == name).unwrap()
+    }
+
+    #[test]
+    fn parses_exec_start_and_install() {
+        let unit = UnitFile::parse(
+            "[Unit]\nDescription=Demo\n[Service]\nExecStart=-/usr/bin/demo \\\n  --flag\n\
+             [Install]\nWantedBy=multi-user.target graphical.target\nDefaultInstance=tty1\n",
+        );
+
+        assert_eq!(unit.description.as_deref(), Some("Demo"));
+        assert_eq!(unit.exec_start, ["/usr/bin/demo --flag"]);
+        assert_eq!(unit.wanted_by, ["multi-user.target", "graphical.target"]);
+        assert!(unit.is_installable());
+        assert_eq!(unit.instance_name("getty@.service"), "getty@tty1.service");
+        assert_eq!(
+            unit.instance_name("getty@tty2.service"),
+            "getty@tty2.service"
+        );
+        assert_eq!(
+            template_name("getty@tty2.service").as_deref(),
+            Some("getty@.service")
+        );
+    }
+
+    #[test]
+    fn enables_and_disables_through_wants_links() {
+        let root = fake_root("systemd-enable");
+        let vendor = root.join("usr/lib/systemd/system");
+        write_unit(
+            &vendor,
+            "demo.service",
+            "[Service]\nExecStart=/usr/bin/demo\n[Install]\nWantedBy=multi-user.target\n",
+        );
+        let scanner = SystemdScanner::with_root(root.clone(), None);
+
+        let items = scanner.scan().unwrap();
+        let item = find(&items, "demo.service");
+        assert_eq!(item.status, ItemStatus::Disabled);
+
+        scanner.enable(item).unwrap();
+        let link = root.join("etc/systemd/system/multi-user.target.wants/demo.service");
+        assert_eq!(
+            fs::read_link(&link).unwrap(),
+            Path::new("/usr/lib/systemd/system/demo.service")
+        );
+        let items = scanner.scan().unwrap();
+        assert_eq!(find(&items, "demo.service").status, ItemStatus::Enabled);
+
+        scanner.disable(find(&items, "demo.service")).unwrap();
+        assert!(fs::symlink_metadata(&link).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn templates_are_linked_as_their_default_instance() {
+        let root = fake_root("systemd-template");
+        write_unit(
+            &root.join("usr/lib/systemd/system"),
+            "worker@.service",
+            "[Service]\nExecStart=/usr/bin/worker %i\n\
+             [Install]\nWantedBy=multi-user.target\nDefaultInstance=main\n",
+        );
+        let scanner = SystemdScanner::with_root(root.clone(), None);
+
+        let items = scanner.scan().unwrap();
+        scanner.enable(find(&items, "worker@.service")).unwrap();
+        let wants = root.join("etc/systemd/system/multi-user.target.wants");
+        assert!(fs::symlink_metadata(wants.join("worker@main.service")).is_ok());
+        assert!(fs::symlink_metadata(wants.join("worker@.service")).is_err());
+
+        // Reported once, as the template
+        let items = scanner.scan().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(find(&items, "worker@.service").status, ItemStatus::Enabled);
+
+        scanner.disable(&items[0]).unwrap();
+        assert!(fs::symlink_metadata(wants.join("worker@main.service")).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn runtime_masked_user_units_are_masked() {
+        let root = fake_root("systemd-mask");
+        let home = root.join("home/user");
+        write_unit(
+            &root.join("usr/lib/systemd/user"),
+            "agent.service",
+            "[Service]\nExecStart=/usr/bin/agent\n[Install]\nWantedBy=default.target\n",
+        );
+        let runtime = root.join("run/systemd/user");
+        fs::create_dir_all(&runtime).unwrap();
+        symlink("/dev/null", runtime.join("agent.service")).unwrap();
+        let scanner = SystemdScanner::with_root(root.clone(), Some(home.clone()));
+
+        let items = scanner.scan().unwrap();
+        let item = find(&items, "agent.service");
+        assert_eq!(item.source, SourceType::SystemdUser);
+        assert!(item.description.as_deref().unwrap().contains("Masked"));
+
+        scanner.enable(item).unwrap();
+        assert!(fs::symlink_metadata(runtime.join("agent.service")).is_err());
+        assert!(fs::symlink_metadata(
+            home.join(".config/systemd/user/default.target.wants/agent.service")
+        )
+        .is_ok());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn static_units_are_skipped_unless_linked() {
+        let root = fake_root("systemd-static");
+        write_unit(
+            &root.join("usr/lib/systemd/system"),
+            "helper.service",
+            "[Service]\nExecStart=/usr/bin/helper\n",
+        );
+        let scanner = SystemdScanner::with_root(root.clone(), None);
+        assert!(scanner.scan().unwrap().is_empty());
+
+        let wants = root.join("usr/lib/systemd/system/sysinit.target.wants");
+        fs::create_dir_all(&wants).unwrap();
+        symlink("../helper.service", wants.join("helper.service")).unwrap();
+        let items = scanner.scan().unwrap();
+        assert_eq!(find(&items, "helper.service").status, ItemStatus::Enabled);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn vendor_linked_units_are_disabled_by_masking() {
+        let root = fake_root("systemd-vendor");
+        let vendor = root.join("usr/lib/systemd/system");
+        write_unit(
+            &vendor,
+            "helper.service",
+            "[Service]\nExecStart=/usr/bin/helper\n",
+        );
+        write_unit(
+            &vendor,
+            "daemon.service",
+            "[Service]\nExecStart=/usr/bin/daemon\n[Install]\nWantedBy=multi-user.target\n",
+        );
+        let wants = vendor.join("multi-user.target.wants");
+        fs::create_dir_all(&wants).unwrap();
+        symlink("../helper.service", wants.join("helper.service")).unwrap();
+        symlink("../daemon.service", wants.join("daemon.service")).unwrap();
+        let scanner = SystemdScanner::with_root(root.clone(), None);
+
+        for name in ["helper.service", "daemon.service"] {
+            let items = scanner.scan().unwrap();
+            scanner.disable(find(&items, name)).unwrap();
+
+            let mask = root.join("etc/systemd/system").join(name);
+            assert_eq!(fs::read_link(&mask).unwrap(), Path::new("/dev/null"));
+            assert!(wants.join(name).exists());
+            let items = scanner.scan().unwrap();
+            assert_eq!(find(&items, name).status, ItemStatus::Disabled);
+
+            scanner.enable(find(&items, name)).unwrap();
+            assert!(fs::symlink_metadata(&mask).is_err());
+            let items = scanner.scan().unwrap();
+            assert_eq!(find(&items, name).status, ItemStatus::Enabled);
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,544 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+use super::comment_marker::{
+    insert_line, line_at, line_for_status, pick_line, remove_line, set_line_enabled, uncomment,
+};
+use super::ini::{self, IniEntry};
+use super::StartupSource;
+
+/// Name used for items coming from `%USERPROFILE%\.wslconfig`
+const WSLCONFIG_ITEM_PREFIX: &str = "WSL2 VM";
+
+/// `.wslconfig` keys that make the VM load something at boot
+const WSLCONFIG_KEYS: &[&str] = &["kernel", "kernelModules"];
+
+/// Share that serves the file systems of running WSL2 distributions
+const WSL2_SHARE: &str = r"\\wsl.localhost\";
+
+/// A registered WSL distribution
+#[derive(Debug, Clone)]
+pub struct WslDistribution {
+    pub name: String,
+    pub base_path: PathBuf,
+    pub wsl2: bool,
+}
+
+impl WslDistribution {
+    /// Where `/etc/wsl.conf` can be read from the Windows side: the `rootfs`
+    /// directory for WSL1, the `\\wsl.localhost` share of the ext4.vhdx for WSL2.
+    /// Opening the share boots a stopped WSL2 distribution, so only read it
+    /// while the distribution [is running](running_distributions).
+    pub fn wsl_conf_path(&self) -> PathBuf {
+        if self.wsl2 {
+            PathBuf::from(format!(r"{WSL2_SHARE}{}\etc\wsl.conf", self.name))
+        } else {
+            self.base_path.join("rootfs").join("etc").join("wsl.conf")
+        }
+    }
+}
+
+/// The WSL2 distribution a path on the `\\wsl.localhost` share belongs to
+fn wsl2_distribution(path: &str) -> Option<&str> {
+    let rest = path
+        .get(..WSL2_SHARE.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(WSL2_SHARE))
+        .map(|_| &path[WSL2_SHARE.len()..])?;
+    rest.split('\\').next().filter(|name| !name.is_empty())
+}
+
+/// Distribution names printed by `wsl.exe --list --quiet`, which writes
+/// UTF-16 unless `WSL_UTF8` is set
+fn parse_distribution_list(output: &[u8]) -> Vec<String> {
+    let utf16 = output.len().is_multiple_of(2) && output.chunks(2).any(|pair| pair[1] == 0);
+    let text = if utf16 {
+        let units: Vec<u16> = output
+            .chunks(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else {
+        String::from_utf8_lossy(output).to_string()
+    };
+
+    text.lines()
+        .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\u{feff}'))
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Names of the WSL distributions that are running right now
+fn running_distributions() -> Vec<String> {
+    Command::new("wsl.exe")
+        .args(["--list", "--running", "--quiet"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_distribution_list(&output.stdout))
+        .unwrap_or_default()
+}
+
+/// `[boot] command=` entries from a `wsl.conf`
+pub fn boot_commands(content: &str) -> Vec<IniEntry> {
+    ini::parse(content)
+        .into_iter()
+        .filter(|e| e.is("boot", "command") && !e.unquoted_value().is_empty())
+        .collect()
+}
+
+/// `[wsl2]` entries from `.wslconfig` that load files when the VM boots
+pub fn wslconfig_boot_entries(content: &str) -> Vec<IniEntry> {
+    ini::parse(content)
+        .into_iter()
+        .filter(|e| {
+            WSLCONFIG_KEYS.iter().any(|key| e.is("wsl2", key)) && !e.unquoted_value().is_empty()
+        })
+        .collect()
+}
+
+pub struct WslScanner;
+
+impl WslScanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    #[cfg(windows)]
+    fn distributions(&self) -> Vec<WslDistribution> {
+        use windows::Win32::System::Registry::HKEY_CURRENT_USER;
+
+        use super::RegistryScanner;
+
+        const LXSS_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Lxss";
+        const VM_MODE_FLAG: u32 = 0x8;
+
+        let registry = RegistryScanner::new();
+        let mut distributions = Vec::new();
+
+        for guid in registry.enumerate_subkeys(HKEY_CURRENT_USER, LXSS_KEY) {
+            let key = format!("{LXSS_KEY}\\{guid}");
+
+            let Some(name) = registry.read_value(HKEY_CURRENT_USER, &key, "DistributionName")
+            else {
+                continue;
+            };
+            let Some(base_path) = registry.read_value(HKEY_CURRENT_USER, &key, "BasePath") else {
+                continue;
+            };
+
+            // Some installers store BasePath with the \\?\ prefix
+            let base_path = PathBuf::from(base_path.trim_start_matches(r"\\?\"));
+            let flags = registry
+                .read_dword(HKEY_CURRENT_USER, &key, "Flags")
+                .unwrap_or(0);
+            let wsl2 = flags & VM_MODE_FLAG != 0 || base_path.join("ext4.vhdx").exists();
+
+            distributions.push(WslDistribution {
+                name,
+                base_path,
+                wsl2,
+            });
+        }
+
+        distributions
+    }
+
+    #[cfg(not(windows))]
+    fn distributions(&self) -> Vec<WslDistribution> {
+        Vec::new()
+    }
+
+    fn wslconfig_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|h| h.join(".wslconfig"))
+    }
+
+    fn scan_distribution(&self, distribution: &WslDistribution, running: bool) -> Vec<StartupItem> {
+        let path = distribution.wsl_conf_path();
+
+        // Reading a stopped WSL2 distribution would boot it
+        if distribution.wsl2 && !running {
+            let mut item = StartupItem::new(
+                distribution.name.clone(),
+                SourceType::Wsl,
+                path.display().to_string(),
+                String::new(),
+            )
+            .with_status(ItemStatus::Unknown)
+            .with_description(Some(
+                "WSL2 distribution is not running; its boot commands are read once it is"
+                    .to_string(),
+            ));
+            item.executable_path = None;
+            item.file_exists = true;
+            return vec![item];
+        }
+
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Vec::new();
+        };
+
+        let version = if distribution.wsl2 { "WSL2" } else { "WSL1" };
+
+        boot_commands(&content)
+            .into_iter()
+            .map(|entry| {
+                let mut item = StartupItem::new(
+                    distribution.name.clone(),
+                    SourceType::Wsl,
+                    path.display().to_string(),
+                    entry.unquoted_value().to_string(),
+                )
+                .with_status(if entry.enabled {
+                    ItemStatus::Enabled
+                } else {
+                    ItemStatus::Disabled
+                })
+                .with_line(entry.line)
+                .with_description(Some(format!(
+                    "{version} boot command, runs as root when the distribution starts"
+                )));
+
+                // The command lives inside the distribution, it cannot be resolved from Windows
+                item.executable_path = None;
+                item.file_exists = true;
+                item
+            })
+            .collect()
+    }
+
+    fn scan_wslconfig(&self) -> Vec<StartupItem> {
+        let Some(path) = Self::wslconfig_path() else {
+            return Vec::new();
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Vec::new();
+        };
+
+        wslconfig_boot_entries(&content)
+            .into_iter()
+            .map(|entry| {
+                // .wslconfig paths are written with escaped backslashes
+                let value = entry.unquoted_value().replace(r"\\", r"\");
+                StartupItem::new(
+                    format!("{WSLCONFIG_ITEM_PREFIX} {}", entry.key),
+                    SourceType::Wsl,
+                    path.display().to_string(),
+                    value,
+                )
+                .with_status(if entry.enabled {
+                    ItemStatus::Enabled
+                } else {
+                    ItemStatus::Disabled
+                })
+                .with_line(entry.line)
+                .with_description(Some("Loaded when the WSL2 VM boots".to_string()))
+            })
+            .collect()
+    }
+
+    /// Find the line backing `item` in the file content, preferring the
+    /// line it was scanned from when the same command is there twice
+    fn find_entry(item: &StartupItem, content: &str) -> Option<IniEntry> {
+        let entries: Vec<IniEntry> = if item.name.starts_with(WSLCONFIG_ITEM_PREFIX) {
+            wslconfig_boot_entries(content)
+                .into_iter()
+                .filter(|e| {
+                    format!("{WSLCONFIG_ITEM_PREFIX} {}", e.key) == item.name
+                        && e.unquoted_value().replace(r"\\", r"\") == item.command
+                })
+                .collect()
+        } else {
+            boot_commands(content)
+                .into_iter()
+                .filter(|e| e.unquoted_value() == item.command)
+                .collect()
+        };
+
+        let lines: Vec<usize> = entries.iter().map(|e| e.line).collect();
+        let line = pick_line(&lines, item.line)?;
+        entries.into_iter().find(|e| e.line == line)
+    }
+
+    /// Read a configuration file, refusing files of stopped WSL2
+    /// distributions rather than booting them
+    fn read_config(item: &StartupItem) -> Result<String> {
+        if let Some(name) = wsl2_distribution(&item.source_location) {
+            if !running_distributions()
+                .iter()
+                .any(|running| running.eq_ignore_ascii_case(name))
+            {
+                return Err(Error::Unsupported(format!(
+                    "changing {name} while it is not running"
+                )));
+            }
+        }
+        fs::read_to_string(&item.source_location).map_err(Error::Io)
+    }
+
+    /// WSL1 root filesystems must not be written from Windows (it loses the
+    /// Linux metadata), so go through `wsl.exe` instead
+    fn write_config(item: &StartupItem, path: &Path, content: &str) -> Result<()> {
+        let is_wsl1_rootfs = path
+            .components()
+            .any(|c| c.as_os_str().eq_ignore_ascii_case("rootfs"));
+
+        if !is_wsl1_rootfs {
+            return fs::write(path, content).map_err(Error::Io);
+        }
+
+        let mut child = Command::new("wsl.exe")
+            .args([
+                "-d",
+                &item.name,
+                "-u",
+                "root",
+                "--",
+                "sh",
+                "-c",
+                "cat > /etc/wsl.conf",
+            ])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(Error::Io)?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(content.as_bytes()).map_err(Error::Io)?;
+        }
+
+        let status = child.wait().map_err(Error::Io)?;
+        if !status.success() {
+            return Err(Error::PermissionDenied {
+                message: format!("wsl.exe could not write /etc/wsl.conf in {}", item.name),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn set_enabled(&self, item: &StartupItem, enabled: bool) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let content = Self::read_config(item)?;
+
+        let entry = Self::find_entry(item, &content).ok_or_else(|| Error::ItemNotFound {
+            id: item.id.clone(),
+        })?;
+
+        let updated =
+            set_line_enabled(&content, entry.line, enabled).ok_or_else(|| Error::ItemNotFound {
+                id: item.id.clone(),
+            })?;
+
+        Self::write_config(item, &path, &updated)
+    }
+
+    /// Section an item's key belongs in
+    fn section(item: &StartupItem) -> &'static str {
+        if item.name.starts_with(WSLCONFIG_ITEM_PREFIX) {
+            "wsl2"
+        } else {
+            "boot"
+        }
+    }
+}
+
+impl StartupSource for WslScanner {
+    fn scan(&self) -> Result<Vec<StartupItem>> {
+        let mut all_items = Vec::new();
+
+        let distributions = self.distributions();
+        let running = if distributions.iter().any(|d| d.wsl2) {
+            running_distributions()
+        } else {
+            Vec::new()
+        };
+
+        for distribution in distributions {
+            let is_running = running
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&distribution.name));
+            all_items.extend(self.scan_distribution(&distribution, is_running));
+        }
+
+        all_items.extend(self.scan_wslconfig());
+
+        Ok(all_items)
+    }
+
+    fn enable(&self, item: &StartupItem) -> Result<()> {
+        self.set_enabled(item, true)
+    }
+
+    fn disable(&self, item: &StartupItem) -> Result<()> {
+        self.set_enabled(item, false)
+    }
+
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        let content = Self::read_config(item)?;
+        Ok(Self::find_entry(item, &content)
+            .and_then(|entry| line_at(&content, entry.line))
+            .map(|line| RawPayload::Line {
+                line: uncomment(line),
+            }))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let Some(RawPayload::Line { line }) = raw else {
+            return Err(Error::RestoreFailed {
+                reason: format!("no configuration line was saved for {}", item.name),
+            });
+        };
+
+        let path = PathBuf::from(&item.source_location);
+        let mut content = match Self::read_config(item) {
+            Ok(content) => content,
+            Err(Error::Io(_)) => String::new(),
+            Err(e) => return Err(e),
+        };
+        let section = Self::section(item);
+        let index = match ini::section_end(&content, section) {
+            Some(index) => index,
+            None => {
+                content = insert_line(&content, usize::MAX, &format!("[{section}]"));
+                usize::MAX
+            }
+        };
+
+        let line = line_for_status(line, item.status.is_enabled());
+        Self::write_config(item, &path, &insert_line(&content, index, &line))
+    }
+
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        let path = PathBuf::from(&item.source_location);
+        let content = Self::read_config(item)?;
+
+        let updated = Self::find_entry(item, &content)
+            .and_then(|entry| remove_line(&content, entry.line))
+            .ok_or_else(|| Error::ItemNotFound {
+                id: item.id.clone(),
+            })?;
+
+        Self::write_config(item, &path, &updated)
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![SourceType::Wsl]
+    }
+}
+
+impl Default for WslScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::comment_marker::DISABLED_MARKER;
+
+    #[test]
+    fn boot_commands_come_from_the_boot_section() {
+        let content = format!(
+            "[automount]\ncommand=not this\n[boot]\ncommand=\"service ssh start\"\ncommand=\n\
+             {DISABLED_MARKER}command=mount -a\n"
+        );
+        let commands = boot_commands(&content);
+
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].unquoted_value(), "service ssh start");
+        assert!(commands[0].enabled);
+        assert_eq!(commands[1].unquoted_value(), "mount -a");
+        assert!(!commands[1].enabled);
+    }
+
+    #[test]
+    fn repeated_boot_commands_are_found_on_their_own_line() {
+        let content = "[boot]\ncommand=mount -a\ncommand=mount -a\ncommand=true\n";
+        let item = |command: &str| {
+            StartupItem::new(
+                "Ubuntu".to_string(),
+                SourceType::Wsl,
+                r"C:\wsl\Ubuntu\rootfs\etc\wsl.conf".to_string(),
+                command.to_string(),
+            )
+        };
+
+        let second = item("mount -a").with_line(2);
+        assert_eq!(WslScanner::find_entry(&second, content).unwrap().line, 2);
+        assert!(WslScanner::find_entry(&item("mount -a"), content).is_none());
+
+        // A single match is found even if lines moved since the scan
+        let moved = item("true").with_line(1);
+        assert_eq!(WslScanner::find_entry(&moved, content).unwrap().line, 3);
+    }
+
+    #[test]
+    fn wslconfig_entries_load_files_at_boot() {
+        let content = "[wsl2]\nmemory=4GB\nkernel=C:\\\\kernels\\\\bzImage\nkernelModules=\n\
+                       [boot]\nkernel=x\n";
+        let entries = wslconfig_boot_entries(content);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "kernel");
+        assert_eq!(entries[0].unquoted_value(), r"C:\\kernels\\bzImage");
+    }
+
+    #[test]
+    fn wsl2_config_is_read_through_the_share() {
+        let wsl2 = WslDistribution {
+            name: "Ubuntu".to_string(),
+            base_path: PathBuf::from(r"C:\wsl\Ubuntu"),
+            wsl2: true,
+        };
+        let path = wsl2.wsl_conf_path().display().to_string();
+
+        assert_eq!(path, r"\\wsl.localhost\Ubuntu\etc\wsl.conf");
+        assert_eq!(wsl2_distribution(&path), Some("Ubuntu"));
+        assert_eq!(
+            wsl2_distribution(r"\\WSL.LOCALHOST\Debian\etc"),
+            Some("Debian")
+        );
+        assert_eq!(
+            wsl2_distribution(r"C:\wsl\Ubuntu\rootfs\etc\wsl.conf"),
+            None
+        );
+        assert_eq!(wsl2_distribution(r"\\wsl.localhost\"), None);
+    }
+
+    #[test]
+    fn stopped_wsl2_distributions_are_not_read() {
+        let distribution = WslDistribution {
+            name: "Ubuntu".to_string(),
+            base_path: PathBuf::from(r"C:\wsl\Ubuntu"),
+            wsl2: true,
+        };
+        let items = WslScanner::new().scan_distribution(&distribution, false);
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].status, ItemStatus::Unknown);
+        assert!(items[0].command.is_empty());
+    }
+
+    #[test]
+    fn parses_utf16_and_utf8_distribution_lists() {
+        let utf16: Vec<u8> = "\u{feff}Ubuntu\r\nDebian\r\n\r\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+
+        assert_eq!(parse_distribution_list(&utf16), ["Ubuntu", "Debian"]);
+        assert_eq!(
+            parse_distribution_list(b"Ubuntu\nDebian\n"),
+            ["Ubuntu", "Debian"]
+        );
+        assert!(parse_distribution_list(b"").is_empty());
+    }
+}
@@ -0,0 +1,719 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::models::{ItemStatus, RawPayload, SourceType, StartupItem};
+
+use super::ini::{self, IniEntry};
+use super::{capture_file, write_captured_file, StartupSource};
+
+const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
+
+/// Key added to overrides written by this tool, so `enable` knows it may remove them
+const OVERRIDE_KEY: &str = "X-Startup-Checker-Override";
+
+/// A parsed `.desktop` file from an autostart directory
+#[derive(Debug, Clone, Default)]
+pub struct DesktopEntry {
+    pub name: Option<String>,
+    pub exec: Option<String>,
+    pub try_exec: Option<String>,
+    pub comment: Option<String>,
+    pub hidden: bool,
+    pub gnome_autostart_enabled: bool,
+    pub only_show_in: Vec<String>,
+    pub not_show_in: Vec<String>,
+    pub is_override: bool,
+}
+
+impl DesktopEntry {
+    pub fn parse(content: &str) -> Self {
+        let mut entry = Self {
+            gnome_autostart_enabled: true,
+            ..Self::default()
+        };
+
+        let entries: Vec<IniEntry> = ini::parse(content)
+            .into_iter()
+            .filter(|e| e.enabled && e.section == DESKTOP_ENTRY_GROUP)
+            .collect();
+
+        // Localized keys (Name[de]) are ignored, only the default value is used
+        for e in &entries {
+            let value = unescape_value(&e.value);
+            match e.key.as_str() {
+                "Name" => entry.name = Some(value),
+                "Exec" => entry.exec = Some(value),
+                "TryExec" => entry.try_exec = Some(value),
+                "Comment" => entry.comment = Some(value),
+                "Hidden" => entry.hidden = value.eq_ignore_ascii_case("true"),
+                "X-GNOME-Autostart-enabled" => {
+                    entry.gnome_autostart_enabled = !value.eq_ignore_ascii_case("false")
+                }
+                "OnlyShowIn" => entry.only_show_in = split_list(&value),
+                "NotShowIn" => entry.not_show_in = split_list(&value),
+                OVERRIDE_KEY => entry.is_override = value.eq_ignore_ascii_case("true"),
+                _ => {}
+            }
+        }
+
+        entry
+    }
+
+    /// Whether the entry is started at all, ignoring desktop restrictions
+    pub fn is_enabled(&self) -> bool {
+        !self.hidden && self.gnome_autostart_enabled
+    }
+
+    /// Apply `OnlyShowIn`/`NotShowIn` against `$XDG_CURRENT_DESKTOP`
+    pub fn shown_in(&self, current_desktops: &[String]) -> bool {
+        let matches = |list: &[String]| {
+            list.iter()
+                .any(|d| current_desktops.iter().any(|c| c.eq_ignore_ascii_case(d)))
+        };
+
+        if !self.only_show_in.is_empty() && !matches(&self.only_show_in) {
+            return false;
+        }
+
+        !matches(&self.not_show_in)
+    }
+
+    /// Program arguments from `Exec`, with field codes removed
+    pub fn arguments(&self) -> Vec<String> {
+        self.exec.as_deref().map(parse_exec).unwrap_or_default()
+    }
+}
+
+/// Undo the string escapes allowed in desktop entry values (`\s`, `\n`, `\t`, `\r`, `\\`)
+fn unescape_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Split an `Exec` value into arguments following the Desktop Entry spec:
+/// double-quoted arguments may escape `"`, `` ` ``, `$` and `\`, `%%` is a
+/// literal percent sign and the other field codes are dropped.
+pub fn parse_exec(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut has_arg = false;
+    let mut in_quotes = false;
+    let mut chars = exec.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            '\\' if in_quotes => {
+                if let Some(&next) = chars.peek() {
+                    if matches!(next, '"' | '`' | '$' | '\\') {
+                        current.push(next);
+                        chars.next();
+                        continue;
+                    }
+                }
+                current.push(c);
+            }
+            // Other field codes expand to nothing when there are no files or URLs
+            '%' if !in_quotes => {
+                if chars.next() == Some('%') {
+                    current.push('%');
+                    has_arg = true;
+                }
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg || !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+
+    if has_arg || !current.is_empty() {
+        args.push(current);
+    }
+
+    args
+}
+
+/// Set `key=value` in the `[Desktop Entry]` group, replacing an existing key
+/// or inserting it right after the group header
+pub fn set_desktop_key(content: &str, key: &str, value: &str) -> String {
+    let line = format!("{key}={value}");
+    let existing = ini::parse(content)
+        .into_iter()
+        .find(|e| e.enabled && e.section == DESKTOP_ENTRY_GROUP && e.key == key);
+
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    match existing {
+        Some(entry) => lines[entry.line] = line,
+        None => {
+            let header = lines
+                .iter()
+                .position(|l| l.trim() == format!("[{DESKTOP_ENTRY_GROUP}]"));
+            match header {
+                Some(index) => lines.insert(index + 1, line),
+                None => {
+                    lines.insert(0, line);
+                    lines.insert(0, format!("[{DESKTOP_ENTRY_GROUP}]"));
+                }
+            }
+        }
+    }
+
+    let mut result = lines.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Look up a program name in `$PATH`, or check an absolute path directly
+pub fn find_program(program: &str, path_var: Option<&str>) -> Option<PathBuf> {
+    let candidate = Path::new(program);
+    if candidate.is_absolute() {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+
+    std::env::split_paths(path_var?)
+        .map(|dir| dir.join(program))
+        .find(|p| p.is_file())
+}
+
+pub struct XdgAutostartScanner {
+    config_home: PathBuf,
+    config_dirs: Vec<PathBuf>,
+    current_desktops: Vec<String>,
+    path_var: Option<String>,
+}
+
+impl XdgAutostartScanner {
+    /// Use `$XDG_CONFIG_HOME`, `$XDG_CONFIG_DIRS` and `$XDG_CURRENT_DESKTOP`
+    /// with the defaults from the XDG Base Directory spec
+    pub fn new() -> Self {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| dirs::home_dir().map(|h| h.join(".config")))
+            .unwrap_or_else(|| PathBuf::from(".config"));
+
+        let config_dirs = std::env::var("XDG_CONFIG_DIRS")
+            .ok()
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                std::env::split_paths(&v)
+                    .filter(|p| p.is_absolute())
+                    .collect()
+            })
+            .unwrap_or_else(|| vec![PathBuf::from("/etc/xdg")]);
+
+        let current_desktops = std::env::var("XDG_CURRENT_DESKTOP")
+            .map(|v| v.split(':').map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Self::with_dirs(config_home, config_dirs)
+            .with_current_desktops(current_desktops)
+            .with_path_var(std::env::var("PATH").ok())
+    }
+
+    /// Scan explicit directories, e.g. below a fake root in tests
+    pub fn with_dirs(config_home: PathBuf, config_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            config_home,
+            config_dirs,
+            current_desktops: Vec::new(),
+            path_var: None,
+        }
+    }
+
+    pub fn with_current_desktops(mut self, current_desktops: Vec<String>) -> Self {
+        self.current_desktops = current_desktops;
+        self
+    }
+
+    pub fn with_path_var(mut self, path_var: Option<String>) -> Self {
+        self.path_var = path_var;
+        self
+    }
+
+    fn user_dir(&self) -> PathBuf {
+        self.config_home.join("autostart")
+    }
+
+    /// Autostart directories, most important first
+    fn autostart_dirs(&self) -> Vec<(PathBuf, SourceType)> {
+        let mut dirs = vec![(self.user_dir(), SourceType::XdgAutostartUser)];
+        dirs.extend(
+            self.config_dirs
+                .iter()
+                .map(|d| (d.join("autostart"), SourceType::XdgAutostartSystem)),
+        );
+        dirs
+    }
+
+    fn desktop_files(dir: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|e| e == "desktop") && p.is_file())
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// The highest-priority system file for a desktop file id, skipping the user dir
+    fn system_file(&self, file_name: &str) -> Option<PathBuf> {
+        self.config_dirs
+            .iter()
+            .map(|d| d.join("autostart").join(file_name))
+            .find(|p| p.is_file())
+    }
+
+    fn build_item(
+        &self,
+        file_name: &str,
+        path: &Path,
+        source: SourceType,
+        entry: &DesktopEntry,
+        shadowed: Option<&DesktopEntry>,
+    ) -> Option<StartupItem> {
+        // A user override that only hides a system entry reports the system entry's details
+        let details = match shadowed {
+            Some(system) if entry.exec.is_none() => system,
+            _ => entry,
+        };
+
+        let exec = details.exec.clone()?;
+        let name = details
+            .name
+            .clone()
+            .unwrap_or_else(|| file_name.trim_end_matches(".desktop").to_string());
+
+        let mut notes = Vec::new();
+        if let Some(comment) = &details.comment {
+            notes.push(comment.clone());
+        }
+        if !details.shown_in(&self.current_desktops) {
+            notes.push("Not started in the current desktop (OnlyShowIn/NotShowIn)".to_string());
+        }
+        if shadowed.is_some() {
+            notes.push("User override of a system entry".to_string());
+        }
+
+        let status = if entry.is_enabled() {
+            ItemStatus::Enabled
+        } else {
+            ItemStatus::Disabled
+        };
+
+        let mut item = StartupItem::new(name, source, path.display().to_string(), exec)
+            .with_status(status)
+            .with_description((!notes.is_empty()).then(|| notes.join(". ")));
+
+        // TryExec decides whether the entry is used at all, so it wins over Exec
+        let program = details
+            .try_exec
+            .clone()
+            .or_else(|| details.arguments().into_iter().next());
+        if let Some(program) = program {
+            let resolved = find_program(&program, self.path_var.as_deref());
+            item.file_exists = resolved.is_some();
+            item.executable_path = Some(resolved.unwrap_or_else(|| PathBuf::from(program)));
+        }
+
+        Some(item)
+    }
+
+    fn read_entry(path: &Path) -> Option<DesktopEntry> {
+        fs::read_to_string(path)
+            .ok()
+            .map(|content| DesktopEntry::parse(&content))
+    }
+
+    fn user_file_path(&self, item: &StartupItem) -> Result<(PathBuf, String)> {
+        let file_name = Path::new(&item.source_location)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| Error::InvalidPath(item.source_location.clone()))?;
+
+        Ok((self.user_dir().join(&file_name), file_name))
+    }
+
+    fn write_user_file(&self, path: &Path, content: &str) -> Result<()> {
+        fs::create_dir_all(self.user_dir()).map_err(Error::Io)?;
+        fs::write(path, content).map_err(Error::Io)
+    }
+}
+
+impl StartupSource for XdgAutostartScanner {
+    fn scan(&self) -> Result<Vec<StartupItem>> {
+        let mut items = Vec::new();
+        let mut seen = HashSet::new();
+
+        // Files in more important directories shadow files with the same name
+        for (dir, source) in self.autostart_dirs() {
+            for path in Self::desktop_files(&dir) {
+                let file_name = path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string();
+                if !seen.insert(file_name.clone()) {
+                    continue;
+                }
+
+                let Some(entry) = Self::read_entry(&path) else {
+                    continue;
+                };
+
+                // A user file overriding a system entry stays in the system group,
+                // so toggling it does not move the item around
+                let system_path = match source {
+                    SourceType::XdgAutostartUser => self.system_file(&file_name),
+                    _ => None,
+                };
+                let shadowed = system_path.as_deref().and_then(Self::read_entry);
+                let (location, source) = match &system_path {
+                    Some(system_path) => (system_path.as_path(), SourceType::XdgAutostartSystem),
+                    None => (path.as_path(), source),
+                };
+
+                if let Some(item) =
+                    self.build_item(&file_name, location, source, &entry, shadowed.as_ref())
+                {
+                    items.push(item);
+                }
+            }
+        }
+
+        Ok(items)
+    }
+
+    fn enable(&self, item: &StartupItem) -> Result<()> {
+        let (user_path, file_name) = self.user_file_path(item)?;
+        let system_path = self.system_file(&file_name);
+
+        if user_path.is_file() {
+            let content = fs::read_to_string(&user_path).map_err(Error::Io)?;
+            let entry = DesktopEntry::parse(&content);
+
+            // Drop our own override if the system entry is enabled on its own
+            if entry.is_override {
+                let system_enabled = system_path
+                    .as_deref()
+                    .and_then(Self::read_entry)
+                    .is_some_and(|e| e.is_enabled());
+                if system_enabled {
+                    return fs::remove_file(&user_path).map_err(Error::Io);
+                }
+            }
+
+            let content = set_desktop_key(&content, "Hidden", "false");
+            let content = set_desktop_key(&content, "X-GNOME-Autostart-enabled", "true");
+            return fs::write(&user_path, content).map_err(Error::Io);
+        }
+
+        let system_path = system_path.ok_or_else(|| Error::ItemNotFound {
+            id: item.id.clone(),
+        })?;
+        let content = fs::read_to_string(&system_path).map_err(Error::Io)?;
+        let content = set_desktop_key(&content, OVERRIDE_KEY, "true");
+        let content = set_desktop_key(&content, "Hidden", "false");
+        let content = set_desktop_key(&content, "X-GNOME-Autostart-enabled", "true");
+        self.write_user_file(&user_path, &content)
+    }
+
+    fn disable(&self, item: &StartupItem) -> Result<()> {
+        let (user_path, file_name) = self.user_file_path(item)?;
+
+        let content = if user_path.is_file() {
+            fs::read_to_string(&user_path).map_err(Error::Io)?
+        } else {
+            let system_path = self
+                .system_file(&file_name)
+                .ok_or_else(|| Error::ItemNotFound {
+                    id: item.id.clone(),
+                })?;
+            let content = fs::read_to_string(&system_path).map_err(Error::Io)?;
+            set_desktop_key(&content, OVERRIDE_KEY, "true")
+        };
+
+        let content = set_desktop_key(&content, "Hidden", "true");
+        self.write_user_file(&user_path, &content)
+    }
+
+    /// A system entry is captured with the user's override of it, if any
+    fn capture(&self, item: &StartupItem) -> Result<Option<RawPayload>> {
+        let path = Path::new(&item.source_location);
+        if item.source != SourceType::XdgAutostartSystem {
+            return capture_file(path);
+        }
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let (user_path, file_name) = self.user_file_path(item)?;
+        let user_override = if user_path.is_file() {
+            Some(fs::read(&user_path).map_err(Error::Io)?)
+        } else {
+            None
+        };
+
+        Ok(Some(RawPayload::DesktopEntry {
+            file_name,
+            data: fs::read(path).map_err(Error::Io)?,
+            user_override,
+        }))
+    }
+
+    fn recreate(&self, item: &StartupItem, raw: Option<&RawPayload>) -> Result<()> {
+        let path = Path::new(&item.source_location);
+        match raw {
+            Some(RawPayload::DesktopEntry {
+                data,
+                user_override,
+                ..
+            }) => {
+                // The system file is normally still there and not writable
+                if fs::read(path).ok().as_ref() != Some(data) {
+                    write_captured_file(item, path, raw)?;
+                }
+
+                let (user_path, _) = self.user_file_path(item)?;
+                match user_override {
+                    Some(content) => {
+                        fs::create_dir_all(self.user_dir()).map_err(Error::Io)?;
+                        fs::write(&user_path, content).map_err(Error::Io)?;
+                    }
+                    None => {
+                        let ours = Self::read_entry(&user_path).is_some_and(|e| e.is_override);
+                        if ours {
+                            fs::remove_file(&user_path).map_err(Error::Io)?;
+                        }
+                    }
+                }
+            }
+            _ => write_captured_file(item, path, raw)?,
+        }
+
+        if item.status.is_disabled() {
+            self.disable(item)?;
+        }
+        Ok(())
+    }
+
+    /// Removes a user entry. System entries are left to the package that
+    /// installed them and hidden by a user-level override instead.
+    fn delete(&self, item: &StartupItem) -> Result<()> {
+        if item.source == SourceType::XdgAutostartSystem {
+            return self.disable(item);
+        }
+
+        let path = Path::new(&item.source_location);
+        if path.is_file() {
+            fs::remove_file(path).map_err(Error::Io)?;
+        }
+        Ok(())
+    }
+
+    fn source_types(&self) -> Vec<SourceType> {
+        vec![SourceType::XdgAutostartUser, SourceType::XdgAutostartSystem]
+    }
+}
+
+impl Default for XdgAutostartScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::fake_root;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn parses_exec_like_the_spec() {
+        assert_eq!(
+            parse_exec(r#"app --name "My \"App\"" %U 100%% "" end"#),
+            ["app", "--name", "My \"App\"", "100%", "", "end"]
+        );
+        assert_eq!(
+            parse_exec(r#""/opt/my app/run" -x"#),
+            ["/opt/my app/run", "-x"]
+        );
+        assert!(parse_exec("  %f ").is_empty());
+    }
+
+    #[test]
+    fn parses_desktop_entries() {
+        let entry = DesktopEntry::parse(
+            "[Desktop Entry]\nName=Sync\nName[de]=Abgleich\nExec=sync\\s--quiet\n\
+             X-GNOME-Autostart-enabled=false\nOnlyShowIn=GNOME;KDE;\n\
+             [Desktop Action New]\nExec=other\n",
+        );
+
+        assert_eq!(entry.name.as_deref(), Some("Sync"));
+        assert_eq!(entry.arguments(), ["sync", "--quiet"]);
+        assert!(!entry.is_enabled());
+        assert!(entry.shown_in(&["kde".to_string()]));
+        assert!(!entry.shown_in(&["XFCE".to_string()]));
+    }
+
+    #[test]
+    fn sets_keys_in_the_desktop_entry_group() {
+        let content = "# comment\n[Desktop Entry]\nExec=app\n";
+        let hidden = set_desktop_key(content, "Hidden", "true");
+
+        assert_eq!(
+            hidden,
+            "# comment\n[Desktop Entry]\nHidden=true\nExec=app\n"
+        );
+        assert_eq!(
+            set_desktop_key(&hidden, "Hidden", "false"),
+            "# comment\n[Desktop Entry]\nHidden=false\nExec=app\n"
+        );
+    }
+
+    #[test]
+    fn disabling_a_system_entry_writes_a_user_override() {
+        let root = fake_root("xdg-override");
+        let config_home = root.join("home/.config");
+        let system_dir = root.join("etc/xdg");
+        let system_file = system_dir.join("autostart/tray.desktop");
+        write(
+            &system_file,
+            "[Desktop Entry]\nName=Tray\nExec=tray --start\n",
+        );
+        let scanner = XdgAutostartScanner::with_dirs(config_home.clone(), vec![system_dir]);
+
+        let items = scanner.scan().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].source, SourceType::XdgAutostartSystem);
+        assert_eq!(items[0].status, ItemStatus::Enabled);
+
+        scanner.disable(&items[0]).unwrap();
+        let user_file = config_home.join("autostart/tray.desktop");
+        let user_entry = DesktopEntry::parse(&fs::read_to_string(&user_file).unwrap());
+        assert!(user_entry.is_override);
+        assert!(user_entry.hidden);
+
+        // The override keeps the item in the system group with the same id
+        let disabled = scanner.scan().unwrap();
+        assert_eq!(disabled.len(), 1);
+        assert_eq!(disabled[0].id, items[0].id);
+        assert_eq!(disabled[0].status, ItemStatus::Disabled);
+
+        // Enabling removes the override again
+        scanner.enable(&disabled[0]).unwrap();
+        assert!(!user_file.exists());
+        assert!(system_file.exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn deleting_a_system_entry_hides_it_and_restores_the_user_override() {
+        let root = fake_root("xdg-delete");
+        let config_home = root.join("home/.config");
+        let system_dir = root.join("etc/xdg");
+        let system_file = system_dir.join("autostart/tray.desktop");
+        let user_file = config_home.join("autostart/tray.desktop");
+        write(
+            &system_file,
+            "[Desktop Entry]\nName=Tray\nExec=tray --start\n",
+        );
+        // Written by the user, not by this tool
+        let user_content = "[Desktop Entry]\nName=Tray\nExec=tray --quiet\n";
+        write(&user_file, user_content);
+        let scanner = XdgAutostartScanner::with_dirs(config_home, vec![system_dir]);
+
+        let item = scanner.scan().unwrap().remove(0);
+        assert_eq!(item.command, "tray --quiet");
+        let raw = scanner.capture(&item).unwrap();
+        assert!(matches!(
+            &raw,
+            Some(RawPayload::DesktopEntry { user_override: Some(data), .. })
+                if data == user_content.as_bytes()
+        ));
+
+        scanner.delete(&item).unwrap();
+        assert!(system_file.exists());
+        assert!(DesktopEntry::parse(&fs::read_to_string(&user_file).unwrap()).hidden);
+
+        scanner.recreate(&item, raw.as_ref()).unwrap();
+        assert_eq!(fs::read_to_string(&user_file).unwrap(), user_content);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn restoring_a_system_entry_drops_our_own_override() {
+        let root = fake_root("xdg-restore");
+        let config_home = root.join("home/.config");
+        let system_dir = root.join("etc/xdg");
+        write(
+            &system_dir.join("autostart/tray.desktop"),
+            "[Desktop Entry]\nName=Tray\nExec=tray\n",
+        );
+        let scanner = XdgAutostartScanner::with_dirs(config_home.clone(), vec![system_dir]);
+
+        let item = scanner.scan().unwrap().remove(0);
+        let raw = scanner.capture(&item).unwrap();
+        scanner.delete(&item).unwrap();
+        assert_eq!(scanner.scan().unwrap()[0].status, ItemStatus::Disabled);
+
+        scanner.recreate(&item, raw.as_ref()).unwrap();
+        assert!(!config_home.join("autostart/tray.desktop").exists());
+        assert_eq!(scanner.scan().unwrap()[0].status, ItemStatus::Enabled);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
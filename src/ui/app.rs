@@ -16,11 +16,21 @@ use ratatui::{
 };
 
 use crate::error::Result;
-use crate::models::StartupItem;
-use crate::operations::create_backup;
-use crate::sources::{modify_item, scan_all_sources};
-use crate::ui::state::MessageType;
-use crate::ui::widgets::{render_help, render_list, render_status_bar};
+use crate::models::{ItemStatus, SourceType, StartupItem};
+use crate::operations::{
+    apply_edit, create_backup, create_item, delay_item, delete_profile, find_delayed, list_backups,
+    list_profiles, list_quarantine, quarantine_item, save_profile, switch_profile, undelay_item,
+    ChangeOutcome, Transaction, TransactionReport, DEFAULT_DELAY_SECS,
+};
+use crate::sources::{scan_all_sources, ServiceState};
+use crate::ui::state::{
+    AddItemForm, ConfirmAction, DelayForm, DiffView, EditItemForm, MessageType, ProfilesView,
+    QuarantineView, RestoreView,
+};
+use crate::ui::widgets::{
+    render_add_item, render_confirm, render_delay, render_diff, render_edit_item, render_help,
+    render_list, render_profiles, render_quarantine, render_restore, render_status_bar,
+};
 use crate::ui::{AppState, Icons, Theme, ViewMode};
 
 pub fn run_app(items: Vec<StartupItem>) -> Result<()> {
@@ -78,7 +88,10 @@ fn run_loop(
                         KeyCode::Char('q') | KeyCode::Esc => {
                             if state.has_pending_changes() {
                                 state.set_message(
-                                    format!("{} Pending changes! Press 'a' to apply or 'u' to undo", Icons::MISSING),
+                                    format!(
+                                        "{} Pending changes! Press 'a' to apply or 'u' to undo",
+                                        Icons::MISSING
+                                    ),
                                     MessageType::Warning,
                                 );
                             } else {
@@ -107,9 +120,26 @@ fn run_loop(
                         KeyCode::Char('a') => {
                             apply_changes(state);
                         }
+                        KeyCode::Char('m') => {
+                            state.transaction_mode = state.transaction_mode.toggle();
+                            state.set_message(
+                                format!(
+                                    "{} Apply mode: {}",
+                                    Icons::INFO,
+                                    state.transaction_mode.display()
+                                ),
+                                MessageType::Info,
+                            );
+                        }
+                        KeyCode::Char('S') => {
+                            toggle_service_disable_mode(state);
+                        }
                         KeyCode::Char('u') => {
                             state.clear_pending_changes();
-                            state.set_message(format!("{} Pending changes discarded", Icons::CHECK), MessageType::Info);
+                            state.set_message(
+                                format!("{} Pending changes discarded", Icons::CHECK),
+                                MessageType::Info,
+                            );
                         }
                         KeyCode::Char('r') => {
                             refresh(state);
@@ -117,18 +147,55 @@ fn run_loop(
                         KeyCode::Char('b') => {
                             create_backup_action(state);
                         }
+                        KeyCode::Char('R') => {
+                            open_restore(state);
+                        }
+                        KeyCode::Char('D') => {
+                            open_diff(state);
+                        }
+                        KeyCode::Char('x') | KeyCode::Delete => {
+                            request_delete(state);
+                        }
+                        KeyCode::Char('Q') => {
+                            open_quarantine(state);
+                        }
+                        KeyCode::Char('n') => {
+                            open_add_item(state);
+                        }
+                        KeyCode::Char('e') => {
+                            open_edit_item(state);
+                        }
+                        KeyCode::Char('L') => {
+                            toggle_delay(state);
+                        }
+                        KeyCode::Char('P') => {
+                            open_profiles(state);
+                        }
                         KeyCode::Char('?') => {
                             state.view_mode = ViewMode::Help;
                         }
                         _ => {}
                     },
+                    ViewMode::Restore => handle_restore_key(state, key.code),
+                    ViewMode::Diff => handle_diff_key(state, key.code),
+                    ViewMode::Quarantine => handle_quarantine_key(state, key.code),
+                    ViewMode::AddItem => handle_add_item_key(state, key.code),
+                    ViewMode::EditItem => handle_edit_item_key(state, key.code),
+                    ViewMode::Delay => handle_delay_key(state, key.code),
+                    ViewMode::Profiles => handle_profiles_key(state, key.code),
                     ViewMode::Confirm => {
                         // Handle confirmation dialogs
                         match key.code {
                             KeyCode::Char('y') | KeyCode::Enter => {
                                 state.view_mode = ViewMode::List;
+                                if let Some(ConfirmAction::DeleteItem { id, .. }) =
+                                    state.confirm_action.take()
+                                {
+                                    delete_item_action(state, &id);
+                                }
                             }
                             KeyCode::Char('n') | KeyCode::Esc => {
+                                state.confirm_action = None;
                                 state.view_mode = ViewMode::List;
                             }
                             _ => {}
@@ -147,10 +214,10 @@ fn render_ui(frame: &mut Frame, state: &mut AppState) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3),  // Header
-            Constraint::Min(10),    // Main list
-            Constraint::Length(6),  // Details
-            Constraint::Length(1),  // Status bar
+            Constraint::Length(3), // Header
+            Constraint::Min(10),   // Main list
+            Constraint::Length(8), // Details
+            Constraint::Length(1), // Status bar
         ])
         .split(size);
 
@@ -170,6 +237,46 @@ fn render_ui(frame: &mut Frame, state: &mut AppState) {
     if state.view_mode == ViewMode::Help {
         render_help(frame, size);
     }
+
+    // Restore overlay
+    if state.view_mode == ViewMode::Restore {
+        render_restore(frame, size, state);
+    }
+
+    // Diff overlay
+    if state.view_mode == ViewMode::Diff {
+        render_diff(frame, size, state);
+    }
+
+    // Quarantine overlay
+    if state.view_mode == ViewMode::Quarantine {
+        render_quarantine(frame, size, state);
+    }
+
+    // Add item form
+    if state.view_mode == ViewMode::AddItem {
+        render_add_item(frame, size, state);
+    }
+
+    // Edit item form
+    if state.view_mode == ViewMode::EditItem {
+        render_edit_item(frame, size, state);
+    }
+
+    // Delay form
+    if state.view_mode == ViewMode::Delay {
+        render_delay(frame, size, state);
+    }
+
+    // Profiles overlay
+    if state.view_mode == ViewMode::Profiles {
+        render_profiles(frame, size, state);
+    }
+
+    // Confirmation dialog
+    if state.view_mode == ViewMode::Confirm {
+        render_confirm(frame, size, state);
+    }
 }
 
 fn render_header(frame: &mut Frame, area: Rect, state: &AppState) {
@@ -199,13 +306,12 @@ fn render_header(frame: &mut Frame, area: Rect, state: &AppState) {
 
     let title = Line::from(all_spans);
 
-    let header = Paragraph::new(title)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_set(border::ROUNDED)
-                .border_style(Theme::border()),
-        );
+    let header = Paragraph::new(title).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::ROUNDED)
+            .border_style(Theme::border()),
+    );
 
     frame.render_widget(header, area);
 }
@@ -213,40 +319,113 @@ fn render_header(frame: &mut Frame, area: Rect, state: &AppState) {
 fn render_details(frame: &mut Frame, area: Rect, state: &AppState) {
     let content = if let Some(item) = state.selected_startup_item() {
         let status = state.get_effective_status(item);
-        let has_pending = state.pending_changes.contains_key(&item.id);
+        let has_pending = state.pending_change(&item.id).is_some();
 
-        let status_icon = if status.is_enabled() { Icons::ENABLED } else { Icons::DISABLED };
-        let status_style = if status.is_enabled() { Theme::item_enabled() } else { Theme::item_disabled() };
+        let status_icon = match status {
+            ItemStatus::Pending | ItemStatus::DisabledButTriggered => Icons::PENDING,
+            _ if status.is_enabled() => Icons::ENABLED,
+            _ => Icons::DISABLED,
+        };
+        let status_style = if status.is_enabled() {
+            Theme::item_enabled()
+        } else {
+            Theme::item_disabled()
+        };
 
         vec![
             Line::from(vec![
                 Span::styled("  Name     ", Theme::detail_label()),
                 Span::styled(&item.name, Theme::detail_value()),
                 if has_pending {
-                    Span::styled(format!("  {} modified", Icons::MODIFIED), Theme::item_pending())
+                    Span::styled(
+                        format!("  {} modified", Icons::MODIFIED),
+                        Theme::item_pending(),
+                    )
                 } else {
                     Span::raw("")
                 },
             ]),
             Line::from(vec![
                 Span::styled("  Command  ", Theme::detail_label()),
-                Span::styled(truncate_str(&item.display_command(), area.width.saturating_sub(14) as usize), Theme::detail_muted()),
+                Span::styled(
+                    truncate_str(
+                        &item.display_command(),
+                        area.width.saturating_sub(14) as usize,
+                    ),
+                    Theme::detail_muted(),
+                ),
             ]),
             Line::from(vec![
                 Span::styled("  Status   ", Theme::detail_label()),
                 Span::styled(format!("{} ", status_icon), status_style),
                 Span::styled(status.display(), status_style),
-                Span::styled(format!("  {}  Source  ", Icons::SEPARATOR), Theme::detail_muted()),
+                match item.start_mode {
+                    Some(mode) => {
+                        Span::styled(format!(" ({})", mode.display()), Theme::detail_muted())
+                    }
+                    None => Span::raw(""),
+                },
+                Span::styled(
+                    format!("  {}  Source  ", Icons::SEPARATOR),
+                    Theme::detail_muted(),
+                ),
                 Span::styled(item.source.short_name(), Theme::detail_value()),
                 if item.requires_admin {
-                    Span::styled(format!("  {} Requires Admin", Icons::ADMIN), Theme::icon_admin())
+                    Span::styled(
+                        format!("  {} Requires Admin", Icons::ADMIN),
+                        Theme::icon_admin(),
+                    )
                 } else {
                     Span::raw("")
                 },
             ]),
             Line::from(vec![
                 Span::styled("  Location ", Theme::detail_label()),
-                Span::styled(truncate_str(&item.source_location, area.width.saturating_sub(14) as usize), Theme::detail_muted()),
+                Span::styled(
+                    truncate_str(
+                        &item.source_location,
+                        area.width.saturating_sub(14) as usize,
+                    ),
+                    Theme::detail_muted(),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  Target   ", Theme::detail_label()),
+                Span::styled(
+                    truncate_str(
+                        &item
+                            .payload
+                            .as_ref()
+                            .map(|p| p.display())
+                            .unwrap_or_else(|| item.display_path()),
+                        area.width.saturating_sub(14) as usize,
+                    ),
+                    Theme::detail_muted(),
+                ),
+            ]),
+            Line::from(vec![
+                Span::styled("  File     ", Theme::detail_label()),
+                Span::styled(
+                    truncate_str(
+                        &item
+                            .file_info()
+                            .map(|info| {
+                                let modified = info
+                                    .modified
+                                    .map(|m| m.format("%Y-%m-%d %H:%M").to_string())
+                                    .unwrap_or_else(|| "unknown".to_string());
+                                format!(
+                                    "{}  {dot}  modified {modified}  {dot}  SHA-256 {}",
+                                    info.display_size(),
+                                    &info.sha256[..16],
+                                    dot = Icons::DOT
+                                )
+                            })
+                            .unwrap_or_else(|| "not found".to_string()),
+                        area.width.saturating_sub(14) as usize,
+                    ),
+                    Theme::detail_muted(),
+                ),
             ]),
         ]
     } else {
@@ -283,23 +462,15 @@ fn truncate_str(s: &str, max_len: usize) -> String {
 
 fn apply_changes(state: &mut AppState) {
     if !state.has_pending_changes() {
-        state.set_message(format!("{} No pending changes to apply", Icons::INFO), MessageType::Info);
+        state.set_message(
+            format!("{} No pending changes to apply", Icons::INFO),
+            MessageType::Info,
+        );
         return;
     }
 
-    let changes: Vec<_> = state.pending_changes.values().cloned().collect();
-    let total = changes.len();
-    let mut success = 0;
-    let mut failed = 0;
-
     // Create backup first
-    let all_items: Vec<_> = state
-        .items_by_source
-        .values()
-        .flatten()
-        .cloned()
-        .collect();
-
+    let all_items = state.all_items();
     if let Err(e) = create_backup(&all_items, Some("Before applying changes".to_string())) {
         state.set_message(
             format!("{} Backup failed: {}. Aborting.", Icons::CROSS, e),
@@ -308,39 +479,57 @@ fn apply_changes(state: &mut AppState) {
         return;
     }
 
-    // Apply each change
-    for change in changes {
-        // Find the item
-        let item = state
-            .items_by_source
-            .values()
-            .flatten()
-            .find(|i| i.id == change.item_id);
-
-        if let Some(item) = item {
-            match modify_item(item, change.new_status) {
-                Ok(()) => success += 1,
-                Err(e) => {
-                    failed += 1;
-                    eprintln!("Failed to modify {}: {}", item.name, e);
-                }
-            }
+    let mut transaction = Transaction::new(state.transaction_mode);
+    for change in &state.pending_changes {
+        if let Some(item) = all_items.iter().find(|i| i.id == change.item_id) {
+            transaction.push(item, change.new_status);
         }
     }
+    let total = transaction.len();
+    let report = transaction.commit();
 
     // Refresh to get updated state
     refresh(state);
 
-    if failed == 0 {
-        state.set_message(
-            format!("{} Applied {} changes successfully", Icons::CHECK, success),
+    let (message, message_type) = report_message(&report, total);
+    state.set_message(message, message_type);
+}
+
+/// Status bar message summing up a committed transaction
+fn report_message(report: &TransactionReport, total: usize) -> (String, MessageType) {
+    let applied = report.count(ChangeOutcome::Applied);
+    let failed = report.count(ChangeOutcome::Failed);
+    let stuck = report.count(ChangeOutcome::RollbackFailed);
+
+    if report.succeeded() {
+        (
+            format!("{} Applied {} changes successfully", Icons::CHECK, applied),
             MessageType::Success,
-        );
+        )
+    } else if let Some(e) = report.error() {
+        let message = if stuck > 0 {
+            format!("{} {} ({} could not be undone)", Icons::CROSS, e, stuck)
+        } else {
+            format!("{} {}", Icons::CROSS, e)
+        };
+        (message, MessageType::Error)
     } else {
-        state.set_message(
-            format!("{} Applied {}/{} changes ({} failed)", Icons::MISSING, success, total, failed),
+        let first_error = report
+            .results
+            .iter()
+            .find_map(|r| r.error.as_ref().map(|e| format!("{}: {}", r.name, e)))
+            .unwrap_or_default();
+        (
+            format!(
+                "{} Applied {}/{} changes ({} failed, {})",
+                Icons::MISSING,
+                applied,
+                total,
+                failed,
+                first_error
+            ),
             MessageType::Warning,
-        );
+        )
     }
 }
 
@@ -351,22 +540,737 @@ fn refresh(state: &mut AppState) {
 }
 
 fn create_backup_action(state: &mut AppState) {
-    let all_items: Vec<_> = state
-        .items_by_source
-        .values()
-        .flatten()
-        .cloned()
-        .collect();
+    let all_items: Vec<_> = state.items_by_source.values().flatten().cloned().collect();
 
     match create_backup(&all_items, Some("Manual backup".to_string())) {
         Ok(path) => {
             state.set_message(
-                format!("{} Backup created: {}", Icons::CHECK, path.file_name().unwrap_or_default().to_string_lossy()),
+                format!(
+                    "{} Backup created: {}",
+                    Icons::CHECK,
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ),
                 MessageType::Success,
             );
         }
         Err(e) => {
-            state.set_message(format!("{} Backup failed: {}", Icons::CROSS, e), MessageType::Error);
+            state.set_message(
+                format!("{} Backup failed: {}", Icons::CROSS, e),
+                MessageType::Error,
+            );
+        }
+    }
+}
+
+fn open_restore(state: &mut AppState) {
+    if state.has_pending_changes() {
+        state.set_message(
+            format!(
+                "{} Apply or undo pending changes before restoring",
+                Icons::MISSING
+            ),
+            MessageType::Warning,
+        );
+        return;
+    }
+
+    match list_backups() {
+        Ok(backups) if backups.is_empty() => {
+            state.set_message(
+                format!("{} No backups found", Icons::INFO),
+                MessageType::Info,
+            );
+        }
+        Ok(backups) => {
+            state.restore = Some(RestoreView::new(backups));
+            state.view_mode = ViewMode::Restore;
+        }
+        Err(e) => {
+            state.set_message(
+                format!("{} Cannot list backups: {}", Icons::CROSS, e),
+                MessageType::Error,
+            );
+        }
+    }
+}
+
+fn close_restore(state: &mut AppState) {
+    state.restore = None;
+    state.view_mode = ViewMode::List;
+}
+
+fn handle_restore_key(state: &mut AppState, code: KeyCode) {
+    let live = state.all_items();
+    let Some(view) = state.restore.as_mut() else {
+        state.view_mode = ViewMode::List;
+        return;
+    };
+
+    // Results only need dismissing
+    if view.results.is_some() {
+        if matches!(code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+            close_restore(state);
+        }
+        return;
+    }
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => view.move_by(-1),
+        KeyCode::Down | KeyCode::Char('j') => view.move_by(1),
+        KeyCode::Esc | KeyCode::Char('q') => {
+            if view.plan.is_some() {
+                view.plan = None;
+            } else {
+                close_restore(state);
+            }
+        }
+        KeyCode::Char(' ') => {
+            if let (Some(plan), Some(index)) = (view.plan.as_mut(), view.plan_state.selected()) {
+                plan.toggle(index);
+            }
+        }
+        KeyCode::Char('*') => {
+            if let Some(plan) = view.plan.as_mut() {
+                let all_selected = plan.selected_count() == plan.len();
+                plan.select_all(!all_selected);
+            }
+        }
+        KeyCode::Enter => match &view.plan {
+            None => {
+                if let Err(e) = view.open_plan(&live) {
+                    state.set_message(
+                        format!("{} Cannot read backup: {}", Icons::CROSS, e),
+                        MessageType::Error,
+                    );
+                }
+            }
+            Some(plan) if plan.selected_count() > 0 => apply_restore(state),
+            Some(_) => {}
+        },
+        _ => {}
+    }
+}
+
+fn apply_restore(state: &mut AppState) {
+    let all_items = state.all_items();
+    if let Err(e) = create_backup(&all_items, Some("Before restoring".to_string())) {
+        state.set_message(
+            format!("{} Backup failed: {}. Aborting.", Icons::CROSS, e),
+            MessageType::Error,
+        );
+        return;
+    }
+
+    let mode = state.transaction_mode;
+    let Some(view) = state.restore.as_mut() else {
+        return;
+    };
+    let results = view
+        .plan
+        .as_ref()
+        .map(|plan| plan.apply(mode))
+        .unwrap_or_default();
+    let failed = results.iter().filter(|r| !r.succeeded()).count();
+    let total = results.len();
+    view.results = Some(results);
+
+    state.refresh(scan_all_sources());
+
+    if failed == 0 {
+        state.set_message(
+            format!("{} Restored {} items", Icons::CHECK, total),
+            MessageType::Success,
+        );
+    } else {
+        state.set_message(
+            format!(
+                "{} Restored {}/{} items ({} failed)",
+                Icons::MISSING,
+                total - failed,
+                total,
+                failed
+            ),
+            MessageType::Warning,
+        );
+    }
+}
+
+fn open_diff(state: &mut AppState) {
+    match list_backups() {
+        Ok(backups) if backups.is_empty() => {
+            state.set_message(
+                format!("{} No backups found", Icons::INFO),
+                MessageType::Info,
+            );
+        }
+        Ok(backups) => {
+            state.diff = Some(DiffView::new(backups));
+            state.view_mode = ViewMode::Diff;
+        }
+        Err(e) => {
+            state.set_message(
+                format!("{} Cannot list backups: {}", Icons::CROSS, e),
+                MessageType::Error,
+            );
+        }
+    }
+}
+
+fn handle_diff_key(state: &mut AppState, code: KeyCode) {
+    let Some(view) = state.diff.as_mut() else {
+        state.view_mode = ViewMode::List;
+        return;
+    };
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => view.move_by(-1),
+        KeyCode::Down | KeyCode::Char('j') => view.move_by(1),
+        KeyCode::Esc | KeyCode::Char('q') => {
+            if view.diff.is_some() {
+                view.diff = None;
+            } else {
+                state.diff = None;
+                state.view_mode = ViewMode::List;
+            }
+        }
+        KeyCode::Enter if view.diff.is_none() => {
+            // Sizes and times of the live executables, so file changes show up
+            let mut live = state.all_items();
+            for item in &mut live {
+                item.stat_file();
+            }
+            if let Some(Err(e)) = state.diff.as_mut().map(|view| view.open_diff(&live)) {
+                state.set_message(
+                    format!("{} Cannot read backup: {}", Icons::CROSS, e),
+                    MessageType::Error,
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+fn request_delete(state: &mut AppState) {
+    let Some(item) = state.selected_startup_item() else {
+        return;
+    };
+
+    if item.requires_admin && !state.is_admin {
+        state.set_message(
+            "Cannot delete: Administrator privileges required".to_string(),
+            MessageType::Warning,
+        );
+        return;
+    }
+
+    if state.has_pending_changes() {
+        state.set_message(
+            format!(
+                "{} Apply or undo pending changes before deleting",
+                Icons::MISSING
+            ),
+            MessageType::Warning,
+        );
+        return;
+    }
+
+    state.confirm_action = Some(ConfirmAction::DeleteItem {
+        id: item.id.clone(),
+        name: item.name.clone(),
+    });
+    state.view_mode = ViewMode::Confirm;
+}
+
+fn delete_item_action(state: &mut AppState, id: &str) {
+    let Some(item) = state.find_item(id).cloned() else {
+        return;
+    };
+
+    match quarantine_item(&item) {
+        Ok(_) => {
+            state.refresh(scan_all_sources());
+            state.set_message(
+                format!(
+                    "{} Deleted {} (press 'Q' to bring it back)",
+                    Icons::CHECK,
+                    item.name
+                ),
+                MessageType::Success,
+            );
+        }
+        Err(e) => {
+            state.set_message(
+                format!("{} Cannot delete {}: {}", Icons::CROSS, item.name, e),
+                MessageType::Error,
+            );
+        }
+    }
+}
+
+fn open_quarantine(state: &mut AppState) {
+    match list_quarantine() {
+        Ok(entries) if entries.is_empty() => {
+            state.set_message(
+                format!("{} Quarantine is empty", Icons::INFO),
+                MessageType::Info,
+            );
+        }
+        Ok(entries) => {
+            state.quarantine = Some(QuarantineView::new(entries));
+            state.view_mode = ViewMode::Quarantine;
+        }
+        Err(e) => {
+            state.set_message(
+                format!("{} Cannot read quarantine: {}", Icons::CROSS, e),
+                MessageType::Error,
+            );
+        }
+    }
+}
+
+fn handle_quarantine_key(state: &mut AppState, code: KeyCode) {
+    let Some(view) = state.quarantine.as_mut() else {
+        state.view_mode = ViewMode::List;
+        return;
+    };
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => view.move_by(-1),
+        KeyCode::Down | KeyCode::Char('j') => view.move_by(1),
+        KeyCode::Esc | KeyCode::Char('q') => {
+            state.quarantine = None;
+            state.view_mode = ViewMode::List;
+        }
+        KeyCode::Enter | KeyCode::Char('r') => {
+            let Some(entry) = view.selected_entry().cloned() else {
+                return;
+            };
+            match entry.restore() {
+                Ok(()) => {
+                    view.remove_selected();
+                    state.refresh(scan_all_sources());
+                    state.set_message(
+                        format!("{} Restored {}", Icons::CHECK, entry.item.name),
+                        MessageType::Success,
+                    );
+                }
+                Err(e) => {
+                    state.set_message(
+                        format!("{} Cannot restore {}: {}", Icons::CROSS, entry.item.name, e),
+                        MessageType::Error,
+                    );
+                }
+            }
+        }
+        KeyCode::Char('p') | KeyCode::Delete => {
+            let Some(entry) = view.selected_entry().cloned() else {
+                return;
+            };
+            match entry.purge() {
+                Ok(()) => {
+                    view.remove_selected();
+                    state.set_message(
+                        format!("{} Purged {} for good", Icons::CHECK, entry.item.name),
+                        MessageType::Info,
+                    );
+                }
+                Err(e) => {
+                    state.set_message(
+                        format!("{} Cannot purge {}: {}", Icons::CROSS, entry.item.name, e),
+                        MessageType::Error,
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+
+    // Close once the last entry is gone
+    if state
+        .quarantine
+        .as_ref()
+        .is_some_and(|view| view.entries.is_empty())
+    {
+        state.quarantine = None;
+        state.view_mode = ViewMode::List;
+    }
+}
+
+fn open_profiles(state: &mut AppState) {
+    match list_profiles() {
+        Ok(profiles) => {
+            let mut view = ProfilesView::new(profiles, &state.all_items());
+            // Nothing to pick yet, so start by naming the first profile
+            if view.profiles.is_empty() {
+                view.new_name = Some(String::new());
+            }
+            state.profiles = Some(view);
+            state.view_mode = ViewMode::Profiles;
+        }
+        Err(e) => {
+            state.set_message(
+                format!("{} Cannot read profiles: {}", Icons::CROSS, e),
+                MessageType::Error,
+            );
+        }
+    }
+}
+
+fn handle_profiles_key(state: &mut AppState, code: KeyCode) {
+    let items = state.all_items();
+    let Some(view) = state.profiles.as_mut() else {
+        state.view_mode = ViewMode::List;
+        return;
+    };
+
+    // Typing the name to save the current state under
+    if let Some(name) = view.new_name.as_mut() {
+        match code {
+            KeyCode::Esc => view.new_name = None,
+            KeyCode::Char(c) => name.push(c),
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            KeyCode::Enter => {
+                let name = name.clone();
+                match save_profile(&name, &items).and_then(|_| list_profiles()) {
+                    Ok(profiles) => {
+                        *view = ProfilesView::new(profiles, &items);
+                        view.select_named(&name);
+                        view.update_plan(&items);
+                        state.set_message(
+                            format!("{} Saved profile {}", Icons::CHECK, name),
+                            MessageType::Success,
+                        );
+                    }
+                    Err(e) => view.error = Some(e.to_string()),
+                }
+            }
+            _ => {}
+        }
+        if state
+            .profiles
+            .as_ref()
+            .is_some_and(|view| view.profiles.is_empty() && view.new_name.is_none())
+        {
+            state.profiles = None;
+            state.view_mode = ViewMode::List;
+        }
+        return;
+    }
+
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            view.move_by(-1);
+            view.update_plan(&items);
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            view.move_by(1);
+            view.update_plan(&items);
+        }
+        KeyCode::Esc | KeyCode::Char('q') => {
+            state.profiles = None;
+            state.view_mode = ViewMode::List;
+        }
+        KeyCode::Char('s') => {
+            view.error = None;
+            view.new_name = Some(String::new());
+        }
+        KeyCode::Char('d') | KeyCode::Delete => {
+            let Some(name) = view.selected_profile().map(|profile| profile.name.clone()) else {
+                return;
+            };
+            match delete_profile(&name).and_then(|()| list_profiles()) {
+                Ok(profiles) => {
+                    *view = ProfilesView::new(profiles, &items);
+                    state.set_message(
+                        format!("{} Deleted profile {}", Icons::CHECK, name),
+                        MessageType::Info,
+                    );
+                }
+                Err(e) => view.error = Some(e.to_string()),
+            }
+        }
+        KeyCode::Enter => switch_to_selected_profile(state),
+        _ => {}
+    }
+}
+
+/// Apply the selected profile's plan and close the dialog
+fn switch_to_selected_profile(state: &mut AppState) {
+    let has_pending_changes = state.has_pending_changes();
+    let Some(view) = state.profiles.as_mut() else {
+        return;
+    };
+    let Some(profile) = view.selected_profile().cloned() else {
+        return;
+    };
+
+    // Switching rescans the items, which would drop pending changes
+    if has_pending_changes {
+        view.error = Some("Apply or undo pending changes before switching".to_string());
+        return;
+    }
+
+    let plan = view.plan.clone();
+    let missing = plan.missing.len();
+    if plan.is_empty() {
+        view.error = Some(format!("Everything already matches {}", profile.name));
+        return;
+    }
+
+    match switch_profile(&profile, &plan, state.transaction_mode) {
+        Ok(report) => {
+            state.profiles = None;
+            state.view_mode = ViewMode::List;
+            refresh(state);
+
+            let (mut message, message_type) = report_message(&report, plan.changes.len());
+            if missing > 0 {
+                message.push_str(&format!(
+                    " ({} items in {} no longer exist)",
+                    missing, profile.name
+                ));
+            }
+            state.set_message(message, message_type);
+        }
+        Err(e) => view.error = Some(e.to_string()),
+    }
+}
+
+fn open_add_item(state: &mut AppState) {
+    // Adding rescans the items, which would drop pending changes
+    if state.has_pending_changes() {
+        state.set_message(
+            format!(
+                "{} Apply or undo pending changes before adding items",
+                Icons::MISSING
+            ),
+            MessageType::Warning,
+        );
+        return;
+    }
+
+    state.add_item = Some(AddItemForm::new());
+    state.view_mode = ViewMode::AddItem;
+}
+
+fn handle_add_item_key(state: &mut AppState, code: KeyCode) {
+    let Some(form) = state.add_item.as_mut() else {
+        state.view_mode = ViewMode::List;
+        return;
+    };
+
+    match code {
+        KeyCode::Esc => {
+            state.add_item = None;
+            state.view_mode = ViewMode::List;
+        }
+        KeyCode::Tab | KeyCode::Down => form.move_field(1),
+        KeyCode::BackTab | KeyCode::Up => form.move_field(-1),
+        KeyCode::Left | KeyCode::Right if !form.field.is_text() => form.cycle(),
+        KeyCode::Char(' ') if !form.field.is_text() => form.cycle(),
+        KeyCode::Char(c) => form.input(c),
+        KeyCode::Backspace => form.backspace(),
+        KeyCode::Enter => {
+            let new = form.to_new_item();
+            match create_item(&new) {
+                Ok(item) => {
+                    state.add_item = None;
+                    state.view_mode = ViewMode::List;
+                    refresh(state);
+                    state.set_message(
+                        format!(
+                            "{} Added {} to {}",
+                            Icons::CHECK,
+                            item.name,
+                            item.source.short_name()
+                        ),
+                        MessageType::Success,
+                    );
+                }
+                Err(e) => form.error = Some(e.to_string()),
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Switch whether services are set to Disabled or Manual when disabled.
+/// The choice is saved, since the services source reads it when applying.
+fn toggle_service_disable_mode(state: &mut AppState) {
+    let result = ServiceState::load().and_then(|mut service_state| {
+        service_state.disable_as = service_state.disable_as.toggle();
+        service_state.save().map(|()| service_state.disable_as)
+    });
+
+    match result {
+        Ok(mode) => {
+            state.service_disable_mode = mode;
+            state.set_message(
+                format!("{} Disable services as: {}", Icons::INFO, mode.display()),
+                MessageType::Info,
+            );
+        }
+        Err(e) => state.set_message(format!("{} {}", Icons::CROSS, e), MessageType::Error),
+    }
+}
+
+fn open_edit_item(state: &mut AppState) {
+    let Some(item) = state.selected_startup_item() else {
+        return;
+    };
+
+    if item.requires_admin && !state.is_admin {
+        state.set_message(
+            "Cannot edit: Administrator privileges required".to_string(),
+            MessageType::Warning,
+        );
+        return;
+    }
+
+    // Editing rescans the items, which would drop pending changes
+    if state.has_pending_changes() {
+        state.set_message(
+            format!(
+                "{} Apply or undo pending changes before editing",
+                Icons::MISSING
+            ),
+            MessageType::Warning,
+        );
+        return;
+    }
+
+    state.edit_item = Some(EditItemForm::new(item.clone()));
+    state.view_mode = ViewMode::EditItem;
+}
+
+fn handle_edit_item_key(state: &mut AppState, code: KeyCode) {
+    let Some(form) = state.edit_item.as_mut() else {
+        state.view_mode = ViewMode::List;
+        return;
+    };
+
+    match code {
+        KeyCode::Esc => {
+            state.edit_item = None;
+            state.view_mode = ViewMode::List;
+        }
+        KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => form.toggle_field(),
+        KeyCode::Char(c) => form.input(c),
+        KeyCode::Backspace => form.backspace(),
+        KeyCode::Enter => {
+            let edit = form.to_edit();
+            match apply_edit(&form.item, &edit) {
+                Ok(()) => {
+                    let name = form.item.name.clone();
+                    state.edit_item = None;
+                    state.view_mode = ViewMode::List;
+                    refresh(state);
+                    state.set_message(
+                        format!("{} Edited {}", Icons::CHECK, name),
+                        MessageType::Success,
+                    );
+                }
+                Err(e) => form.error = Some(e.to_string()),
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Undelay the selected item if it is delayed, otherwise ask for the delay.
+/// Services need no delay, they switch to delayed automatic start.
+fn toggle_delay(state: &mut AppState) {
+    let Some(item) = state.selected_startup_item().cloned() else {
+        return;
+    };
+
+    if item.requires_admin && !state.is_admin {
+        state.set_message(
+            "Cannot delay: Administrator privileges required".to_string(),
+            MessageType::Warning,
+        );
+        return;
+    }
+
+    // Delaying rescans the items, which would drop pending changes
+    if state.has_pending_changes() {
+        state.set_message(
+            format!(
+                "{} Apply or undo pending changes before delaying",
+                Icons::MISSING
+            ),
+            MessageType::Warning,
+        );
+        return;
+    }
+
+    let result = match find_delayed(&item) {
+        Ok(Some(link)) => {
+            undelay_item(&link).map(|()| format!("{} is no longer delayed", link.item.name))
+        }
+        Ok(None) if item.source == SourceType::WindowsService => {
+            delay_item(&item, DEFAULT_DELAY_SECS)
+                .map(|_| format!("{} now uses delayed automatic start", item.name))
+        }
+        Ok(None) => {
+            state.delay = Some(DelayForm::new(item));
+            state.view_mode = ViewMode::Delay;
+            return;
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(message) => {
+            refresh(state);
+            state.set_message(
+                format!("{} {}", Icons::CHECK, message),
+                MessageType::Success,
+            );
+        }
+        Err(e) => state.set_message(format!("{} {}", Icons::CROSS, e), MessageType::Error),
+    }
+}
+
+fn handle_delay_key(state: &mut AppState, code: KeyCode) {
+    let Some(form) = state.delay.as_mut() else {
+        state.view_mode = ViewMode::List;
+        return;
+    };
+
+    match code {
+        KeyCode::Esc => {
+            state.delay = None;
+            state.view_mode = ViewMode::List;
+        }
+        KeyCode::Char(c) => form.input(c),
+        KeyCode::Backspace => form.backspace(),
+        KeyCode::Enter => {
+            let Some(delay_secs) = form.delay_secs() else {
+                form.error = Some("Enter the delay in seconds".to_string());
+                return;
+            };
+            match delay_item(&form.item, delay_secs) {
+                Ok(link) => {
+                    let name = form.item.name.clone();
+                    state.delay = None;
+                    state.view_mode = ViewMode::List;
+                    refresh(state);
+                    state.set_message(
+                        format!(
+                            "{} {} now starts {}s after logon",
+                            Icons::CHECK,
+                            name,
+                            link.delay_secs
+                        ),
+                        MessageType::Success,
+                    );
+                }
+                Err(e) => form.error = Some(e.to_string()),
+            }
         }
+        _ => {}
     }
 }
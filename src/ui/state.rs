@@ -2,14 +2,28 @@ use std::collections::{HashMap, HashSet};
 
 use ratatui::widgets::ListState;
 
+use crate::error::Result;
+use crate::models::command_line::split_arguments;
 use crate::models::{ItemStatus, SourceType, StartupItem};
+use crate::operations::{
+    BackupEntry, ItemEdit, NewItem, NewItemKind, Profile, ProfilePlan, QuarantineEntry,
+    RestorePlan, RestoreResult, SnapshotDiff, TransactionMode, DEFAULT_DELAY_SECS,
+};
 use crate::permissions::is_elevated;
+use crate::sources::{ServiceDisableMode, ServiceState};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ViewMode {
     List,
     Help,
     Confirm,
+    Restore,
+    Diff,
+    Quarantine,
+    AddItem,
+    EditItem,
+    Delay,
+    Profiles,
 }
 
 #[derive(Debug, Clone)]
@@ -19,7 +33,7 @@ pub enum DisplayItem {
         count: usize,
         collapsed: bool,
     },
-    Item(StartupItem),
+    Item(Box<StartupItem>),
 }
 
 #[derive(Debug, Clone)]
@@ -39,8 +53,15 @@ pub struct AppState {
     /// List widget state
     pub list_state: ListState,
 
-    /// Items with pending changes
-    pub pending_changes: HashMap<String, PendingChange>,
+    /// Items with pending changes, in the order they were made, which is
+    /// the order they are applied in
+    pub pending_changes: Vec<PendingChange>,
+
+    /// Whether one failed change undoes the others when applying
+    pub transaction_mode: TransactionMode,
+
+    /// What services are set to when disabled
+    pub service_disable_mode: ServiceDisableMode,
 
     /// Current view mode
     pub view_mode: ViewMode,
@@ -56,6 +77,464 @@ pub struct AppState {
 
     /// Show confirmation dialog
     pub confirm_action: Option<ConfirmAction>,
+
+    /// Restore dialog, while it is open
+    pub restore: Option<RestoreView>,
+
+    /// Diff dialog, while it is open
+    pub diff: Option<DiffView>,
+
+    /// Quarantine dialog, while it is open
+    pub quarantine: Option<QuarantineView>,
+
+    /// Add item form, while it is open
+    pub add_item: Option<AddItemForm>,
+
+    /// Edit item form, while it is open
+    pub edit_item: Option<EditItemForm>,
+
+    /// Delay form, while it is open
+    pub delay: Option<DelayForm>,
+
+    /// Profiles dialog, while it is open
+    pub profiles: Option<ProfilesView>,
+}
+
+/// Delay form: how long after logon the selected item starts
+#[derive(Debug, Clone)]
+pub struct DelayForm {
+    pub item: StartupItem,
+    pub seconds: String,
+    /// Why the last attempt to delay the item failed
+    pub error: Option<String>,
+}
+
+impl DelayForm {
+    pub fn new(item: StartupItem) -> Self {
+        Self {
+            item,
+            seconds: DEFAULT_DELAY_SECS.to_string(),
+            error: None,
+        }
+    }
+
+    /// Only digits are accepted
+    pub fn input(&mut self, c: char) {
+        if c.is_ascii_digit() {
+            self.seconds.push(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.seconds.pop();
+    }
+
+    pub fn delay_secs(&self) -> Option<u32> {
+        self.seconds.parse().ok()
+    }
+}
+
+/// Fields of the edit item form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditField {
+    Name,
+    Command,
+}
+
+/// Edit item form: a new name and command line for the selected item
+#[derive(Debug, Clone)]
+pub struct EditItemForm {
+    pub item: StartupItem,
+    pub name: String,
+    pub command: String,
+    pub field: EditField,
+    /// Why the last attempt to save the edit failed
+    pub error: Option<String>,
+}
+
+impl EditItemForm {
+    pub fn new(item: StartupItem) -> Self {
+        let edit = ItemEdit::for_item(&item);
+        Self {
+            item,
+            name: edit.name,
+            command: edit.command,
+            field: EditField::Command,
+            error: None,
+        }
+    }
+
+    pub fn toggle_field(&mut self) {
+        self.field = match self.field {
+            EditField::Name => EditField::Command,
+            EditField::Command => EditField::Name,
+        };
+    }
+
+    fn text_mut(&mut self) -> &mut String {
+        match self.field {
+            EditField::Name => &mut self.name,
+            EditField::Command => &mut self.command,
+        }
+    }
+
+    pub fn input(&mut self, c: char) {
+        self.text_mut().push(c);
+    }
+
+    pub fn backspace(&mut self) {
+        self.text_mut().pop();
+    }
+
+    pub fn to_edit(&self) -> ItemEdit {
+        ItemEdit {
+            name: self.name.clone(),
+            command: self.command.trim().to_string(),
+        }
+    }
+}
+
+/// Fields of the add item form, in the order Tab moves through them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddField {
+    Name,
+    Kind,
+    Scope,
+    Target,
+    Arguments,
+}
+
+impl AddField {
+    pub fn all() -> &'static [AddField] {
+        &[
+            Self::Name,
+            Self::Kind,
+            Self::Scope,
+            Self::Target,
+            Self::Arguments,
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Name => "Name",
+            Self::Kind => "Register as",
+            Self::Scope => "For",
+            Self::Target => "Program",
+            Self::Arguments => "Arguments",
+        }
+    }
+
+    /// Whether the field is typed into rather than picked
+    pub fn is_text(&self) -> bool {
+        matches!(self, Self::Name | Self::Target | Self::Arguments)
+    }
+}
+
+/// Add item form: what to run and where to register it
+#[derive(Debug, Clone)]
+pub struct AddItemForm {
+    pub name: String,
+    pub kind: NewItemKind,
+    pub all_users: bool,
+    pub target: String,
+    /// Typed like a command line, split when the item is created
+    pub arguments: String,
+    pub field: AddField,
+    /// Why the last attempt to add the item failed
+    pub error: Option<String>,
+}
+
+impl AddItemForm {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            kind: NewItemKind::default(),
+            all_users: false,
+            target: String::new(),
+            arguments: String::new(),
+            field: AddField::Name,
+            error: None,
+        }
+    }
+
+    pub fn move_field(&mut self, delta: isize) {
+        let all = AddField::all();
+        let current = all.iter().position(|f| *f == self.field).unwrap_or(0) as isize;
+        self.field = all[(current + delta).rem_euclid(all.len() as isize) as usize];
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self.field {
+            AddField::Name => Some(&mut self.name),
+            AddField::Target => Some(&mut self.target),
+            AddField::Arguments => Some(&mut self.arguments),
+            AddField::Kind | AddField::Scope => None,
+        }
+    }
+
+    pub fn input(&mut self, c: char) {
+        if let Some(text) = self.text_mut() {
+            text.push(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(text) = self.text_mut() {
+            text.pop();
+        }
+    }
+
+    /// Change the picked value of the kind or scope field
+    pub fn cycle(&mut self) {
+        match self.field {
+            AddField::Kind => self.kind = self.kind.next(),
+            AddField::Scope => self.all_users = !self.all_users,
+            _ => {}
+        }
+    }
+
+    pub fn to_new_item(&self) -> NewItem {
+        // A pasted path may come with quotes around it
+        let target = self.target.trim().trim_matches('"');
+        NewItem::new(self.name.clone(), self.kind, target)
+            .with_arguments(split_arguments(&self.arguments))
+            .with_all_users(self.all_users)
+    }
+}
+
+impl Default for AddItemForm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Quarantine dialog: deleted items that can still be brought back
+pub struct QuarantineView {
+    pub entries: Vec<QuarantineEntry>,
+    pub list_state: ListState,
+}
+
+impl QuarantineView {
+    pub fn new(entries: Vec<QuarantineEntry>) -> Self {
+        let mut list_state = ListState::default();
+        if !entries.is_empty() {
+            list_state.select(Some(0));
+        }
+
+        Self {
+            entries,
+            list_state,
+        }
+    }
+
+    pub fn selected_entry(&self) -> Option<&QuarantineEntry> {
+        self.list_state.selected().and_then(|i| self.entries.get(i))
+    }
+
+    /// Drop the selected entry after it was restored or purged
+    pub fn remove_selected(&mut self) {
+        let Some(index) = self.list_state.selected() else {
+            return;
+        };
+        if index < self.entries.len() {
+            self.entries.remove(index);
+        }
+        self.list_state.select(match self.entries.len() {
+            0 => None,
+            len => Some(index.min(len - 1)),
+        });
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        let len = self.entries.len();
+        if len == 0 {
+            return;
+        }
+
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        self.list_state
+            .select(Some((current + delta).rem_euclid(len as isize) as usize));
+    }
+}
+
+/// Profiles dialog: saved sets of item statuses to switch between
+pub struct ProfilesView {
+    pub profiles: Vec<Profile>,
+    pub list_state: ListState,
+    /// What switching to the selected profile changes
+    pub plan: ProfilePlan,
+    /// Name being typed to save the current state under
+    pub new_name: Option<String>,
+    /// Why the last action failed
+    pub error: Option<String>,
+}
+
+impl ProfilesView {
+    pub fn new(profiles: Vec<Profile>, items: &[StartupItem]) -> Self {
+        let mut view = Self {
+            profiles,
+            list_state: ListState::default(),
+            plan: ProfilePlan::default(),
+            new_name: None,
+            error: None,
+        };
+        if !view.profiles.is_empty() {
+            view.list_state.select(Some(0));
+        }
+        view.update_plan(items);
+        view
+    }
+
+    pub fn selected_profile(&self) -> Option<&Profile> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.profiles.get(i))
+    }
+
+    /// Recompute the plan for the selected profile
+    pub fn update_plan(&mut self, items: &[StartupItem]) {
+        self.plan = self
+            .selected_profile()
+            .map(|profile| profile.plan(items))
+            .unwrap_or_default();
+    }
+
+    /// Select the profile with this name, if there is one
+    pub fn select_named(&mut self, name: &str) {
+        if let Some(index) = self.profiles.iter().position(|p| p.name == name) {
+            self.list_state.select(Some(index));
+        }
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        let len = self.profiles.len();
+        if len == 0 {
+            return;
+        }
+
+        let current = self.list_state.selected().unwrap_or(0) as isize;
+        self.list_state
+            .select(Some((current + delta).rem_euclid(len as isize) as usize));
+    }
+}
+
+/// Diff dialog: pick a backup, then see what changed since
+pub struct DiffView {
+    pub backups: Vec<BackupEntry>,
+    pub backup_state: ListState,
+    pub diff: Option<SnapshotDiff>,
+    pub diff_state: ListState,
+}
+
+impl DiffView {
+    pub fn new(backups: Vec<BackupEntry>) -> Self {
+        let mut backup_state = ListState::default();
+        if !backups.is_empty() {
+            backup_state.select(Some(0));
+        }
+
+        Self {
+            backups,
+            backup_state,
+            diff: None,
+            diff_state: ListState::default(),
+        }
+    }
+
+    /// Compare the selected backup with the live items
+    pub fn open_diff(&mut self, live: &[StartupItem]) -> Result<()> {
+        let Some(entry) = self
+            .backup_state
+            .selected()
+            .and_then(|i| self.backups.get(i))
+        else {
+            return Ok(());
+        };
+
+        let backup = entry.load()?;
+        let diff = SnapshotDiff::between(&entry.file_name, &backup.items, "live", live);
+        self.diff_state = ListState::default();
+        if !diff.is_empty() {
+            self.diff_state.select(Some(0));
+        }
+        self.diff = Some(diff);
+        Ok(())
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        let (state, len) = match &self.diff {
+            Some(diff) => (&mut self.diff_state, diff.changes.len()),
+            None => (&mut self.backup_state, self.backups.len()),
+        };
+        if len == 0 {
+            return;
+        }
+
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).rem_euclid(len as isize) as usize));
+    }
+}
+
+/// Restore dialog: pick a backup, review its plan, then see the results
+pub struct RestoreView {
+    pub backups: Vec<BackupEntry>,
+    pub backup_state: ListState,
+    pub plan: Option<RestorePlan>,
+    pub plan_state: ListState,
+    pub results: Option<Vec<RestoreResult>>,
+}
+
+impl RestoreView {
+    pub fn new(backups: Vec<BackupEntry>) -> Self {
+        let mut backup_state = ListState::default();
+        if !backups.is_empty() {
+            backup_state.select(Some(0));
+        }
+
+        Self {
+            backups,
+            backup_state,
+            plan: None,
+            plan_state: ListState::default(),
+            results: None,
+        }
+    }
+
+    pub fn selected_backup(&self) -> Option<&BackupEntry> {
+        self.backup_state
+            .selected()
+            .and_then(|i| self.backups.get(i))
+    }
+
+    /// Show the plan for the selected backup against the live items
+    pub fn open_plan(&mut self, live: &[StartupItem]) -> Result<()> {
+        let Some(entry) = self.selected_backup() else {
+            return Ok(());
+        };
+
+        let plan = RestorePlan::new(&entry.load()?, live);
+        self.plan_state = ListState::default();
+        if !plan.is_empty() {
+            self.plan_state.select(Some(0));
+        }
+        self.plan = Some(plan);
+        Ok(())
+    }
+
+    /// Move the cursor of whichever list is showing
+    pub fn move_by(&mut self, delta: isize) {
+        let (state, len) = match &self.plan {
+            Some(plan) => (&mut self.plan_state, plan.len()),
+            None => (&mut self.backup_state, self.backups.len()),
+        };
+        if len == 0 {
+            return;
+        }
+
+        let current = state.selected().unwrap_or(0) as isize;
+        state.select(Some((current + delta).rem_euclid(len as isize) as usize));
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -71,6 +550,21 @@ pub enum ConfirmAction {
     ApplyChanges,
     DiscardChanges,
     Quit,
+    DeleteItem { id: String, name: String },
+}
+
+impl ConfirmAction {
+    /// The question shown in the confirmation dialog
+    pub fn prompt(&self) -> String {
+        match self {
+            ConfirmAction::ApplyChanges => "Apply pending changes?".to_string(),
+            ConfirmAction::DiscardChanges => "Discard pending changes?".to_string(),
+            ConfirmAction::Quit => "Quit?".to_string(),
+            ConfirmAction::DeleteItem { name, .. } => {
+                format!("Delete {name}? A copy is kept in the quarantine.")
+            }
+        }
+    }
 }
 
 impl AppState {
@@ -80,27 +574,33 @@ impl AppState {
         // Group items by source
         let mut items_by_source: HashMap<SourceType, Vec<StartupItem>> = HashMap::new();
         for item in items {
-            items_by_source
-                .entry(item.source)
-                .or_default()
-                .push(item);
+            items_by_source.entry(item.source).or_default().push(item);
         }
 
         // Sort items within each group
         for items in items_by_source.values_mut() {
-            items.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+            items.sort_by_key(|item| item.name.to_lowercase());
         }
 
         let mut state = Self {
             items_by_source,
             display_list: Vec::new(),
             list_state: ListState::default(),
-            pending_changes: HashMap::new(),
+            pending_changes: Vec::new(),
+            transaction_mode: TransactionMode::default(),
+            service_disable_mode: ServiceState::load().unwrap_or_default().disable_as,
             view_mode: ViewMode::List,
             is_admin,
             status_message: None,
             collapsed_groups: HashSet::new(),
             confirm_action: None,
+            restore: None,
+            diff: None,
+            quarantine: None,
+            add_item: None,
+            edit_item: None,
+            delay: None,
+            profiles: None,
         };
 
         state.rebuild_display_list();
@@ -134,7 +634,8 @@ impl AppState {
                 // Add items if not collapsed
                 if !collapsed {
                     for item in items {
-                        self.display_list.push(DisplayItem::Item(item.clone()));
+                        self.display_list
+                            .push(DisplayItem::Item(Box::new(item.clone())));
                     }
                 }
             }
@@ -146,8 +647,7 @@ impl AppState {
     }
 
     pub fn selected_item(&self) -> Option<&DisplayItem> {
-        self.selected_index()
-            .and_then(|i| self.display_list.get(i))
+        self.selected_index().and_then(|i| self.display_list.get(i))
     }
 
     pub fn selected_startup_item(&self) -> Option<&StartupItem> {
@@ -190,6 +690,7 @@ impl AppState {
             return;
         };
 
+        let mut note = None;
         match &self.display_list[index] {
             DisplayItem::GroupHeader { source, .. } => {
                 // Toggle collapse
@@ -224,10 +725,17 @@ impl AppState {
                     return;
                 }
 
+                if !item.status.can_toggle() {
+                    self.set_message(
+                        format!("Cannot modify: {}", item.status.display().to_lowercase()),
+                        MessageType::Warning,
+                    );
+                    return;
+                }
+
                 // Get current effective status (considering pending changes)
                 let current_status = self
-                    .pending_changes
-                    .get(&item_id)
+                    .pending_change(&item_id)
                     .map(|c| c.new_status)
                     .unwrap_or(item.status);
 
@@ -236,24 +744,45 @@ impl AppState {
                 // Update or create pending change
                 if new_status == item.status {
                     // Change would revert to original, remove pending change
-                    self.pending_changes.remove(&item_id);
+                    self.pending_changes.retain(|c| c.item_id != item_id);
                 } else {
-                    self.pending_changes.insert(
-                        item_id.clone(),
-                        PendingChange {
+                    if item.source == SourceType::WindowsService && new_status.is_disabled() {
+                        note = Some(format!(
+                            "{} will be set to {} (S to switch)",
+                            item.name,
+                            self.service_disable_mode
+                                .for_service(item.start_mode)
+                                .display()
+                        ));
+                    }
+                    // A change made again keeps its place in the order
+                    match self
+                        .pending_changes
+                        .iter_mut()
+                        .find(|c| c.item_id == item_id)
+                    {
+                        Some(change) => change.new_status = new_status,
+                        None => self.pending_changes.push(PendingChange {
                             item_id,
                             old_status: item.status,
                             new_status,
-                        },
-                    );
+                        }),
+                    }
                 }
             }
         }
+
+        if let Some(note) = note {
+            self.set_message(note, MessageType::Info);
+        }
+    }
+
+    pub fn pending_change(&self, item_id: &str) -> Option<&PendingChange> {
+        self.pending_changes.iter().find(|c| c.item_id == item_id)
     }
 
     pub fn get_effective_status(&self, item: &StartupItem) -> ItemStatus {
-        self.pending_changes
-            .get(&item.id)
+        self.pending_change(&item.id)
             .map(|c| c.new_status)
             .unwrap_or(item.status)
     }
@@ -278,6 +807,17 @@ impl AppState {
         self.status_message = None;
     }
 
+    pub fn all_items(&self) -> Vec<StartupItem> {
+        self.items_by_source.values().flatten().cloned().collect()
+    }
+
+    pub fn find_item(&self, id: &str) -> Option<&StartupItem> {
+        self.items_by_source
+            .values()
+            .flatten()
+            .find(|item| item.id == id)
+    }
+
     pub fn total_items(&self) -> usize {
         self.items_by_source.values().map(|v| v.len()).sum()
     }
@@ -297,7 +837,7 @@ impl AppState {
 
         // Sort items within each group
         for items in self.items_by_source.values_mut() {
-            items.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+            items.sort_by_key(|item| item.name.to_lowercase());
         }
 
         // Rebuild display
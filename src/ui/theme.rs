@@ -343,6 +343,9 @@ impl Icons {
     pub const CHECKBOX_ON: &'static str = "◉";
     pub const CHECKBOX_OFF: &'static str = "○";
     pub const CHECKBOX_UNKNOWN: &'static str = "◌";
+    pub const CHECKBOX_ONCE: &'static str = "◎";
+    pub const CHECKBOX_LOCKED: &'static str = "⊘";
+    pub const CHECKBOX_TRIGGERED: &'static str = "◑";
 
     // Group arrows
     pub const ARROW_RIGHT: &'static str = "▸";
@@ -361,6 +364,8 @@ impl Icons {
     pub const TASK: &'static str = "⏱";
     pub const FOLDER: &'static str = "📁";
     pub const REGISTRY: &'static str = "📝";
+    pub const SCRIPT: &'static str = "📜";
+    pub const LINUX: &'static str = "🐧";
 
     // Actions
     pub const CHECK: &'static str = "✓";
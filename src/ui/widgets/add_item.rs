@@ -0,0 +1,108 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::ui::state::{AddField, AddItemForm};
+use crate::ui::{AppState, Icons, Theme};
+
+pub fn render_add_item(frame: &mut Frame, area: Rect, state: &AppState) {
+    let Some(form) = &state.add_item else {
+        return;
+    };
+
+    let popup_area = centered_rect(65, 55, area);
+    frame.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(Theme::border_focused())
+        .title(format!(" {} Add Startup Item ", Icons::LOGO))
+        .title_style(Theme::header_accent());
+
+    let mut lines = vec![Line::from("")];
+    for field in AddField::all() {
+        lines.push(field_line(form, *field));
+    }
+
+    let new = form.to_new_item();
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(format!("  {:<13}", "Source"), Theme::detail_label()),
+        Span::styled(new.source().display_name(), Theme::detail_muted()),
+    ]));
+    if !form.target.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<13}", "Command"), Theme::detail_label()),
+            Span::styled(new.command_line(), Theme::detail_muted()),
+        ]));
+    }
+
+    if let Some(error) = &form.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {} {}", Icons::CROSS, error),
+            Theme::error(),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, chunks[0]);
+
+    let hint = if form.field.is_text() {
+        "  Tab next field  ·  Enter add  ·  Esc cancel"
+    } else {
+        "  Tab next field  ·  ←/→ change  ·  Enter add  ·  Esc cancel"
+    };
+    let footer = Paragraph::new(Line::from(Span::styled(hint, Theme::status_description())))
+        .style(Theme::status_bar());
+    frame.render_widget(footer, chunks[1]);
+}
+
+fn field_line(form: &AddItemForm, field: AddField) -> Line<'static> {
+    let focused = form.field == field;
+    let value = match field {
+        AddField::Name => form.name.clone(),
+        AddField::Kind => format!("‹ {} ›", form.kind.display()),
+        AddField::Scope => {
+            if form.all_users {
+                "‹ All users (needs administrator) ›".to_string()
+            } else {
+                "‹ Current user ›".to_string()
+            }
+        }
+        AddField::Target => form.target.clone(),
+        AddField::Arguments => form.arguments.clone(),
+    };
+    let value = if focused && field.is_text() {
+        format!("{value}▏")
+    } else {
+        value
+    };
+
+    let marker = if focused { Icons::ARROW_RIGHT } else { " " };
+    Line::from(vec![
+        Span::styled(format!(" {marker}"), Theme::header_accent()),
+        Span::styled(format!("{:<13}", field.label()), Theme::detail_label()),
+        Span::styled(
+            value,
+            if focused {
+                Theme::item_selected()
+            } else {
+                Theme::detail_value()
+            },
+        ),
+    ])
+}
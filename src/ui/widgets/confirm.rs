@@ -0,0 +1,44 @@
+use ratatui::{
+    layout::{Alignment, Rect},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::ui::{AppState, Icons, Theme};
+
+pub fn render_confirm(frame: &mut Frame, area: Rect, state: &AppState) {
+    let Some(action) = &state.confirm_action else {
+        return;
+    };
+
+    let popup_area = centered_rect(50, 25, area);
+    frame.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(Theme::border_focused())
+        .title(format!(" {} Confirm ", Icons::MISSING))
+        .title_style(Theme::header_accent());
+
+    let text = vec![
+        Line::from(""),
+        Line::from(Span::styled(action.prompt(), Theme::detail_value())),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(" y ", Theme::status_key()),
+            Span::styled(" yes   ", Theme::status_description()),
+            Span::styled(" n ", Theme::status_key()),
+            Span::styled(" no", Theme::status_description()),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    frame.render_widget(paragraph, popup_area);
+}
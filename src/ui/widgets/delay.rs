@@ -0,0 +1,68 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::ui::{AppState, Icons, Theme};
+
+pub fn render_delay(frame: &mut Frame, area: Rect, state: &AppState) {
+    let Some(form) = &state.delay else {
+        return;
+    };
+
+    let popup_area = centered_rect(60, 35, area);
+    frame.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(Theme::border_focused())
+        .title(format!(" {} Delay {} ", Icons::LOGO, form.item.name))
+        .title_style(Theme::header_accent());
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(format!(" {}", Icons::ARROW_RIGHT), Theme::header_accent()),
+            Span::styled(format!("{:<10}", "Seconds"), Theme::detail_label()),
+            Span::styled(format!("{}▏", form.seconds), Theme::item_selected()),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "  The entry in {} is disabled and a logon task starts it after the delay",
+                form.item.source.display_name()
+            ),
+            Theme::detail_muted(),
+        )),
+    ];
+
+    if let Some(error) = &form.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {} {}", Icons::CROSS, error),
+            Theme::error(),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, chunks[0]);
+
+    let footer = Paragraph::new(Line::from(Span::styled(
+        "  Enter delay  ·  Esc cancel",
+        Theme::status_description(),
+    )))
+    .style(Theme::status_bar());
+    frame.render_widget(footer, chunks[1]);
+}
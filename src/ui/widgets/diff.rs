@@ -0,0 +1,100 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use super::{backup_list_items, centered_rect};
+use crate::operations::ChangeKind;
+use crate::ui::{AppState, Icons, Theme};
+
+pub fn render_diff(frame: &mut Frame, area: Rect, state: &mut AppState) {
+    let Some(view) = state.diff.as_mut() else {
+        return;
+    };
+
+    let popup_area = centered_rect(80, 70, area);
+    frame.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(popup_area);
+
+    let hint = if view.diff.is_some() {
+        "  Esc back"
+    } else {
+        "  Enter compare with current items  ·  Esc close"
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(Theme::border_focused())
+        .title_style(Theme::header_accent());
+
+    match &view.diff {
+        Some(diff) if diff.is_empty() => {
+            let block = block.title(format!(" {} Changes Since {} ", Icons::LOGO, diff.from));
+            let message = Paragraph::new(Line::from(Span::styled(
+                format!("  {} No changes since the backup", Icons::INFO),
+                Theme::detail_muted(),
+            )))
+            .block(block);
+            frame.render_widget(message, chunks[0]);
+        }
+        Some(diff) => {
+            let items: Vec<ListItem> = diff
+                .changes
+                .iter()
+                .map(|change| {
+                    let style = match change.kind {
+                        ChangeKind::Added => Theme::success(),
+                        ChangeKind::Removed => Theme::error(),
+                        _ => Theme::item_pending(),
+                    };
+                    let mut spans = vec![
+                        Span::styled(
+                            format!(" {} {:<8} ", change.kind.symbol(), change.kind.display()),
+                            style,
+                        ),
+                        Span::styled(change.name.clone(), Theme::detail_value()),
+                        Span::styled(
+                            format!("  {}", change.source.short_name()),
+                            Theme::detail_muted(),
+                        ),
+                    ];
+                    let summary = change.summary();
+                    if !summary.is_empty() {
+                        spans.push(Span::styled(
+                            format!("  {}  {summary}", Icons::DOT),
+                            Theme::detail_muted(),
+                        ));
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect();
+
+            let block = block
+                .title(format!(" {} Changes Since {} ", Icons::LOGO, diff.from))
+                .title_bottom(format!(" {} ", diff.summary()));
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(Theme::item_selected());
+            frame.render_stateful_widget(list, chunks[0], &mut view.diff_state);
+        }
+        None => {
+            let block = block.title(format!(" {} Compare With Backup ", Icons::LOGO));
+            let list = List::new(backup_list_items(&view.backups))
+                .block(block)
+                .highlight_style(Theme::item_selected());
+            frame.render_stateful_widget(list, chunks[0], &mut view.backup_state);
+        }
+    }
+
+    let footer = Paragraph::new(Line::from(Span::styled(hint, Theme::status_description())))
+        .style(Theme::status_bar());
+    frame.render_widget(footer, chunks[1]);
+}
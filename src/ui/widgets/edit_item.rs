@@ -0,0 +1,85 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::ui::state::EditField;
+use crate::ui::{AppState, Icons, Theme};
+
+pub fn render_edit_item(frame: &mut Frame, area: Rect, state: &AppState) {
+    let Some(form) = &state.edit_item else {
+        return;
+    };
+
+    let popup_area = centered_rect(70, 40, area);
+    frame.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(Theme::border_focused())
+        .title(format!(" {} Edit {} ", Icons::LOGO, form.item.name))
+        .title_style(Theme::header_accent());
+
+    let field_line = |field: EditField, label: &str, value: &str| {
+        let focused = form.field == field;
+        let marker = if focused { Icons::ARROW_RIGHT } else { " " };
+        let value = if focused {
+            format!("{value}▏")
+        } else {
+            value.to_string()
+        };
+        Line::from(vec![
+            Span::styled(format!(" {marker}"), Theme::header_accent()),
+            Span::styled(format!("{label:<10}"), Theme::detail_label()),
+            Span::styled(
+                value,
+                if focused {
+                    Theme::item_selected()
+                } else {
+                    Theme::detail_value()
+                },
+            ),
+        ])
+    };
+
+    let mut lines = vec![
+        Line::from(""),
+        field_line(EditField::Name, "Name", &form.name),
+        field_line(EditField::Command, "Command", &form.command),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(format!("  {:<10}", "Source"), Theme::detail_label()),
+            Span::styled(form.item.source.display_name(), Theme::detail_muted()),
+        ]),
+    ];
+
+    if let Some(error) = &form.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {} {}", Icons::CROSS, error),
+            Theme::error(),
+        )));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, chunks[0]);
+
+    let footer = Paragraph::new(Line::from(Span::styled(
+        "  Tab switch field  ·  Enter save  ·  Esc cancel",
+        Theme::status_description(),
+    )))
+    .style(Theme::status_bar());
+    frame.render_widget(footer, chunks[1]);
+}
@@ -1,16 +1,17 @@
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::Rect,
     symbols::border,
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
 
+use super::centered_rect;
 use crate::ui::{Icons, Theme};
 
 pub fn render_help(frame: &mut Frame, area: Rect) {
     // Center the help dialog
-    let popup_area = centered_rect(55, 65, area);
+    let popup_area = centered_rect(55, 70, area);
 
     // Clear the background
     frame.render_widget(Clear, popup_area);
@@ -31,7 +32,14 @@ pub fn render_help(frame: &mut Frame, area: Rect) {
                 ("Space", "Toggle item"),
                 ("Tab", "Expand/collapse"),
                 ("a", "Apply changes"),
+                ("m", "All-or-nothing/best effort"),
+                ("S", "Disable services as Disabled/Manual"),
                 ("u", "Undo pending"),
+                ("n", "Add item"),
+                ("e", "Edit item"),
+                ("L", "Delay/undelay item"),
+                ("P", "Profiles"),
+                ("x", "Delete item"),
             ],
         ),
         (
@@ -39,6 +47,9 @@ pub fn render_help(frame: &mut Frame, area: Rect) {
             vec![
                 ("r", "Refresh list"),
                 ("b", "Create backup"),
+                ("R", "Restore backup"),
+                ("D", "Changes since backup"),
+                ("Q", "Quarantine"),
                 ("?", "Toggle help"),
                 ("q", "Quit app"),
             ],
@@ -121,23 +132,3 @@ pub fn render_help(frame: &mut Frame, area: Rect) {
 
     frame.render_widget(paragraph, popup_area);
 }
-
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
-}
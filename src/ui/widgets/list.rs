@@ -25,7 +25,7 @@ pub fn render_list(frame: &mut Frame, area: Rect, state: &mut AppState) {
                 } => render_group_header(source, *count, *collapsed, is_selected),
                 DisplayItem::Item(item) => {
                     let effective_status = state.get_effective_status(item);
-                    let has_pending = state.pending_changes.contains_key(&item.id);
+                    let has_pending = state.pending_change(&item.id).is_some();
                     render_item(item, effective_status, has_pending, is_selected, state.is_admin)
                 }
             }
@@ -98,6 +98,9 @@ fn render_item(
     let checkbox = match effective_status {
         ItemStatus::Enabled => Icons::CHECKBOX_ON,
         ItemStatus::Disabled => Icons::CHECKBOX_OFF,
+        ItemStatus::Pending => Icons::CHECKBOX_ONCE,
+        ItemStatus::DisabledByPolicy => Icons::CHECKBOX_LOCKED,
+        ItemStatus::DisabledButTriggered => Icons::CHECKBOX_TRIGGERED,
         ItemStatus::Unknown => Icons::CHECKBOX_UNKNOWN,
     };
 
@@ -161,5 +164,19 @@ fn get_source_icon(source: &SourceType) -> &'static str {
         SourceType::StartupFolderUser | SourceType::StartupFolderAllUsers => Icons::FOLDER,
         SourceType::ScheduledTask => Icons::TASK,
         SourceType::WindowsService => Icons::SERVICE,
+        SourceType::PowerShellProfileAllUsers
+        | SourceType::PowerShellProfileCurrentUser
+        | SourceType::LogonScript => Icons::SCRIPT,
+        SourceType::Miscellaneous => Icons::REGISTRY,
+        SourceType::Wsl => Icons::LINUX,
+        SourceType::XdgAutostartUser | SourceType::XdgAutostartSystem => Icons::FOLDER,
+        SourceType::SystemdSystem | SourceType::SystemdUser => Icons::SERVICE,
+        SourceType::CronReboot => Icons::TASK,
+        SourceType::RcLocal => Icons::SCRIPT,
+        SourceType::LaunchAgentsUser
+        | SourceType::LaunchAgentsSystem
+        | SourceType::LaunchDaemons => Icons::SERVICE,
+        SourceType::LoginItems => Icons::FOLDER,
+        SourceType::ShellStartupUser | SourceType::ShellStartupSystem => Icons::SCRIPT,
     }
 }
@@ -1,7 +1,77 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    text::{Line, Span},
+    widgets::ListItem,
+};
+
+use crate::operations::BackupEntry;
+use crate::ui::{Icons, Theme};
+
+mod add_item;
+mod confirm;
+mod delay;
+mod diff;
+mod edit_item;
 mod help;
 mod list;
+mod profiles;
+mod quarantine;
+mod restore;
 mod status_bar;
 
+pub use add_item::render_add_item;
+pub use confirm::render_confirm;
+pub use delay::render_delay;
+pub use diff::render_diff;
+pub use edit_item::render_edit_item;
 pub use help::render_help;
 pub use list::render_list;
+pub use profiles::render_profiles;
+pub use quarantine::render_quarantine;
+pub use restore::render_restore;
 pub use status_bar::render_status_bar;
+
+/// A popup area of the given size, centered in `r`
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+/// One row per backup: time, item count and description
+fn backup_list_items(backups: &[BackupEntry]) -> Vec<ListItem<'static>> {
+    backups
+        .iter()
+        .map(|backup| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(" {} ", backup.timestamp.format("%Y-%m-%d %H:%M:%S")),
+                    Theme::detail_value(),
+                ),
+                Span::styled(
+                    format!(
+                        " {}  {} items  {}",
+                        Icons::DOT,
+                        backup.item_count,
+                        backup.description.as_deref().unwrap_or_default()
+                    ),
+                    Theme::detail_muted(),
+                ),
+            ]))
+        })
+        .collect()
+}
@@ -0,0 +1,115 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::models::ItemStatus;
+use crate::ui::{AppState, Icons, Theme};
+
+pub fn render_profiles(frame: &mut Frame, area: Rect, state: &mut AppState) {
+    let Some(view) = state.profiles.as_mut() else {
+        return;
+    };
+
+    let popup_area = centered_rect(75, 70, area);
+    frame.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(3),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .split(popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(Theme::border_focused())
+        .title(format!(" {} Profiles ", Icons::LOGO))
+        .title_style(Theme::header_accent());
+
+    let items: Vec<ListItem> = view
+        .profiles
+        .iter()
+        .map(|profile| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!(" {}", profile.name), Theme::detail_value()),
+                Span::styled(
+                    format!(
+                        "  {} items  {}  saved {}",
+                        profile.items.len(),
+                        Icons::DOT,
+                        profile.saved_at.format("%Y-%m-%d %H:%M:%S")
+                    ),
+                    Theme::detail_muted(),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Theme::item_selected());
+    frame.render_stateful_widget(list, chunks[0], &mut view.list_state);
+
+    // What switching changes, or the name being typed
+    let mut lines = Vec::new();
+    if let Some(name) = &view.new_name {
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {}", Icons::ARROW_RIGHT), Theme::header_accent()),
+            Span::styled(format!("{:<10}", "Save as"), Theme::detail_label()),
+            Span::styled(format!("{name}▏"), Theme::item_selected()),
+        ]));
+    } else if view.selected_profile().is_some() {
+        let plan_line = |label: &str, names: Vec<&str>| {
+            let names = if names.is_empty() {
+                "nothing".to_string()
+            } else {
+                names.join(", ")
+            };
+            Line::from(vec![
+                Span::styled(format!("  {label:<16}"), Theme::detail_label()),
+                Span::styled(names, Theme::detail_value()),
+            ])
+        };
+        lines.push(plan_line("Enables", view.plan.names(ItemStatus::Enabled)));
+        lines.push(plan_line("Disables", view.plan.names(ItemStatus::Disabled)));
+        if !view.plan.missing.is_empty() {
+            let missing = view.plan.missing.iter().map(|e| e.name.as_str()).collect();
+            lines.push(plan_line("No longer exist", missing));
+        }
+    }
+
+    if let Some(error) = &view.error {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("  {} {}", Icons::CROSS, error),
+            Theme::error(),
+        )));
+    }
+
+    let preview = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_set(border::ROUNDED)
+                .border_style(Theme::border()),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(preview, chunks[1]);
+
+    let help = if view.new_name.is_some() {
+        "  Enter save  ·  Esc cancel"
+    } else {
+        "  Enter switch  ·  s save current  ·  d delete  ·  Esc close"
+    };
+    let footer = Paragraph::new(Line::from(Span::styled(help, Theme::status_description())))
+        .style(Theme::status_bar());
+    frame.render_widget(footer, chunks[2]);
+}
@@ -0,0 +1,66 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use super::centered_rect;
+use crate::ui::{AppState, Icons, Theme};
+
+pub fn render_quarantine(frame: &mut Frame, area: Rect, state: &mut AppState) {
+    let Some(view) = state.quarantine.as_mut() else {
+        return;
+    };
+
+    let popup_area = centered_rect(75, 70, area);
+    frame.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(popup_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(Theme::border_focused())
+        .title(format!(" {} Quarantine ", Icons::LOGO))
+        .title_style(Theme::header_accent());
+
+    let items: Vec<ListItem> = view
+        .entries
+        .iter()
+        .map(|entry| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(" {} ", entry.deleted_at.format("%Y-%m-%d %H:%M:%S")),
+                    Theme::detail_muted(),
+                ),
+                Span::styled(format!(" {}", entry.item.name), Theme::detail_value()),
+                Span::styled(
+                    format!(
+                        "  {}  {}  {}",
+                        entry.item.source.short_name(),
+                        Icons::DOT,
+                        entry.raw.kind()
+                    ),
+                    Theme::detail_muted(),
+                ),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Theme::item_selected());
+    frame.render_stateful_widget(list, chunks[0], &mut view.list_state);
+
+    let footer = Paragraph::new(Line::from(Span::styled(
+        "  Enter restore  ·  p purge  ·  Esc close",
+        Theme::status_description(),
+    )))
+    .style(Theme::status_bar());
+    frame.render_widget(footer, chunks[1]);
+}
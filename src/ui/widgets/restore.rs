@@ -0,0 +1,130 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    symbols::border,
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use super::{backup_list_items, centered_rect};
+use crate::operations::RestoreAction;
+use crate::ui::{AppState, Icons, Theme};
+
+pub fn render_restore(frame: &mut Frame, area: Rect, state: &mut AppState) {
+    let Some(view) = state.restore.as_mut() else {
+        return;
+    };
+
+    let popup_area = centered_rect(75, 70, area);
+    frame.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(popup_area);
+
+    let (title, hint) = if view.results.is_some() {
+        (" Restore Results ", "  Enter/Esc close")
+    } else if view.plan.is_some() {
+        (
+            " Restore Plan ",
+            "  Space select  ·  * select all/none  ·  Enter apply  ·  Esc back",
+        )
+    } else {
+        (" Restore From Backup ", "  Enter preview  ·  Esc close")
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_set(border::ROUNDED)
+        .border_style(Theme::border_focused())
+        .title(format!(" {}{}", Icons::LOGO, title))
+        .title_style(Theme::header_accent());
+
+    if let Some(results) = &view.results {
+        let items: Vec<ListItem> = results
+            .iter()
+            .map(|result| {
+                let (icon, style) = if result.succeeded() {
+                    (Icons::CHECK, Theme::success())
+                } else {
+                    (Icons::CROSS, Theme::error())
+                };
+                let mut spans = vec![
+                    Span::styled(format!(" {icon} "), style),
+                    Span::styled(
+                        format!("{:<9}", result.action.display()),
+                        Theme::detail_label(),
+                    ),
+                    Span::styled(result.name.clone(), Theme::detail_value()),
+                ];
+                if let Some(error) = &result.error {
+                    spans.push(Span::styled(format!("  {error}"), Theme::detail_muted()));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        frame.render_widget(List::new(items).block(block), chunks[0]);
+    } else if let Some(plan) = &view.plan {
+        if plan.is_empty() {
+            let message = Paragraph::new(Line::from(Span::styled(
+                format!(
+                    "  {} Nothing to restore, the items match the backup",
+                    Icons::INFO
+                ),
+                Theme::detail_muted(),
+            )))
+            .block(block);
+            frame.render_widget(message, chunks[0]);
+        } else {
+            let items: Vec<ListItem> = plan
+                .steps
+                .iter()
+                .map(|step| {
+                    let checkbox = if step.selected {
+                        Icons::CHECKBOX_ON
+                    } else {
+                        Icons::CHECKBOX_OFF
+                    };
+                    let action_style = match step.action {
+                        RestoreAction::Enable => Theme::item_enabled(),
+                        RestoreAction::Disable => Theme::item_disabled(),
+                        RestoreAction::Recreate => Theme::item_pending(),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!(" {checkbox}  "), Theme::checkbox_enabled()),
+                        Span::styled(format!("{:<9}", step.action.display()), action_style),
+                        Span::styled(step.item.name.clone(), Theme::detail_value()),
+                        Span::styled(
+                            format!(
+                                "  {}  {}  {}",
+                                step.item.source.short_name(),
+                                Icons::DOT,
+                                step.summary()
+                            ),
+                            Theme::detail_muted(),
+                        ),
+                    ]))
+                })
+                .collect();
+            let block = block.title_bottom(format!(
+                " {} of {} selected ",
+                plan.selected_count(),
+                plan.len()
+            ));
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(Theme::item_selected());
+            frame.render_stateful_widget(list, chunks[0], &mut view.plan_state);
+        }
+    } else {
+        let list = List::new(backup_list_items(&view.backups))
+            .block(block)
+            .highlight_style(Theme::item_selected());
+        frame.render_stateful_widget(list, chunks[0], &mut view.backup_state);
+    }
+
+    let footer = Paragraph::new(Line::from(Span::styled(hint, Theme::status_description())))
+        .style(Theme::status_bar());
+    frame.render_widget(footer, chunks[1]);
+}
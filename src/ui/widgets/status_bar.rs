@@ -26,7 +26,7 @@ pub fn render_status_bar(frame: &mut Frame, area: Rect, state: &AppState) {
     }
 
     // Modern pill-style keybindings
-    let bindings = vec![
+    let bindings = [
         ("Space", "Toggle"),
         ("Tab", "Expand"),
         ("a", "Apply"),